@@ -0,0 +1,103 @@
+use std::{cell::RefCell, rc::Rc};
+
+use super::sound::{SoundDriver, FREQUENCY};
+
+pub const Z80_CLOCK: f32 = 3579545.0;
+
+// Samples the sound driver should have queued
+const TARGET_QUEUED: usize = FREQUENCY as usize / 20;
+// Largest change of the output rate to keep the queue at its target
+const MAX_RATE_ADJUST: f32 = 0.005;
+// Samples collected before they are handed to the driver
+const BATCH_SIZE: usize = 128;
+
+// Sound chip stepped by Z80 cycles
+pub trait SoundChip {
+    // Runs the chip, accumulating its output
+    fn advance(&mut self, cycles: u32);
+    // Average output since the last call, between -1 and 1
+    fn take_sample(&mut self) -> f32;
+}
+
+// Turns elapsed Z80 cycles into samples for the sound driver
+pub struct AudioMixer {
+    sound: Rc<RefCell<dyn SoundDriver>>,
+    cycles_per_sample: f32,
+    // Cycles left until the next sample
+    next_sample: f32,
+    // Cycles run by the CPU, not by the chips yet
+    pending: u32,
+    samples: Vec<f32>,
+    // DC blocker state
+    last_input: f32,
+    last_output: f32,
+}
+
+impl AudioMixer {
+    pub fn new(sound: Rc<RefCell<dyn SoundDriver>>) -> Self {
+        let cycles_per_sample = Z80_CLOCK / FREQUENCY as f32;
+        let _ = sound.borrow_mut().play();
+        Self {
+            sound,
+            cycles_per_sample,
+            next_sample: cycles_per_sample,
+            pending: 0,
+            samples: Vec::with_capacity(BATCH_SIZE),
+            last_input: 0.0,
+            last_output: 0.0,
+        }
+    }
+
+    pub fn get_sound(&self) -> Rc<RefCell<dyn SoundDriver>> {
+        self.sound.clone()
+    }
+
+    // Counts cycles run by the CPU. The chips only catch up once a
+    // sample is due, or before a register write through `flush`.
+    pub fn run(&mut self, cycles: u32, chips: &mut [&mut dyn SoundChip]) {
+        self.pending += cycles;
+        if self.pending as f32 >= self.next_sample {
+            self.flush(chips);
+        }
+    }
+
+    // Brings the chips up to the current cycle
+    pub fn flush(&mut self, chips: &mut [&mut dyn SoundChip]) {
+        while self.pending > 0 {
+            let step = self.pending.min(self.next_sample.ceil() as u32);
+            for chip in chips.iter_mut() {
+                chip.advance(step);
+            }
+            self.pending -= step;
+            self.next_sample -= step as f32;
+            if self.next_sample <= 0.0 {
+                let input = chips.iter_mut().map(|chip| chip.take_sample()).sum::<f32>();
+                self.push_sample(input);
+                self.next_sample += self.cycles_per_sample;
+            }
+        }
+    }
+
+    fn push_sample(&mut self, input: f32) {
+        // Removes the DC offset of the PSG output
+        let output = input - self.last_input + 0.995 * self.last_output;
+        self.last_input = input;
+        self.last_output = output;
+        self.samples.push(output.clamp(-1.0, 1.0));
+        if self.samples.len() >= BATCH_SIZE {
+            self.queue_samples();
+        }
+    }
+
+    // Hands the samples to the driver and adjusts the rate so its queue
+    // stays around TARGET_QUEUED, instead of running dry or overflowing
+    fn queue_samples(&mut self) {
+        let mut sound = self.sound.borrow_mut();
+        let _ = sound.queue_samples(&self.samples);
+        self.samples.clear();
+
+        let error = (sound.queued() as f32 - TARGET_QUEUED as f32) / TARGET_QUEUED as f32;
+        let ratio = 1.0 + MAX_RATE_ADJUST * error.clamp(-1.0, 1.0);
+        self.cycles_per_sample = ratio * Z80_CLOCK / FREQUENCY as f32;
+    }
+}
@@ -0,0 +1,254 @@
+/*
+
+    AY-3-8910 sound generator
+
+    Registers:
+
+    0-5     tone period of channels A-C (12 bits, low byte first)
+    6       noise period (5 bits)
+    7       mixer: bits 0-2 disable tone, bits 3-5 disable noise (active low)
+    8-10    amplitude of channels A-C (4 bits), bit 4 selects the envelope
+    11-12   envelope period (16 bits, low byte first)
+    13      envelope shape: CONTINUE, ATTACK, ALTERNATE, HOLD
+
+    The chip runs at half the Z80 clock and its counters tick every 8 of
+    its clocks, i.e. every 16 Z80 cycles.
+
+*/
+
+use std::f32::consts::FRAC_1_SQRT_2;
+
+use super::audio_mixer::SoundChip;
+
+pub const AY_CLOCK: f32 = 1789772.5;
+// Z80 cycles between two ticks of the counters
+pub const CYCLES_PER_TICK: u32 = 16;
+
+// Output level of each amplitude, 3dB per step
+const VOLUMES: [f32; 16] = [
+    0.0,
+    0.0078,
+    0.0110,
+    0.0156,
+    0.0221,
+    0.0312,
+    0.0442,
+    0.0625,
+    0.0884,
+    0.1250,
+    0.1768,
+    0.2500,
+    0.3536,
+    0.5000,
+    FRAC_1_SQRT_2,
+    1.0,
+];
+
+// Bits implemented by every register
+const REGISTER_MASKS: [u8; 14] = [
+    0xff, 0x0f, 0xff, 0x0f, 0xff, 0x0f, 0x1f, 0xff, 0x1f, 0x1f, 0x1f, 0xff, 0xff, 0x0f,
+];
+
+pub struct AY8910 {
+    registers: [u8; 14],
+    tone_counters: [u16; 3],
+    tone_outputs: [bool; 3],
+    noise_counter: u16,
+    // 17 bit LFSR, bit 0 is the output
+    noise_shift: u32,
+    noise_prescaler: bool,
+    env_counter: u32,
+    env_step: i8,
+    env_attack: u8,
+    env_hold: bool,
+    env_alternate: bool,
+    env_holding: bool,
+    env_volume: u8,
+    // Z80 cycles since the last tick
+    phase: u32,
+    output: f32,
+    // Output integrated over the cycles since the last sample
+    output_sum: f32,
+    output_cycles: u32,
+}
+
+impl Default for AY8910 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AY8910 {
+    pub fn new() -> Self {
+        let mut ay = Self {
+            registers: [0; 14],
+            tone_counters: [0; 3],
+            tone_outputs: [false; 3],
+            noise_counter: 0,
+            noise_shift: 1,
+            noise_prescaler: false,
+            env_counter: 0,
+            env_step: 0,
+            env_attack: 0,
+            env_hold: false,
+            env_alternate: false,
+            env_holding: false,
+            env_volume: 0,
+            phase: 0,
+            output: 0.0,
+            output_sum: 0.0,
+            output_cycles: 0,
+        };
+        ay.registers[7] = 0xff;
+        ay.reset_envelope();
+        ay
+    }
+
+    pub fn write_register(&mut self, reg: usize, value: u8) {
+        if reg >= self.registers.len() {
+            return;
+        }
+        self.registers[reg] = value & REGISTER_MASKS[reg];
+        if reg == 13 {
+            self.reset_envelope();
+        }
+        self.output = self.mixed_output();
+    }
+
+    pub fn read_register(&self, reg: usize) -> u8 {
+        self.registers[reg]
+    }
+
+    // Amplitude the envelope generator is outputting (0-15)
+    pub fn get_envelope_volume(&self) -> u8 {
+        self.env_volume
+    }
+
+    fn tone_period(&self, chn: usize) -> u16 {
+        let period = ((self.registers[chn * 2 + 1] as u16) << 8) | self.registers[chn * 2] as u16;
+        period.max(1)
+    }
+
+    fn noise_period(&self) -> u16 {
+        (self.registers[6] as u16).max(1)
+    }
+
+    fn env_period(&self) -> u32 {
+        (((self.registers[12] as u32) << 8) | self.registers[11] as u32).max(1)
+    }
+
+    // Writing register 13 restarts the envelope
+    fn reset_envelope(&mut self) {
+        let shape = self.registers[13];
+        self.env_attack = if shape & 0x04 != 0 { 0x0f } else { 0x00 };
+        if shape & 0x08 == 0 {
+            // Without CONTINUE the shape ends at 0, like HOLD after ALTERNATE
+            // from an attack or HOLD after a decay
+            self.env_hold = true;
+            self.env_alternate = self.env_attack != 0;
+        } else {
+            self.env_hold = shape & 0x01 != 0;
+            self.env_alternate = shape & 0x02 != 0;
+        }
+        self.env_counter = 0;
+        self.env_step = 15;
+        self.env_holding = false;
+        self.env_volume = self.env_step as u8 ^ self.env_attack;
+    }
+
+    // Advances the chip by 8 clocks
+    fn tick(&mut self) {
+        for chn in 0..3 {
+            self.tone_counters[chn] += 1;
+            if self.tone_counters[chn] >= self.tone_period(chn) {
+                self.tone_counters[chn] = 0;
+                self.tone_outputs[chn] = !self.tone_outputs[chn];
+            }
+        }
+
+        // The noise generator runs at half the rate of the tone generators
+        self.noise_prescaler = !self.noise_prescaler;
+        if self.noise_prescaler {
+            self.noise_counter += 1;
+            if self.noise_counter >= self.noise_period() {
+                self.noise_counter = 0;
+                let bit = (self.noise_shift ^ (self.noise_shift >> 3)) & 0x01;
+                self.noise_shift = (self.noise_shift >> 1) | (bit << 16);
+            }
+        }
+
+        // Each one of the 16 envelope steps lasts 16 * period clocks
+        self.env_counter += 1;
+        if self.env_counter >= self.env_period() * 2 {
+            self.env_counter = 0;
+            self.step_envelope();
+        }
+    }
+
+    fn step_envelope(&mut self) {
+        if self.env_holding {
+            return;
+        }
+        self.env_step -= 1;
+        if self.env_step < 0 {
+            if self.env_alternate {
+                self.env_attack ^= 0x0f;
+            }
+            if self.env_hold {
+                self.env_holding = true;
+                self.env_step = 0;
+            } else {
+                self.env_step &= 0x0f;
+            }
+        }
+        self.env_volume = self.env_step as u8 ^ self.env_attack;
+    }
+
+    // Current output of a channel, between 0 and 1
+    fn channel_output(&self, chn: usize) -> f32 {
+        let mixer = self.registers[7];
+        let tone = self.tone_outputs[chn] || mixer & (0x01 << chn) != 0;
+        let noise = self.noise_shift & 0x01 != 0 || mixer & (0x08 << chn) != 0;
+        if !(tone && noise) {
+            return 0.0;
+        }
+        let amplitude = self.registers[8 + chn];
+        if amplitude & 0x10 != 0 {
+            VOLUMES[self.env_volume as usize]
+        } else {
+            VOLUMES[amplitude as usize & 0x0f]
+        }
+    }
+
+    fn mixed_output(&self) -> f32 {
+        (0..3).map(|chn| self.channel_output(chn)).sum::<f32>() / 3.0
+    }
+}
+
+impl SoundChip for AY8910 {
+    fn advance(&mut self, cycles: u32) {
+        let mut cycles = cycles;
+        while cycles > 0 {
+            let run = cycles.min(CYCLES_PER_TICK - self.phase);
+            self.output_sum += self.output * run as f32;
+            self.output_cycles += run;
+            self.phase += run;
+            cycles -= run;
+            if self.phase == CYCLES_PER_TICK {
+                self.phase = 0;
+                self.tick();
+                self.output = self.mixed_output();
+            }
+        }
+    }
+
+    fn take_sample(&mut self) -> f32 {
+        if self.output_cycles == 0 {
+            return self.output;
+        }
+        let sample = self.output_sum / self.output_cycles as f32;
+        self.output_sum = 0.0;
+        self.output_cycles = 0;
+        sample
+    }
+}
@@ -1,9 +1,13 @@
+use std::{cell::RefCell, rc::Rc};
+
 use sha1::{Digest, Sha1};
 
-use crate::libs::rom_database::search_in_rom_database;
+use crate::libs::rom_database::RomDatabase;
 
 use super::memory::Mapper;
+use super::scc::SCC;
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum CartType {
     NORMAL = 0,
     UNKNOWN,
@@ -12,34 +16,243 @@ pub enum CartType {
     ASCII8KB,
     ASCII16KB,
     RTYPE,
+    ASCII8SRAM2,
+    ASCII8SRAM8,
+    ASCII16SRAM2,
+    ASCII16SRAM8,
+    KOEISRAM8,
+    KOEISRAM32,
+    GAMEMASTER2,
+}
+
+impl CartType {
+    // Accepts both the softwaredb.xml type names and the --mtype names
+    pub fn from_name(name: &str) -> Option<CartType> {
+        match name {
+            "NORMAL" | "Normal" => Some(CartType::NORMAL),
+            "Konami" | "KONAMI4" => Some(CartType::KONAMI4),
+            "KonamiSCC" | "KONAMI5" => Some(CartType::KONAMI5),
+            "ASCII8" | "ASCII8KB" => Some(CartType::ASCII8KB),
+            "ASCII16" | "ASCII16KB" => Some(CartType::ASCII16KB),
+            "RType" | "R-Type" | "RTYPE" => Some(CartType::RTYPE),
+            "ASCII8SRAM2" => Some(CartType::ASCII8SRAM2),
+            "ASCII8SRAM8" => Some(CartType::ASCII8SRAM8),
+            "ASCII16SRAM2" => Some(CartType::ASCII16SRAM2),
+            "ASCII16SRAM8" => Some(CartType::ASCII16SRAM8),
+            "KoeiSRAM8" | "KOEISRAM8" => Some(CartType::KOEISRAM8),
+            "KoeiSRAM32" | "KOEISRAM32" => Some(CartType::KOEISRAM32),
+            "GameMaster2" | "GAMEMASTER2" => Some(CartType::GAMEMASTER2),
+            _ => None,
+        }
+    }
 }
 
-pub fn get_cart_type(data: &[u8]) -> CartType {
+pub fn get_rom_hash(data: &[u8]) -> String {
     let mut hasher = Sha1::new();
     hasher.update(data);
-    let result = hasher.finalize();
-    let hash = format!("{:016x}", result);
+    format!("{:016x}", hasher.finalize())
+}
+
+pub fn get_cart_type(data: &[u8], rom_database: &RomDatabase) -> CartType {
+    let hash = get_rom_hash(data);
     log::info!("Hash: {}", hash);
-    if let Ok(str) = search_in_rom_database(&hash) {
-        match str.as_str() {
-            "NORMAL" => CartType::NORMAL,
-            "Konami" => CartType::KONAMI4,
-            "KonamiSCC" => CartType::KONAMI5,
-            "ASCII8" => CartType::ASCII8KB,
-            _ => {
-                log::info!("Rom {} not supported\n", str);
-                unimplemented!()
-            }
+    if let Some(entry) = rom_database.find(&hash) {
+        log::info!("Found {} ({}, {})", entry.title, entry.company, entry.year);
+        if let Some(cart_type) = CartType::from_name(entry.mapper.as_str()) {
+            return cart_type;
         }
+        log::warn!("Mapper {} is not supported, guessing another", entry.mapper);
+    } else {
+        log::warn!("Rom hash {} not found in the database", hash);
+    }
+    let (cart_type, confidence) = guess_cart_type(data);
+    log::info!(
+        "Guessed cartridge type {:?} (confidence {:.0}%)",
+        cart_type,
+        confidence * 100.0
+    );
+    cart_type
+}
+
+// Number of banks of a ROM, which has to fill one bank at least
+fn count_banks(data: &[u8], bank_size: usize) -> Result<usize, String> {
+    if data.len() < bank_size {
+        return Err(format!(
+            "ROM of {} bytes is smaller than a {}KB bank",
+            data.len(),
+            bank_size / 1024
+        ));
+    }
+    Ok(data.len() / bank_size)
+}
+
+// Cartridge header: "AB" followed by the INIT, STATEMENT, DEVICE and TEXT
+// addresses. Found at the beginning of the ROM, or 16KB further for ROMs
+// that also fill page 0.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RomHeader {
+    // Offset of the header inside the ROM image
+    pub offset: usize,
+    pub init: u16,
+    pub statement: u16,
+    pub device: u16,
+    pub text: u16,
+}
+
+impl RomHeader {
+    pub fn parse(data: &[u8]) -> Option<RomHeader> {
+        [0, 0x4000].iter().find_map(|&offset| {
+            let h = data.get(offset..offset + 10)?;
+            if &h[0..2] != b"AB" {
+                return None;
+            }
+            let word = |i: usize| u16::from_le_bytes([h[i], h[i + 1]]);
+            Some(RomHeader {
+                offset,
+                init: word(2),
+                statement: word(4),
+                device: word(6),
+                text: word(8),
+            })
+        })
+    }
+
+    // BASIC program ROMs have no INIT routine, only TEXT
+    pub fn is_basic(&self) -> bool {
+        self.init == 0 && self.text != 0
+    }
+}
+
+// Address where the first byte of a plain ROM is mapped. `hint` is the
+// <start> of the database entry, if any.
+pub fn get_rom_start(data: &[u8], hint: Option<u16>) -> u16 {
+    if let Some(start) = hint {
+        return start & 0xc000;
+    }
+    let size = data.len();
+    let start = match RomHeader::parse(data) {
+        // Header in the second 16KB: the ROM also fills page 0
+        Some(header) if header.offset != 0 => 0x0000,
+        Some(header) if header.is_basic() => header.text & 0xc000,
+        // INIT usually points inside the ROM when it starts at page 1
+        Some(header) if (0x4000..0x4000 + size).contains(&(header.init as usize)) => 0x4000,
+        Some(header) if header.init != 0 => header.init & 0xc000,
+        Some(_) => 0x4000,
+        None if size > 0x8000 => 0x0000,
+        None => 0x4000,
+    };
+    // It has to fit below 0x10000
+    if start as usize + size > 0x10000 {
+        0x0000
     } else {
-        log::error!("Rom hash {} not supported\n", hash);
-        unreachable!()
+        start
+    }
+}
+
+// Builds the 64KB view of a plain ROM mapped at `start`. ROMs up to 32KB
+// are mirrored over the whole slot, as the cartridges do not decode the
+// upper address lines. Addresses without ROM read 0xFF. Returns the image
+// and which pages are in use.
+pub fn get_rom_image(data: &[u8], start: u16) -> (Vec<u8>, [bool; 4]) {
+    let mut image = vec![0xff; 0x10000];
+    let mut pages = [false; 4];
+    let size = data.len().min(0x10000);
+    if size == 0 {
+        return (image, pages);
+    }
+    // Odd sizes are padded to 8KB boundaries
+    let padded = (size + 0x1fff) & !0x1fff;
+    let mirrored = padded <= 0x8000;
+    let window = if mirrored {
+        padded.next_power_of_two()
+    } else {
+        padded
+    };
+    for (address, byte) in image.iter_mut().enumerate() {
+        let offset = (address + 0x10000 - start as usize) % 0x10000;
+        let offset = if mirrored {
+            offset % window
+        } else if offset < window {
+            offset
+        } else {
+            continue;
+        };
+        pages[address >> 14] = true;
+        if offset < size {
+            *byte = data[offset];
+        }
     }
+    (image, pages)
+}
+
+// Guesses the mapper of a ROM that is not in the database by counting
+// the LD (nnnn),A instructions that target bank switching registers.
+// Returns the type and a confidence between 0 and 1.
+pub fn guess_cart_type(data: &[u8]) -> (CartType, f32) {
+    let has_header = data.len() >= 2 && &data[0..2] == b"AB";
+
+    // Plain ROMs fit in the address space without a mapper
+    if data.len() <= 0x10000 {
+        let confidence = if data.len() <= 0x8000 { 1.0 } else { 0.75 };
+        return (CartType::NORMAL, confidence);
+    }
+
+    let mut konami4: usize = 0;
+    let mut konami5: usize = 0;
+    let mut ascii8: usize = 0;
+    let mut ascii16: usize = 0;
+    for i in 0..data.len() - 2 {
+        if data[i] != 0x32 {
+            continue;
+        }
+        let address = u16::from_le_bytes([data[i + 1], data[i + 2]]);
+        match address {
+            0x5000 | 0x9000 | 0xb000 => konami5 += 1,
+            0x4000 | 0x8000 | 0xa000 => konami4 += 1,
+            0x6800 | 0x7800 => ascii8 += 1,
+            0x6000 => {
+                konami4 += 1;
+                ascii8 += 1;
+                ascii16 += 1;
+            }
+            0x7000 => {
+                konami5 += 1;
+                ascii8 += 1;
+                ascii16 += 1;
+            }
+            0x77ff => ascii16 += 1,
+            _ => {}
+        }
+    }
+    // 0x6000 and 0x7000 are shared, so one hit alone does not make ASCII8
+    ascii8 = ascii8.saturating_sub(1);
+
+    // In case of a tie, the first one wins
+    let votes = [
+        (CartType::ASCII16KB, ascii16),
+        (CartType::KONAMI5, konami5),
+        (CartType::KONAMI4, konami4),
+        (CartType::ASCII8KB, ascii8),
+    ];
+    let total: usize = votes.iter().map(|(_, n)| n).sum();
+    let (cart_type, best) = votes
+        .iter()
+        .fold(votes[0], |acc, v| if v.1 > acc.1 { *v } else { acc });
+    if total == 0 {
+        // No bank switching found
+        return (CartType::ASCII16KB, 0.0);
+    }
+    let mut confidence = best as f32 / total as f32;
+    if !has_header {
+        confidence /= 2.0;
+    }
+    (cart_type, confidence)
 }
 
 #[derive(Clone)]
 pub struct MapperKonami4 {
     contents: Vec<u8>,
+    num_banks: usize,
     sels: [usize; 4],
 }
 
@@ -53,12 +266,15 @@ impl MapperKonami4 {
     pub fn new() -> Self {
         Self {
             contents: vec![0_u8],
+            num_banks: 0,
             sels: [0; 4],
         }
     }
-    pub fn init(&mut self, data: &[u8]) {
+    pub fn init(&mut self, data: &[u8]) -> Result<(), String> {
         self.contents = data.to_vec();
-        self.sels = [0, 1, 2, 3];
+        self.num_banks = count_banks(data, 0x2000)?;
+        self.sels = [0, 1, 2, 3].map(|bank| bank % self.num_banks);
+        Ok(())
     }
 }
 
@@ -79,16 +295,18 @@ impl Mapper for MapperKonami4 {
         if place == 0 {
             return;
         }
-        self.sels[place as usize] = value as usize;
+        self.sels[place as usize] = value as usize % self.num_banks;
     }
 }
 
 #[derive(Clone)]
 pub struct MapperKonami5 {
     contents: Vec<u8>,
-    num_banks: isize,
+    num_banks: usize,
     sels: [usize; 4],
-    scc: [u8; 0x800],
+    // 0x3F in the third bank register, before masking
+    scc_enabled: bool,
+    scc: Rc<RefCell<SCC>>,
 }
 
 impl Default for MapperKonami5 {
@@ -103,13 +321,15 @@ impl MapperKonami5 {
             contents: vec![0_u8],
             num_banks: 0,
             sels: [0; 4],
-            scc: [0; 0x800],
+            scc_enabled: false,
+            scc: Rc::new(RefCell::new(SCC::new())),
         }
     }
-    pub fn init(&mut self, data: &[u8]) {
+    pub fn init(&mut self, data: &[u8]) -> Result<(), String> {
         self.contents = data.to_vec();
-        self.num_banks = (data.len() / 8192) as isize;
-        self.sels = [0, 1, 2, 3];
+        self.num_banks = count_banks(data, 0x2000)?;
+        self.sels = [0, 1, 2, 3].map(|bank| bank % self.num_banks);
+        Ok(())
     }
 }
 
@@ -118,9 +338,9 @@ impl Mapper for MapperKonami5 {
         false
     }
     fn read_byte(&self, mut address: u16) -> u8 {
-        if (self.sels[2] & 0x3f == 0x3f) && (0x9800..=0x9fff).contains(&address) {
+        if self.scc_enabled && (0x9800..=0x9fff).contains(&address) {
             // SCC Area
-            return self.scc[address as usize - 0x9800];
+            return self.scc.borrow().read_byte(address - 0x9800);
         }
         address -= 0x4000;
         let place = address / 0x2000;
@@ -129,48 +349,36 @@ impl Mapper for MapperKonami5 {
         real_mem[delta as usize]
     }
     fn write_byte(&mut self, address: u16, value: u8) {
-        if (self.sels[2] & 0x3f == 0x3f) && (0x9800..=0x9fff).contains(&address) {
+        if self.writes_sound(address) {
             // SCC AREA
-            unimplemented!();
-            // scc_write(address - 0x9800, value);
-            // self.scc[address-0x9800] = value
-            // return;
-        }
-
-        if (0x5000..=0x57ff).contains(&address) {
-            self.sels[0] = value.into();
-            return;
-        }
-
-        if (0x7000..=0x77ff).contains(&address) {
-            self.sels[1] = value.into();
-            return;
-        }
-
-        if (0x9000..=0x97ff).contains(&address) {
-            self.sels[2] = value.into();
+            self.scc.borrow_mut().write_byte(address - 0x9800, value);
             return;
         }
 
-        if (0xb000..=0xb7ff).contains(&address) {
-            self.sels[3] = value.into();
-            return;
-        }
-
-        // TODO: Fix for SCC...
-        let address = address - 0x4000;
-        let place = address / 0x2000;
-        if place == 0 {
-            return;
+        let place = match address {
+            0x5000..=0x57ff => 0,
+            0x7000..=0x77ff => 1,
+            0x9000..=0x97ff => 2,
+            0xb000..=0xb7ff => 3,
+            _ => return,
+        };
+        if place == 2 {
+            self.scc_enabled = value & 0x3f == 0x3f;
         }
-        self.sels[place as usize] = value as usize;
+        self.sels[place] = value as usize % self.num_banks;
+    }
+    fn get_scc(&self) -> Option<Rc<RefCell<SCC>>> {
+        Some(self.scc.clone())
+    }
+    fn writes_sound(&self, address: u16) -> bool {
+        self.scc_enabled && (0x9800..=0x9fff).contains(&address)
     }
 }
 
 #[derive(Clone)]
 pub struct MapperASCII8 {
     contents: Vec<u8>,
-    num_banks: isize,
+    num_banks: usize,
     sels: [usize; 4],
 }
 
@@ -188,10 +396,11 @@ impl MapperASCII8 {
             sels: [0; 4],
         }
     }
-    pub fn init(&mut self, data: &[u8]) {
+    pub fn init(&mut self, data: &[u8]) -> Result<(), String> {
         self.contents = data.to_vec();
-        self.num_banks = (data.len() / 8192) as isize;
-        self.sels = [0, 1, 2, 3];
+        self.num_banks = count_banks(data, 0x2000)?;
+        self.sels = [0, 1, 2, 3].map(|bank| bank % self.num_banks);
+        Ok(())
     }
 }
 
@@ -208,22 +417,374 @@ impl Mapper for MapperASCII8 {
     }
     fn write_byte(&mut self, address: u16, value: u8) {
         if (0x6000..=0x67ff).contains(&address) {
-            self.sels[0] = (value % self.num_banks as u8).into();
+            self.sels[0] = value as usize % self.num_banks;
             return;
         }
 
         if (0x6800..=0x6fff).contains(&address) {
-            self.sels[1] = (value % self.num_banks as u8).into();
+            self.sels[1] = value as usize % self.num_banks;
             return;
         }
 
         if (0x7000..=0x77ff).contains(&address) {
-            self.sels[2] = (value % self.num_banks as u8).into();
+            self.sels[2] = value as usize % self.num_banks;
             return;
         }
 
         if (0x7800..=0x7fff).contains(&address) {
-            self.sels[3] = (value % self.num_banks as u8).into();
+            self.sels[3] = value as usize % self.num_banks;
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct MapperASCII16 {
+    contents: Vec<u8>,
+    num_banks: usize,
+    sels: [usize; 2],
+}
+
+impl Default for MapperASCII16 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MapperASCII16 {
+    pub fn new() -> Self {
+        Self {
+            contents: vec![0_u8],
+            num_banks: 0,
+            sels: [0; 2],
+        }
+    }
+    pub fn init(&mut self, data: &[u8]) -> Result<(), String> {
+        self.contents = data.to_vec();
+        self.num_banks = count_banks(data, 0x4000)?;
+        self.sels = [0, 0];
+        Ok(())
+    }
+}
+
+impl Mapper for MapperASCII16 {
+    fn is_void(&self) -> bool {
+        false
+    }
+    fn read_byte(&self, mut address: u16) -> u8 {
+        address -= 0x4000;
+        let place = address / 0x4000;
+        let real_mem = &self.contents[self.sels[place as usize] * 0x4000_usize..];
+        let delta = address - 0x4000 * place;
+        real_mem[delta as usize]
+    }
+    fn write_byte(&mut self, address: u16, value: u8) {
+        if (0x6000..=0x67ff).contains(&address) {
+            self.sels[0] = value as usize % self.num_banks;
+            return;
+        }
+
+        if (0x7000..=0x77ff).contains(&address) {
+            self.sels[1] = value as usize % self.num_banks;
+        }
+    }
+}
+
+// R-Type: 0x4000-0x7FFF is fixed to bank 0x17, 0x8000-0xBFFF is
+// switched by writes to 0x7000-0x7FFF.
+#[derive(Clone)]
+pub struct MapperRType {
+    contents: Vec<u8>,
+    num_banks: usize,
+    sels: [usize; 2],
+}
+
+impl Default for MapperRType {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MapperRType {
+    pub fn new() -> Self {
+        Self {
+            contents: vec![0_u8],
+            num_banks: 0,
+            sels: [0; 2],
+        }
+    }
+    pub fn init(&mut self, data: &[u8]) -> Result<(), String> {
+        self.contents = data.to_vec();
+        self.num_banks = count_banks(data, 0x4000)?;
+        self.sels = [0x17 % self.num_banks, 0];
+        Ok(())
+    }
+}
+
+impl Mapper for MapperRType {
+    fn is_void(&self) -> bool {
+        false
+    }
+    fn read_byte(&self, mut address: u16) -> u8 {
+        address -= 0x4000;
+        let place = address / 0x4000;
+        let real_mem = &self.contents[self.sels[place as usize] * 0x4000_usize..];
+        let delta = address - 0x4000 * place;
+        real_mem[delta as usize]
+    }
+    fn write_byte(&mut self, address: u16, value: u8) {
+        if (0x7000..=0x7fff).contains(&address) {
+            // Banks 0x10-0x17 only have 3 valid low bits
+            let bank = if value & 0x10 != 0 {
+                value & 0x17
+            } else {
+                value & 0x0f
+            };
+            self.sels[1] = bank as usize % self.num_banks;
+        }
+    }
+}
+
+// ASCII8 with battery-backed SRAM. A bank register value with the
+// enable bit (the number of ROM banks) set maps SRAM into that page.
+#[derive(Clone)]
+pub struct MapperASCII8SRAM {
+    contents: Vec<u8>,
+    num_banks: usize,
+    sels: [usize; 4],
+    sram: Vec<u8>,
+    sram_sels: [usize; 4],
+    sram_enable_bit: u8,
+    // Pages showing SRAM (bit n = page at 0x4000 + n * 0x2000)
+    sram_mapped: u8,
+    // Pages where SRAM can be written
+    sram_writable: u8,
+}
+
+impl MapperASCII8SRAM {
+    pub fn new(sram_size: usize) -> Self {
+        Self {
+            contents: vec![0_u8],
+            num_banks: 0,
+            sels: [0; 4],
+            sram: vec![0xff; sram_size],
+            sram_sels: [0; 4],
+            sram_enable_bit: 0,
+            sram_mapped: 0,
+            sram_writable: 0b1100,
+        }
+    }
+    // Koei cartridges can also write SRAM at 0x4000-0x5FFF
+    pub fn new_koei(sram_size: usize) -> Self {
+        Self {
+            sram_writable: 0b1101,
+            ..Self::new(sram_size)
+        }
+    }
+    pub fn init(&mut self, data: &[u8]) -> Result<(), String> {
+        self.contents = data.to_vec();
+        self.num_banks = count_banks(data, 0x2000)?;
+        // No bit is left for the SRAM above 255 banks
+        self.sram_enable_bit = u8::try_from(self.num_banks).unwrap_or(0);
+        self.sels = [0, 0, 0, 0];
+        Ok(())
+    }
+}
+
+impl Mapper for MapperASCII8SRAM {
+    fn is_void(&self) -> bool {
+        false
+    }
+    fn read_byte(&self, mut address: u16) -> u8 {
+        address -= 0x4000;
+        let place = (address / 0x2000) as usize;
+        let delta = (address - 0x2000 * place as u16) as usize;
+        if self.sram_mapped & (1 << place) != 0 {
+            return self.sram[(self.sram_sels[place] * 0x2000 + delta) % self.sram.len()];
+        }
+        let real_mem = &self.contents[self.sels[place] * 0x2000_usize..];
+        real_mem[delta]
+    }
+    fn write_byte(&mut self, address: u16, value: u8) {
+        if (0x6000..=0x7fff).contains(&address) {
+            let place = ((address >> 11) & 0x03) as usize;
+            if value & self.sram_enable_bit != 0 {
+                let sram_banks = (self.sram.len() / 0x2000).max(1);
+                self.sram_sels[place] = value as usize & (sram_banks - 1);
+                self.sram_mapped |= 1 << place;
+            } else {
+                self.sels[place] = value as usize % self.num_banks;
+                self.sram_mapped &= !(1 << place);
+            }
+            return;
+        }
+
+        let place = ((address - 0x4000) / 0x2000) as usize;
+        if self.sram_mapped & self.sram_writable & (1 << place) != 0 {
+            let delta = (address as usize) & 0x1fff;
+            let idx = (self.sram_sels[place] * 0x2000 + delta) % self.sram.len();
+            self.sram[idx] = value;
+        }
+    }
+    fn get_sram(&self) -> Option<&[u8]> {
+        Some(&self.sram)
+    }
+    fn set_sram(&mut self, data: &[u8]) {
+        let n = data.len().min(self.sram.len());
+        self.sram[..n].copy_from_slice(&data[..n]);
+    }
+}
+
+// ASCII16 with battery-backed SRAM, writable at 0x8000-0xBFFF only.
+#[derive(Clone)]
+pub struct MapperASCII16SRAM {
+    contents: Vec<u8>,
+    num_banks: usize,
+    sels: [usize; 2],
+    sram: Vec<u8>,
+    sram_enable_bit: u8,
+    sram_mapped: [bool; 2],
+}
+
+impl MapperASCII16SRAM {
+    pub fn new(sram_size: usize) -> Self {
+        Self {
+            contents: vec![0_u8],
+            num_banks: 0,
+            sels: [0; 2],
+            sram: vec![0xff; sram_size],
+            sram_enable_bit: 0,
+            sram_mapped: [false; 2],
         }
     }
+    pub fn init(&mut self, data: &[u8]) -> Result<(), String> {
+        self.contents = data.to_vec();
+        self.num_banks = count_banks(data, 0x4000)?;
+        // 2KB boards decode bit 4, 8KB boards the bit above the last ROM bank
+        self.sram_enable_bit = if self.sram.len() <= 0x800 {
+            0x10
+        } else {
+            u8::try_from(self.num_banks).unwrap_or(0)
+        };
+        self.sels = [0, 0];
+        Ok(())
+    }
+}
+
+impl Mapper for MapperASCII16SRAM {
+    fn is_void(&self) -> bool {
+        false
+    }
+    fn read_byte(&self, mut address: u16) -> u8 {
+        address -= 0x4000;
+        let place = (address / 0x4000) as usize;
+        let delta = (address - 0x4000 * place as u16) as usize;
+        if self.sram_mapped[place] {
+            return self.sram[delta % self.sram.len()];
+        }
+        let real_mem = &self.contents[self.sels[place] * 0x4000_usize..];
+        real_mem[delta]
+    }
+    fn write_byte(&mut self, address: u16, value: u8) {
+        if (0x6000..=0x67ff).contains(&address) || (0x7000..=0x77ff).contains(&address) {
+            let place = ((address >> 12) & 0x01) as usize;
+            if value & self.sram_enable_bit != 0 {
+                self.sram_mapped[place] = true;
+            } else {
+                self.sels[place] = value as usize % self.num_banks;
+                self.sram_mapped[place] = false;
+            }
+            return;
+        }
+
+        if (0x8000..=0xbfff).contains(&address) && self.sram_mapped[1] {
+            let idx = (address as usize - 0x8000) % self.sram.len();
+            self.sram[idx] = value;
+        }
+    }
+    fn get_sram(&self) -> Option<&[u8]> {
+        Some(&self.sram)
+    }
+    fn set_sram(&mut self, data: &[u8]) {
+        let n = data.len().min(self.sram.len());
+        self.sram[..n].copy_from_slice(&data[..n]);
+    }
+}
+
+// Konami's Game Master 2: 8KB banks, 0x4000-0x5FFF fixed to bank 0.
+// Bit 4 of a bank register maps one of the two 4KB SRAM halves
+// (selected by bit 5), which is only writable at 0xB000-0xBFFF.
+#[derive(Clone)]
+pub struct MapperGameMaster2 {
+    contents: Vec<u8>,
+    num_banks: usize,
+    sels: [usize; 4],
+    sram: Vec<u8>,
+}
+
+impl Default for MapperGameMaster2 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MapperGameMaster2 {
+    pub fn new() -> Self {
+        Self {
+            contents: vec![0_u8],
+            num_banks: 0,
+            sels: [0; 4],
+            sram: vec![0xff; 0x2000],
+        }
+    }
+    pub fn init(&mut self, data: &[u8]) -> Result<(), String> {
+        self.contents = data.to_vec();
+        self.num_banks = count_banks(data, 0x2000)?;
+        self.sels = [0, 1, 2, 3].map(|bank| bank % self.num_banks);
+        Ok(())
+    }
+    fn sram_offset(&self, place: usize) -> Option<usize> {
+        let sel = self.sels[place];
+        if sel & 0x10 == 0 {
+            return None;
+        }
+        Some(if sel & 0x20 != 0 { 0x1000 } else { 0 })
+    }
+}
+
+impl Mapper for MapperGameMaster2 {
+    fn is_void(&self) -> bool {
+        false
+    }
+    fn read_byte(&self, mut address: u16) -> u8 {
+        address -= 0x4000;
+        let place = (address / 0x2000) as usize;
+        let delta = (address - 0x2000 * place as u16) as usize;
+        if let Some(offset) = self.sram_offset(place) {
+            return self.sram[offset + (delta & 0x0fff)];
+        }
+        let bank = (self.sels[place] & 0x0f) % self.num_banks;
+        self.contents[bank * 0x2000 + delta]
+    }
+    fn write_byte(&mut self, address: u16, value: u8) {
+        if !(0x6000..=0xbfff).contains(&address) {
+            return;
+        }
+        let place = ((address - 0x4000) / 0x2000) as usize;
+        if address & 0x1000 == 0 {
+            self.sels[place] = value as usize;
+            return;
+        }
+        if place == 3 {
+            if let Some(offset) = self.sram_offset(place) {
+                self.sram[offset + (address as usize & 0x0fff)] = value;
+            }
+        }
+    }
+    fn get_sram(&self) -> Option<&[u8]> {
+        Some(&self.sram)
+    }
+    fn set_sram(&mut self, data: &[u8]) {
+        let n = data.len().min(self.sram.len());
+        self.sram[..n].copy_from_slice(&data[..n]);
+    }
 }
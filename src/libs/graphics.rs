@@ -2,19 +2,24 @@ use std::{cell::RefCell, rc::Rc};
 
 use macroquad::prelude::*;
 
-use super::vdp::{SCREEN0, SCREEN1, SCREEN2};
+use super::palette::Palette;
+use super::vdp::{
+    MODE_MULTICOLOR_BITMAP, MODE_TEXT80, MODE_TEXT_BITMAP, MODE_TEXT_STRIPES, SCREEN0, SCREEN1,
+    SCREEN2, SCREEN3, SCREEN4, SCREEN5, SCREEN6, SCREEN7, SCREEN8,
+};
 
-const MSX_W1: u16 = 320;
 const MSX_W2: u16 = 256;
 const MSX_H: u16 = 192;
 const SCALE: f32 = 2.0;
-const WIN_W: f32 = MSX_W2 as f32 * SCALE * 1.5;
-const WIN_H: f32 = MSX_H as f32 * SCALE;
+// Room for the buttons at the right of the screen
+pub const PANEL_W: f32 = 256.0;
+const MIN_WIN_H: f32 = 384.0;
+// The 16 colours of the palette, then the fixed SCREEN8 colours
+const NUM_COLORS: usize = 16 + 256;
 
 #[derive(Clone, Debug)]
 enum ActiveTexture {
     Tex256,
-    Tex320,
 }
 
 pub enum GraphicsType {
@@ -35,7 +40,15 @@ pub trait GraphicsDriver {
     fn init(&mut self);
     fn render(&mut self);
     fn set_logical_resolution(&mut self, scr_mode: u8);
+    // Size of the frame, border included
+    fn set_frame_size(&mut self, width: u32, height: u32);
     fn draw_pixel(&mut self, x: u32, y: u32, color: usize);
+    // Changes a colour of the V9938 palette
+    fn set_palette_color(&mut self, index: usize, r: u8, g: u8, b: u8);
+    // Colours 0-15, for the frame already drawn too
+    fn set_palette(&mut self, palette: &Palette);
+    // Saves the last frame, border included, as a PNG file
+    fn save_screenshot(&self, path: &str) -> Result<(), String>;
 }
 
 /// null driver for test without graphics
@@ -50,19 +63,28 @@ impl GraphicsDriver for NullGraphics {
     fn init(&mut self) {}
     fn render(&mut self) {}
     fn set_logical_resolution(&mut self, _scr_mode: u8) {}
+    fn set_frame_size(&mut self, _width: u32, _height: u32) {}
     fn draw_pixel(&mut self, _x: u32, _y: u32, _color: usize) {}
+    fn set_palette_color(&mut self, _index: usize, _r: u8, _g: u8, _b: u8) {}
+    fn set_palette(&mut self, _palette: &Palette) {}
+    fn save_screenshot(&self, _path: &str) -> Result<(), String> {
+        Err("null graphics driver".to_string())
+    }
 }
 
 #[derive(Clone)]
 pub struct Graphics {
     quality: bool,
-    colors: [Color; 16],
+    colors: Vec<Color>,
     graphics_tex256: Texture2D,
-    graphics_tex320: Texture2D,
     graphics_image256: Image,
-    graphics_image320: Image,
+    // Colour index of every pixel of the image, to recolour it when the
+    // palette changes
+    indices: Vec<usize>,
     active_texture: ActiveTexture,
     zoom: f32,
+    // Lines of 512 pixels are not zoomed horizontally
+    zoom_x: f32,
     x0: i16,
     y0: i16,
 }
@@ -74,34 +96,26 @@ fn get_filter_mode(quality: bool) -> FilterMode {
         FilterMode::Linear
     }
 }
+
+fn set_colors(colors: &mut [Color], palette: &Palette) {
+    for (color, (r, g, b)) in colors.iter_mut().zip(palette.colors) {
+        *color = Color::from_rgba(r, g, b, 255);
+    }
+}
+
 impl Graphics {
     pub fn new(quality: bool) -> Self {
+        let mut colors = vec![BLACK; NUM_COLORS];
+        set_colors(&mut colors, &Palette::default());
         Self {
             quality,
-            colors: [
-                Color::from_hex(0xff000000),
-                Color::from_hex(0xff010101),
-                Color::from_hex(0xff3eb849),
-                Color::from_hex(0xff74d07d),
-                Color::from_hex(0xff5955e0),
-                Color::from_hex(0xff8076f1),
-                Color::from_hex(0xffb95e51),
-                Color::from_hex(0xff65dbef),
-                Color::from_hex(0xffdb6559),
-                Color::from_hex(0xffff897d),
-                Color::from_hex(0xffccc35e),
-                Color::from_hex(0xffded087),
-                Color::from_hex(0xff3aa241),
-                Color::from_hex(0xffb766b5),
-                Color::from_hex(0xffcccccc),
-                Color::from_hex(0xffffffff),
-            ],
+            colors,
             graphics_tex256: Texture2D::empty(),
-            graphics_tex320: Texture2D::empty(),
             graphics_image256: Image::gen_image_color(MSX_W2, MSX_H, BLUE),
-            graphics_image320: Image::gen_image_color(MSX_W1, MSX_H, BLUE),
+            indices: vec![0; MSX_W2 as usize * MSX_H as usize],
             active_texture: ActiveTexture::Tex256,
             zoom: SCALE,
+            zoom_x: SCALE,
             x0: 0,
             y0: 0,
         }
@@ -109,14 +123,13 @@ impl Graphics {
 }
 impl GraphicsDriver for Graphics {
     fn init(&mut self) {
-        request_new_screen_size(WIN_W, WIN_H);
+        let width = self.graphics_image256.width() as f32 * self.zoom_x + PANEL_W;
+        let height = self.graphics_image256.height() as f32 * self.zoom;
+        request_new_screen_size(width, height.max(MIN_WIN_H));
 
         self.graphics_tex256 = Texture2D::from_image(&self.graphics_image256);
         self.graphics_tex256
             .set_filter(get_filter_mode(self.quality));
-        self.graphics_tex320 = Texture2D::from_image(&self.graphics_image320);
-        self.graphics_tex320
-            .set_filter(get_filter_mode(self.quality));
     }
 
     fn render(&mut self) {
@@ -125,19 +138,15 @@ impl GraphicsDriver for Graphics {
                 self.graphics_tex256 = Texture2D::from_image(&self.graphics_image256);
                 &self.graphics_tex256
             }
-            ActiveTexture::Tex320 => {
-                self.graphics_tex320 = Texture2D::from_image(&self.graphics_image320);
-                &self.graphics_tex320
-            }
         };
         current_texture.set_filter(get_filter_mode(self.quality));
         draw_texture_ex(
             current_texture,
-            ((self.x0) * self.zoom as i16).into(),
+            ((self.x0) * self.zoom_x as i16).into(),
             ((self.y0) * self.zoom as i16).into(),
             WHITE,
             DrawTextureParams {
-                dest_size: Some(current_texture.size() * self.zoom),
+                dest_size: Some(current_texture.size() * vec2(self.zoom_x, self.zoom)),
                 ..Default::default()
             },
         );
@@ -145,18 +154,68 @@ impl GraphicsDriver for Graphics {
 
     fn set_logical_resolution(&mut self, scr_mode: u8) {
         match scr_mode {
-            SCREEN0 => self.active_texture = ActiveTexture::Tex320,
+            // Text modes are 240 pixels wide, centered in 256
+            SCREEN0 | MODE_TEXT_BITMAP | MODE_TEXT_STRIPES => {
+                self.active_texture = ActiveTexture::Tex256
+            }
             SCREEN2 => self.active_texture = ActiveTexture::Tex256,
             SCREEN1 => self.active_texture = ActiveTexture::Tex256,
+            SCREEN3 | MODE_MULTICOLOR_BITMAP => self.active_texture = ActiveTexture::Tex256,
+            MODE_TEXT80 | SCREEN4 | SCREEN5 | SCREEN6 | SCREEN7 | SCREEN8 => {
+                self.active_texture = ActiveTexture::Tex256
+            }
             _ => panic!("setLogicalResolution: mode not supported"),
         }
     }
+    fn set_frame_size(&mut self, width: u32, height: u32) {
+        if self.graphics_image256.width() == width as usize
+            && self.graphics_image256.height() == height as usize
+        {
+            return;
+        }
+        self.graphics_image256 = Image::gen_image_color(width as u16, height as u16, BLACK);
+        self.indices = vec![0; width as usize * height as usize];
+        self.zoom_x = if width > 2 * MSX_W2 as u32 {
+            self.zoom / 2.0
+        } else {
+            self.zoom
+        };
+        self.init();
+    }
     fn draw_pixel(&mut self, x: u32, y: u32, color: usize) {
         // println!("dp: {}/{} {} ci:{:?}", x, y, color, self.active_texture);
         let current_image = match self.active_texture {
             ActiveTexture::Tex256 => &mut self.graphics_image256,
-            ActiveTexture::Tex320 => &mut self.graphics_image320,
         };
         current_image.set_pixel(x, y, self.colors[color]);
+        self.indices[y as usize * current_image.width() + x as usize] = color;
+    }
+    fn set_palette_color(&mut self, index: usize, r: u8, g: u8, b: u8) {
+        self.colors[index] = Color::from_rgba(r, g, b, 255);
+    }
+    fn set_palette(&mut self, palette: &Palette) {
+        log::info!("Palette: {}", palette.name);
+        set_colors(&mut self.colors, palette);
+        // The frame on screen is redrawn with the new colours
+        let width = self.graphics_image256.width();
+        for (i, &color) in self.indices.iter().enumerate() {
+            let (x, y) = ((i % width) as u32, (i / width) as u32);
+            self.graphics_image256.set_pixel(x, y, self.colors[color]);
+        }
+    }
+    fn save_screenshot(&self, path: &str) -> Result<(), String> {
+        let image = &self.graphics_image256;
+        // export_png flips the image, as it expects the rows of a render
+        // target
+        let mut flipped = image.clone();
+        let (width, height) = (image.width() as u32, image.height() as u32);
+        for y in 0..height {
+            for x in 0..width {
+                flipped.set_pixel(x, height - 1 - y, image.get_pixel(x, y));
+            }
+        }
+        flipped.export_png(path);
+        log::info!("Screenshot saved to {}", path);
+        Ok(())
     }
 }
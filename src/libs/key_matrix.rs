@@ -116,8 +116,8 @@ pub fn key_matrix(row: usize) -> u8 {
 
     let mut result = 0xff_u8;
     if row < 11 {
-        for i in 0..8 {
-            if macroquad::input::is_key_down(KEYCODES_DATA[row][i]) {
+        for (i, key) in KEYCODES_DATA[row].iter().enumerate() {
+            if macroquad::input::is_key_down(*key) {
                 result &= !(1 << (7 - i));
             }
         }
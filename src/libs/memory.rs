@@ -1,12 +1,28 @@
-use std::io::Read;
-use std::{cell::RefCell, fs::File, rc::Rc};
+use std::path::Path;
+use std::{cell::RefCell, fs, rc::Rc};
 
 use serde::{Deserialize, Serialize};
 
 use crate::libs::cartridges::MapperKonami4;
 
-use super::cartridges::{get_cart_type, CartType, MapperASCII8, MapperKonami5};
+use super::cartridges::{
+    get_cart_type, get_rom_hash, get_rom_image, get_rom_start, CartType, MapperASCII16,
+    MapperASCII16SRAM, MapperASCII8, MapperASCII8SRAM, MapperGameMaster2, MapperKonami5,
+    MapperRType, RomHeader,
+};
 use super::ppi::{PPIData, PPI};
+use super::rom_database::{RomDatabase, XML_DATABASE};
+use super::scc::SCC;
+
+// Builds a mapper and loads the ROM into it
+fn with_rom<M: Mapper + 'static>(
+    mut mapper: M,
+    init: fn(&mut M, &[u8]) -> Result<(), String>,
+    data: &[u8],
+) -> Result<Rc<RefCell<dyn Mapper>>, String> {
+    init(&mut mapper, data)?;
+    Ok(Rc::new(RefCell::new(mapper)))
+}
 
 pub struct NullMapper {}
 impl Default for NullMapper {
@@ -35,14 +51,31 @@ pub trait Mapper {
     }
     fn read_byte(&self, address: u16) -> u8;
     fn write_byte(&mut self, address: u16, value: u8);
+    // Battery-backed SRAM contents, if the cartridge has any
+    fn get_sram(&self) -> Option<&[u8]> {
+        None
+    }
+    fn set_sram(&mut self, _data: &[u8]) {}
+    // Sound chip on the cartridge, if any
+    fn get_scc(&self) -> Option<Rc<RefCell<SCC>>> {
+        None
+    }
+    // Whether a write to address goes to a register of that sound chip
+    fn writes_sound(&self, _address: u16) -> bool {
+        false
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
 #[allow(non_snake_case)]
 pub struct MemoryData {
-    pub(crate) contents: Vec<u8>, //[u8; 4 * 4 * 0x4000],
-    pub(crate) can_write: [bool; 4 * 4],
-    pub(crate) slot_mapper: isize,
+    pub(crate) contents: Vec<u8>,    //[u8; 4 * 4 * 4 * 0x4000],
+    pub(crate) can_write: Vec<bool>, //[bool; 4 * 4 * 4],
+    // Primary slots with a secondary slot register at 0xFFFF
+    pub(crate) slot_expanded: [bool; 4],
+    // Secondary slot registers, one per primary slot (2 bits per page)
+    pub(crate) subslot_regs: [u8; 4],
 }
 impl Default for MemoryData {
     fn default() -> Self {
@@ -53,20 +86,37 @@ impl Default for MemoryData {
 impl MemoryData {
     pub fn new() -> Self {
         Self {
-            contents: vec![0; 4 * 4 * 0x4000],
-            can_write: [true; 4 * 4],
-            slot_mapper: -1,
+            contents: vec![0; 4 * 4 * 4 * 0x4000],
+            can_write: vec![true; 4 * 4 * 4],
+            slot_expanded: [false; 4],
+            subslot_regs: [0; 4],
         }
     }
 }
 
-// TODO: Secondary mapper (0xFFFF)
+const SUBSLOT_REGISTER: u16 = 0xffff;
+
+// Index of a 16KB block in contents/can_write
+fn block_index(page: usize, slot: usize, subslot: usize) -> usize {
+    (page * 4 + slot) * 4 + subslot
+}
+
+// Index of a (primary, secondary) slot in mappers
+fn mapper_index(slot: usize, subslot: usize) -> usize {
+    slot * 4 + subslot
+}
+
 pub type MemoryAccessor = Rc<RefCell<Memory>>;
 
 pub struct Memory {
     pub(crate) data: MemoryData,
     pub(crate) ppi: Rc<RefCell<PPI>>,
-    mapper: Rc<RefCell<dyn Mapper>>,
+    // One mapper per secondary slot (4 primary x 4 secondary)
+    mappers: Vec<Rc<RefCell<dyn Mapper>>>,
+    // SRAM sidecar files: (mapper index, path)
+    sram_files: Vec<(usize, String)>,
+    // Loaded on the first cartridge if not set
+    rom_database: Option<Rc<RomDatabase>>,
 }
 
 impl Memory {
@@ -74,7 +124,11 @@ impl Memory {
         Self {
             data: MemoryData::default(),
             ppi,
-            mapper: Rc::new(RefCell::new(NullMapper::new())),
+            mappers: (0..4 * 4)
+                .map(|_| Rc::new(RefCell::new(NullMapper::new())) as Rc<RefCell<dyn Mapper>>)
+                .collect(),
+            sram_files: Vec::new(),
+            rom_database: None,
         }
     }
     // pub fn save_state(&self) -> Memory {
@@ -93,98 +147,270 @@ impl Memory {
     //     self.mapper = m.mapper.clone();
     //     self.ppi = m.ppi;
     // }
-    pub fn load_bios_basic(&mut self, fname: &str) {
-        let mut f = File::open(fname).unwrap();
-        let mut buffer = Vec::new();
-        f.read_to_end(&mut buffer).unwrap();
+    pub fn set_rom_database(&mut self, rom_database: Rc<RomDatabase>) {
+        self.rom_database = Some(rom_database);
+    }
+
+    pub fn get_rom_database(&mut self) -> Rc<RomDatabase> {
+        self.rom_database
+            .get_or_insert_with(|| match RomDatabase::load(XML_DATABASE) {
+                Ok(db) => Rc::new(db),
+                Err(err) => {
+                    log::warn!("Cannot load ROM database: {}", err);
+                    Rc::new(RomDatabase::new())
+                }
+            })
+            .clone()
+    }
+
+    pub fn load_bios_basic(&mut self, fname: &str) -> Result<(), String> {
+        let buffer = fs::read(fname).map_err(|err| format!("{}: {}", fname, err))?;
         self.load(&buffer, 0, 0);
         if buffer.len() > 0x4000 {
             // Load BASIC, if present
             self.load(&buffer[0x4000..], 1, 0);
         }
+        Ok(())
     }
 
-    pub fn load_rom(&mut self, fname: &str, slot: usize, mapper_type: &str) {
-        let mut f = File::open(fname).unwrap();
-        let mut buffer = Vec::new();
-        f.read_to_end(&mut buffer).unwrap();
-        match get_cart_type(&buffer) {
-            CartType::KONAMI4 => {
-                log::info!("Loading ROM {} to slot 1 as type KONAMI4", fname);
-                let mut mapper_konami4 = MapperKonami4::new();
-                mapper_konami4.init(&buffer);
-                self.set_mapper(Rc::new(RefCell::new(mapper_konami4)), slot);
-                return;
-            }
-            CartType::KONAMI5 => {
-                log::info!("Loading ROM {} to slot 1 as type KONAMI5", fname);
-                let mut mapper_konami5 = MapperKonami5::new();
-                mapper_konami5.init(&buffer);
-                self.set_mapper(Rc::new(RefCell::new(mapper_konami5)), slot);
-                return;
-            }
-            CartType::ASCII8KB => {
-                log::info!("Loading ROM {} to slot 1 as type ASCII8KB", fname);
-                let mut mapper_ascii8 = MapperASCII8::new();
-                mapper_ascii8.init(&buffer);
-                self.set_mapper(Rc::new(RefCell::new(mapper_ascii8)), slot);
-                return;
-            }
+    // Loads the 16KB sub ROM of the MSX2 in page 0
+    pub fn load_sub_rom(&mut self, fname: &str, slot: usize, subslot: usize) -> Result<(), String> {
+        let mut buffer = fs::read(fname).map_err(|err| format!("{}: {}", fname, err))?;
+        buffer.resize(0x4000, 0xff);
+        self.load_subslot(&buffer, 0, slot, subslot);
+        Ok(())
+    }
+
+    pub fn load_rom(&mut self, fname: &str, slot: usize, mapper_type: &str) -> Result<(), String> {
+        self.load_rom_subslot(fname, slot, 0, mapper_type)
+    }
+
+    pub fn load_rom_subslot(
+        &mut self,
+        fname: &str,
+        slot: usize,
+        subslot: usize,
+        mapper_type: &str,
+    ) -> Result<(), String> {
+        let buffer = fs::read(fname).map_err(|err| format!("{}: {}", fname, err))?;
+        let cart_type = if mapper_type.is_empty() {
+            get_cart_type(&buffer, &self.get_rom_database())
+        } else if let Some(cart_type) = CartType::from_name(mapper_type) {
+            log::info!("Mapper type forced to {:?}", cart_type);
+            cart_type
+        } else {
+            log::warn!("Unknown mapper type {}", mapper_type);
+            get_cart_type(&buffer, &self.get_rom_database())
+        };
+        let mapper = match cart_type {
             CartType::NORMAL => {
                 log::info!("Cartridge is type NORMAL");
+                self.load_plain_rom(fname, &buffer, slot, subslot);
+                return Ok(());
             }
-            _ => {
-                unimplemented!()
+            CartType::UNKNOWN => return Err(format!("{}: unknown cartridge type", fname)),
+            CartType::KONAMI4 => with_rom(MapperKonami4::new(), MapperKonami4::init, &buffer),
+            CartType::KONAMI5 => with_rom(MapperKonami5::new(), MapperKonami5::init, &buffer),
+            CartType::ASCII8KB => with_rom(MapperASCII8::new(), MapperASCII8::init, &buffer),
+            CartType::ASCII16KB => with_rom(MapperASCII16::new(), MapperASCII16::init, &buffer),
+            CartType::RTYPE => with_rom(MapperRType::new(), MapperRType::init, &buffer),
+            CartType::ASCII8SRAM2 => with_rom(
+                MapperASCII8SRAM::new(0x800),
+                MapperASCII8SRAM::init,
+                &buffer,
+            ),
+            CartType::ASCII8SRAM8 => with_rom(
+                MapperASCII8SRAM::new(0x2000),
+                MapperASCII8SRAM::init,
+                &buffer,
+            ),
+            CartType::ASCII16SRAM2 => with_rom(
+                MapperASCII16SRAM::new(0x800),
+                MapperASCII16SRAM::init,
+                &buffer,
+            ),
+            CartType::ASCII16SRAM8 => with_rom(
+                MapperASCII16SRAM::new(0x2000),
+                MapperASCII16SRAM::init,
+                &buffer,
+            ),
+            CartType::KOEISRAM8 => with_rom(
+                MapperASCII8SRAM::new_koei(0x2000),
+                MapperASCII8SRAM::init,
+                &buffer,
+            ),
+            CartType::KOEISRAM32 => with_rom(
+                MapperASCII8SRAM::new_koei(0x8000),
+                MapperASCII8SRAM::init,
+                &buffer,
+            ),
+            CartType::GAMEMASTER2 => {
+                with_rom(MapperGameMaster2::new(), MapperGameMaster2::init, &buffer)
             }
         }
-        log::info!("Trying to load as a standard cartridge...");
+        .map_err(|err| format!("{}: {}", fname, err))?;
+        let has_sram = matches!(
+            cart_type,
+            CartType::ASCII8SRAM2
+                | CartType::ASCII8SRAM8
+                | CartType::ASCII16SRAM2
+                | CartType::ASCII16SRAM8
+                | CartType::KOEISRAM8
+                | CartType::KOEISRAM32
+                | CartType::GAMEMASTER2
+        );
+        log::info!(
+            "Loading ROM {} to slot {}-{} as type {:?}",
+            fname,
+            slot,
+            subslot,
+            cart_type
+        );
+        self.insert_cartridge(fname, mapper, has_sram, slot, subslot);
+        Ok(())
+    }
 
-        if !mapper_type.is_empty() && mapper_type == "KONAMI4" {
-            let mut mapper_konami4 = MapperKonami4::new();
-            mapper_konami4.init(&buffer);
-            self.set_mapper(Rc::new(RefCell::new(mapper_konami4)), slot);
-            return;
+    // Puts a megaROM cartridge in a slot, with the SRAM saved next to the
+    // ROM file if it has any
+    fn insert_cartridge(
+        &mut self,
+        fname: &str,
+        mapper: Rc<RefCell<dyn Mapper>>,
+        has_sram: bool,
+        slot: usize,
+        subslot: usize,
+    ) {
+        self.set_mapper_subslot(mapper, slot, subslot);
+        if has_sram {
+            self.load_sram(fname, slot, subslot);
         }
-        let num_of_pages = buffer.len() / 0x4000;
-        match num_of_pages {
-            1 => {
-                // Load ROM to page 1, slot 1
-                // TODO: mirrored????
-                log::info!("Loading ROM {} to slot 1 (16KB)", fname);
-                self.load(&buffer, 1, slot);
-            }
-            2 => {
-                // Load ROM to slot 1. Mirrored pg1&pg2 <=> pg3&pg4
-                log::info!("Loading ROM {} to slot 1 (32KB)", fname);
-                self.load(&buffer, 0, slot);
-                self.load(&buffer, 1, slot);
-                self.load(&buffer[0x4000..], 2, slot);
-                self.load(&buffer[0x4000..], 3, slot);
-            }
-            4 => {
-                log::info!("Loading ROM {} to slot 1 (64KB)", fname);
-                self.load(&buffer, 0, slot);
-                self.load(&buffer[0x4000..], 1, slot);
-                self.load(&buffer[0x8000..], 2, slot);
-                self.load(&buffer[0xC000..], 3, slot);
-            }
-            _ => {
-                log::error!("ROM size not supported")
+    }
+
+    // Maps a ROM without a mapper at the pages it fills
+    fn load_plain_rom(&mut self, fname: &str, buffer: &[u8], slot: usize, subslot: usize) {
+        let hash = get_rom_hash(buffer);
+        let hint = self
+            .get_rom_database()
+            .find(&hash)
+            .and_then(|entry| entry.start);
+        if let Some(header) = RomHeader::parse(buffer) {
+            log::info!(
+                "ROM header: INIT={:04x} STATEMENT={:04x} DEVICE={:04x} TEXT={:04x}",
+                header.init,
+                header.statement,
+                header.device,
+                header.text
+            );
+        }
+        let start = get_rom_start(buffer, hint);
+        log::info!(
+            "Loading ROM {} to slot {}-{} at {:04x} ({}KB)",
+            fname,
+            slot,
+            subslot,
+            start,
+            buffer.len() / 1024
+        );
+        let (image, pages) = get_rom_image(buffer, start);
+        for (page, used) in pages.iter().enumerate() {
+            if *used {
+                self.load_subslot(&image[page * 0x4000..], page, slot, subslot);
             }
         }
     }
 
     // Loads 16k (one page)
     pub fn load(&mut self, data: &[u8], page: usize, slot: usize) {
-        let base_addr = (page * 4 + slot) * 0x4000;
+        self.load_subslot(data, page, slot, 0);
+    }
+
+    // Loads 16k (one page) into a secondary slot
+    pub fn load_subslot(&mut self, data: &[u8], page: usize, slot: usize, subslot: usize) {
+        let block = block_index(page, slot, subslot);
+        let base_addr = block * 0x4000;
         self.data.contents[base_addr..(0x4000 + base_addr)].copy_from_slice(&data[..0x4000]);
-        self.data.can_write[page * 4 + slot] = false;
+        self.data.can_write[block] = false;
+    }
+
+    // Marks a primary slot as expanded: its secondary slot register
+    // becomes visible at 0xFFFF when the slot is selected for page 3.
+    pub fn set_slot_expanded(&mut self, slot: usize, expanded: bool) {
+        self.data.slot_expanded[slot] = expanded;
+        self.data.subslot_regs[slot] = 0;
+    }
+
+    pub fn is_slot_expanded(&self, slot: usize) -> bool {
+        self.data.slot_expanded[slot]
+    }
+
+    // Enables or disables writes (RAM) for a page of a secondary slot
+    pub fn set_writable(&mut self, page: usize, slot: usize, subslot: usize, writable: bool) {
+        self.data.can_write[block_index(page, slot, subslot)] = writable;
+    }
+
+    // Returns the (primary, secondary) slot selected for a page
+    pub fn get_slot(&self, page: usize) -> (usize, usize) {
+        let slot = self.ppi.borrow().data.pg_slots[page] as usize;
+        if !self.data.slot_expanded[slot] {
+            return (slot, 0);
+        }
+        let subslot = (self.data.subslot_regs[slot] >> (page * 2)) & 0x03;
+        (slot, subslot as usize)
     }
 
     pub fn set_mapper(&mut self, mapper: Rc<RefCell<dyn Mapper>>, slot: usize) {
-        log::info!("Loading MegaROM in slot {}", slot);
-        self.mapper = mapper;
-        self.data.slot_mapper = slot as isize;
+        self.set_mapper_subslot(mapper, slot, 0);
+    }
+
+    pub fn set_mapper_subslot(
+        &mut self,
+        mapper: Rc<RefCell<dyn Mapper>>,
+        slot: usize,
+        subslot: usize,
+    ) {
+        log::info!("Loading MegaROM in slot {}-{}", slot, subslot);
+        self.mappers[mapper_index(slot, subslot)] = mapper;
+    }
+
+    // Returns the SCC of the first cartridge that has one
+    pub fn get_scc(&self) -> Option<Rc<RefCell<SCC>>> {
+        self.mappers.iter().find_map(|m| m.borrow().get_scc())
+    }
+
+    // Loads the SRAM of the cartridge in a slot from its sidecar file
+    // (the ROM file name with a .sav extension) and remembers the file
+    // so save_sram() can write it back.
+    fn load_sram(&mut self, fname: &str, slot: usize, subslot: usize) {
+        let idx = mapper_index(slot, subslot);
+        let sav = Path::new(fname).with_extension("sav");
+        if let Ok(data) = fs::read(&sav) {
+            log::info!("Loading SRAM from {}", sav.display());
+            self.mappers[idx].borrow_mut().set_sram(&data);
+        }
+        self.sram_files.retain(|(i, _)| *i != idx);
+        self.sram_files
+            .push((idx, sav.to_string_lossy().into_owned()));
+    }
+
+    // Writes the SRAM of every cartridge that has one to its sidecar file
+    pub fn save_sram(&self) {
+        for (idx, path) in &self.sram_files {
+            if let Some(sram) = self.mappers[*idx].borrow().get_sram() {
+                match fs::write(path, sram) {
+                    Ok(_) => log::info!("SRAM saved to {}", path),
+                    Err(err) => log::warn!("Cannot save SRAM to {}: {}", path, err),
+                }
+            }
+        }
+    }
+
+    // Returns the mapper in a secondary slot, if any
+    pub fn get_mapper(&self, slot: usize, subslot: usize) -> Option<Rc<RefCell<dyn Mapper>>> {
+        let mapper = &self.mappers[mapper_index(slot, subslot)];
+        if mapper.borrow().is_void() {
+            return None;
+        }
+        Some(mapper.clone())
     }
 
     pub fn read_byte(&self, address: u16) -> u8 {
@@ -195,18 +421,33 @@ impl Memory {
     // into account contention.
     pub fn read_byte_internal(&self, address: u16) -> u8 {
         let page = (address / 0x4000) as usize;
-        let slot = self.ppi.borrow().data.pg_slots[page];
+        let (slot, subslot) = self.get_slot(page);
+
+        if address == SUBSLOT_REGISTER && self.data.slot_expanded[slot] {
+            // Secondary slot register reads back inverted
+            return !self.data.subslot_regs[slot];
+        }
 
-        if !self.mapper.borrow().is_void()
-            && self.data.slot_mapper == slot
-            && (page == 1 || page == 2)
-        {
-            return self.mapper.borrow().read_byte(address);
+        let mapper = &self.mappers[mapper_index(slot, subslot)];
+        if !mapper.borrow().is_void() && (page == 1 || page == 2) {
+            return mapper.borrow().read_byte(address);
         }
 
         let delta = (address as usize) - page * 0x4000;
-        // return self.contents[page][slot as usize][delta];
-        self.data.contents[(page * 4 + slot as usize) * 0x4000 + delta]
+        // return self.contents[page][slot][subslot][delta];
+        self.data.contents[block_index(page, slot, subslot) * 0x4000 + delta]
+    }
+
+    // Whether a write to address changes the sound of a cartridge
+    pub fn writes_sound(&self, address: u16) -> bool {
+        let page = (address / 0x4000) as usize;
+        if page != 1 && page != 2 {
+            return false;
+        }
+        let (slot, subslot) = self.get_slot(page);
+        self.mappers[mapper_index(slot, subslot)]
+            .borrow()
+            .writes_sound(address)
     }
 
     // WriteByte writes a byte at address taking into account
@@ -219,46 +460,45 @@ impl Memory {
     // into account contention.
     fn write_byte_internal(&mut self, address: u16, value: u8) {
         let page = (address / 0x4000) as usize;
-        let slot = self.ppi.borrow().data.pg_slots[page];
+        let (slot, subslot) = self.get_slot(page);
 
-        if !self.mapper.borrow().is_void()
-            && self.data.slot_mapper == slot
-            && (page == 1 || page == 2)
-        {
-            self.mapper.borrow_mut().write_byte(address, value);
+        if address == SUBSLOT_REGISTER && self.data.slot_expanded[slot] {
+            self.data.subslot_regs[slot] = value;
             return;
         }
 
-        if self.data.can_write[page * 4 + slot as usize] {
-            let delta = (address as usize) - page * 0x4000;
-            // return self.contents[page][slot as usize][delta];
-            self.data.contents[(page * 4 + slot as usize) * 0x4000 + delta] = value;
+        let mapper = &self.mappers[mapper_index(slot, subslot)];
+        if !mapper.borrow().is_void() && (page == 1 || page == 2) {
+            mapper.borrow_mut().write_byte(address, value);
+            return;
         }
-    }
-
-    pub fn contend_read(&mut self, _address: u16, _time: isize) {
-        //panic("ContendRead not implemented")
-    }
 
-    pub fn contend_read_no_mreq(&mut self, _address: u16, _time: isize) {
-        //panic("ContendReadNoMreq not implemented")
-    }
-
-    pub fn contend_read_no_mreq_loop(&mut self, _address: u16, _time: isize, _count: usize) {
-        //panic("ContendReadNoMreq_loop not implemented")
-    }
-
-    pub fn contend_write_no_mreq(&mut self, _address: u16, _time: isize) {
-        //panic("ContendWriteNoMreq not implemented")
+        let block = block_index(page, slot, subslot);
+        if self.data.can_write[block] {
+            let delta = (address as usize) - page * 0x4000;
+            // return self.contents[page][slot][subslot][delta];
+            self.data.contents[block * 0x4000 + delta] = value;
+        }
     }
 
-    pub fn contend_write_no_mreq_loop(&mut self, _address: u16, _time: isize, _count: usize) {
-        //panic("ContendWriteNoMreq_loop not implemented")
-    }
     pub fn get_data(&self) -> MemoryData {
         self.data.clone()
     }
-    pub fn set_data(&mut self, data: MemoryData) {
+    pub fn set_data(&mut self, mut data: MemoryData) {
+        if data.contents.len() == 4 * 4 * 0x4000 {
+            // Saved before the secondary slots: each page goes to subslot 0
+            let mut contents = vec![0; 4 * 4 * 4 * 0x4000];
+            let mut can_write = vec![true; 4 * 4 * 4];
+            for (block, page) in data.contents.chunks(0x4000).enumerate() {
+                let addr = block * 4 * 0x4000;
+                contents[addr..addr + 0x4000].copy_from_slice(page);
+                can_write[block * 4] = data.can_write.get(block).copied().unwrap_or(true);
+            }
+            data.contents = contents;
+            data.can_write = can_write;
+        }
+        data.contents.resize(4 * 4 * 4 * 0x4000, 0);
+        data.can_write.resize(4 * 4 * 4, true);
         self.data = data;
     }
     pub fn get_ppi_data(&self) -> PPIData {
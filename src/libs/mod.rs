@@ -1,13 +1,19 @@
+pub mod audio_mixer;
+pub mod ay8910;
 pub mod cartridges;
 pub mod graphics;
 pub mod key_matrix;
 pub mod memory;
 pub mod msx;
+pub mod msx_bus;
+pub mod palette;
 pub mod ports;
 pub mod ppi;
 pub mod psg;
 pub mod rom_database;
+pub mod scc;
 pub mod sound;
-pub mod tone_generator;
+pub mod timing;
 pub mod vdp;
+pub mod vdp_command;
 pub mod z80;
@@ -6,8 +6,12 @@ use serde::{Deserialize, Serialize};
 use macroquad::prelude::*;
 use macroquad::ui::{hash, root_ui};
 
+use super::graphics::PANEL_W;
 use super::memory::MemoryData;
+use super::msx_bus::MsxBus;
+use super::palette::Palette;
 use super::ppi::PPIData;
+use super::timing::CYCLES_PER_LINE;
 use super::vdp::{Vdp, VdpData};
 use super::z80::z80_base::{Z80Data, Z80};
 // use super::{vdp::Vdp, z80::z80_base::Z80};
@@ -20,24 +24,33 @@ struct SaveData {
     vdp: VdpData,
 }
 
-const CYCLES_PER_FRAME: u64 = 60000; // The z80 runs at 3.58 Mhz. Every 16msec 57280 cycles pass.
 const NANO_SEC_PER_SEC: u32 = 1_000_000_000;
 const MILLIS_PER_NANO_SEC: u32 = 1_000_000;
 const SAVE_FILE_PATH: &str = "msx.save";
+const SCREENSHOT_FILE_PATH: &str = "msx.png";
 
 fn nanoseconds() -> i64 {
     (get_time() * NANO_SEC_PER_SEC as f64) as i64
 }
 
 pub struct MSX {
-    cpu_z80: Z80,
+    cpu_z80: Z80<MsxBus>,
     vdp: Rc<RefCell<Vdp>>,
+    // Built-in palettes the UI cycles through
+    palettes: Vec<Palette>,
+    palette_index: usize,
 }
 
 impl MSX {
-    pub fn new(cpu_z80: Z80, vdp: Rc<RefCell<Vdp>>) -> Self {
-        Self { cpu_z80, vdp }
+    pub fn new(cpu_z80: Z80<MsxBus>, vdp: Rc<RefCell<Vdp>>) -> Self {
+        Self {
+            cpu_z80,
+            vdp,
+            palettes: Palette::builtin(),
+            palette_index: 0,
+        }
     }
+    // With a frame_interval of 0 the frames follow the video timing
     pub async fn main_loop(&mut self, frame_interval: isize) -> f64 {
         log::info!("Beginning simulation...");
         // state_init();
@@ -47,7 +60,12 @@ impl MSX {
         let mut elapsed_time: i64;
         let mut lag: i64 = 0;
         let mut n_frames: i64 = 0;
-        let update_interval = (MILLIS_PER_NANO_SEC as i64) * (frame_interval as i64);
+        let update_interval = if frame_interval > 0 {
+            (MILLIS_PER_NANO_SEC as i64) * (frame_interval as i64)
+        } else {
+            let frame_rate = self.vdp.borrow().get_timing().frame_rate();
+            (NANO_SEC_PER_SEC as f64 / frame_rate) as i64
+        };
 
         let start_time = nanoseconds();
         let mut previous_time = start_time;
@@ -55,6 +73,8 @@ impl MSX {
 
         // self.cpu_z80.debug = true;
 
+        // Closing the window leaves the loop, so the SRAM gets saved
+        prevent_quit();
         loop {
             current_time = nanoseconds();
             elapsed_time = current_time - previous_time;
@@ -67,13 +87,12 @@ impl MSX {
                 lag -= update_interval;
             }
 
-            clear_background(RED);
+            clear_background(BLACK);
 
             // draw_line(40.0, 40.0, 100.0, 200.0, 15.0, BLUE);
             // draw_rectangle(screen_width() / 2.0 - 60.0, 100.0, 120.0, 60.0, GREEN);
             // draw_text(format!("FPS: {}", get_fps()).as_str(), 0., 16., 32., WHITE);
 
-            self.vdp.borrow_mut().update_buffer();
             self.vdp.borrow_mut().graphics_render();
 
             // if !paused {
@@ -83,7 +102,7 @@ impl MSX {
             // }
 
             controls.update();
-            if controls.f12 == 1 {
+            if controls.f12 == 1 || is_quit_requested() {
                 break;
             }
             if controls.pause == 1 {
@@ -101,41 +120,73 @@ impl MSX {
             // 	paused = false;
             // }
 
-            root_ui().window(hash!(), vec2(512., 0.), vec2(256., 192. * 2.), |ui| {
-                if ui.button(None, "Save") {
-                    self.save();
-                }
-                if ui.button(None, "Load") {
-                    self.load();
-                }
-                if ui.button(None, "Reset") {
-                    self.cpu_z80.reset();
-                    self.cpu_z80.SetPC(0);
-                }
+            root_ui().window(
+                hash!(),
+                vec2(screen_width() - PANEL_W, 0.),
+                vec2(PANEL_W, 192. * 2.),
+                |ui| {
+                    if ui.button(None, "Save") {
+                        self.save();
+                    }
+                    if ui.button(None, "Load") {
+                        self.load();
+                    }
+                    if ui.button(None, "Reset") {
+                        self.cpu_z80.reset();
+                        self.cpu_z80.SetPC(0);
+                    }
+                    if ui.button(None, "Palette") {
+                        self.palette_index = (self.palette_index + 1) % self.palettes.len();
+                        let palette = &self.palettes[self.palette_index];
+                        self.vdp.borrow_mut().set_palette(palette);
+                    }
+                    if ui.button(None, "Screenshot") {
+                        if let Err(err) = self.vdp.borrow().save_screenshot(SCREENSHOT_FILE_PATH) {
+                            log::warn!("Cannot save screenshot: {}", err);
+                        }
+                    }
 
-                ui.separator();
-            });
+                    ui.separator();
+                },
+            );
 
             n_frames += 1;
             next_frame().await;
         }
+        self.cpu_z80.save_sram();
         let delta = (nanoseconds() - start_time) as f64 / (NANO_SEC_PER_SEC as f64);
-        (n_frames as f64) / (delta as f64)
+        n_frames as f64 / delta
     }
 
     pub fn cpu_frame(&mut self) {
-        self.cpu_z80.data.cycles %= CYCLES_PER_FRAME;
-        while self.cpu_z80.data.cycles < CYCLES_PER_FRAME {
-            if self.cpu_z80.data.halted {
-                break;
-            }
+        let timing = self.vdp.borrow().get_timing();
+        let cycles_per_frame = timing.cycles_per_frame();
+        let lines_per_frame = timing.lines_per_frame();
+        self.cpu_z80.start_frame(cycles_per_frame);
+        let mut line = 0;
+        // One instruction at a time, drawing a line every CYCLES_PER_LINE
+        // (228) cycles
+        while self.cpu_z80.data.cycles < cycles_per_frame {
+            let cycles = self.cpu_z80.data.cycles;
             self.cpu_z80.do_opcode();
+            let elapsed = (self.cpu_z80.data.cycles - cycles) as u32;
+            self.cpu_z80.run_sound(elapsed);
+            self.cpu_z80.run_vdp();
+            // Lines are drawn once the beam has gone past them
+            while line < lines_per_frame
+                && self.cpu_z80.data.cycles >= (line + 1) as u64 * CYCLES_PER_LINE
+            {
+                self.vdp.borrow_mut().render_line(line);
+                line += 1;
+            }
         }
-
-        if self.vdp.borrow().data.enabled_interrupts {
-            self.vdp.borrow_mut().set_frame_flag();
-            self.cpu_z80.interrupt();
+        let mut vdp = self.vdp.borrow_mut();
+        for line in line..lines_per_frame {
+            vdp.render_line(line);
         }
+        // Vertical blanking: INT stays asserted, with IE0, until the
+        // interrupt routine reads the status register
+        vdp.set_frame_flag();
     }
     fn save(&mut self) {
         let data = SaveData {
@@ -149,8 +200,20 @@ impl MSX {
         fs::write(SAVE_FILE_PATH, serialized).unwrap();
     }
     fn load(&mut self) {
-        let contents = fs::read(SAVE_FILE_PATH).unwrap();
-        let deserialized: SaveData = serde_json::from_slice(&contents).unwrap();
+        let contents = match fs::read(SAVE_FILE_PATH) {
+            Ok(contents) => contents,
+            Err(err) => {
+                log::warn!("Cannot read {}: {}", SAVE_FILE_PATH, err);
+                return;
+            }
+        };
+        let deserialized: SaveData = match serde_json::from_slice(&contents) {
+            Ok(data) => data,
+            Err(err) => {
+                log::warn!("Cannot load {}: {}", SAVE_FILE_PATH, err);
+                return;
+            }
+        };
         self.cpu_z80.set_data(deserialized.z80);
         self.cpu_z80.set_memory_data(deserialized.memory);
         self.cpu_z80.set_ppi_data(deserialized.ppi);
@@ -0,0 +1,93 @@
+use super::memory::{Memory, MemoryData};
+use super::ports::Ports;
+use super::ppi::PPIData;
+use super::z80::z80_base::Z80;
+use super::z80::z80_bus::Bus;
+
+// The slots and the I/O ports of the MSX, as the Z80 sees them
+pub struct MsxBus {
+    pub memory: Memory,
+    pub ports: Ports,
+}
+
+impl MsxBus {
+    pub fn new(memory: Memory, ports: Ports) -> Self {
+        Self { memory, ports }
+    }
+}
+
+impl Bus for MsxBus {
+    fn read_byte(&self, address: u16) -> u8 {
+        self.memory.read_byte(address)
+    }
+
+    fn write_byte(&mut self, address: u16, value: u8) {
+        // The SCC of a cartridge catches up before its registers change,
+        // like the PSG does on its ports
+        if self.memory.writes_sound(address) {
+            self.ports.flush_sound();
+        }
+        self.memory.write_byte(address, value);
+    }
+
+    fn read_port(&mut self, port: u16) -> u8 {
+        self.ports.read_port(port)
+    }
+
+    fn write_port(&mut self, port: u16, value: u8) {
+        self.ports.write_port(port, value);
+    }
+
+    // The MSX engine adds a wait state to every M1 cycle
+    fn m1_wait(&mut self) -> u64 {
+        1
+    }
+
+    // No waits on I/O, but the VDP is brought up to the access so the
+    // command engine and its status are exact to the cycle
+    fn io_wait(&mut self, port: u16, time: u64) -> u64 {
+        if let 0x98..=0x9b = port as u8 {
+            self.ports.run_vdp(time);
+        }
+        0
+    }
+
+    fn int_line(&self) -> bool {
+        self.ports.int_line()
+    }
+
+    fn has_hooks(&self) -> bool {
+        true
+    }
+}
+
+// The parts of the machine reached through the CPU
+impl Z80<MsxBus> {
+    pub fn get_memory_data(&self) -> MemoryData {
+        self.bus.memory.get_data()
+    }
+    pub fn set_memory_data(&mut self, data: MemoryData) {
+        self.bus.memory.set_data(data);
+    }
+    pub fn save_sram(&self) {
+        self.bus.memory.save_sram();
+    }
+    pub fn run_sound(&mut self, cycles: u32) {
+        self.bus.ports.run_sound(cycles);
+    }
+    // Runs the VDP up to the current cycle
+    pub fn run_vdp(&mut self) {
+        self.bus.ports.run_vdp(self.data.cycles);
+    }
+    // The cycles are counted from the start of the frame
+    pub fn start_frame(&mut self, cycles_per_frame: u64) {
+        self.data.cycles %= cycles_per_frame;
+        self.bus.ports.set_vdp_cycles(self.data.cycles);
+    }
+    pub fn get_ppi_data(&self) -> PPIData {
+        self.bus.memory.get_ppi_data()
+    }
+    pub fn set_ppi_data(&mut self, data: PPIData) {
+        self.bus.memory.set_ppi_data(data);
+    }
+}
@@ -0,0 +1,118 @@
+/*
+
+    Colour tables of the TMS9918 family
+
+    A palette file has 16 lines with the colours 0 to 15, as RRGGBB hex
+    values (optionally starting with #). Empty lines and lines starting
+    with ; are ignored.
+
+*/
+
+use std::fs;
+
+use super::vdp::DEFAULT_PALETTE;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Palette {
+    pub name: String,
+    pub colors: [(u8, u8, u8); 16],
+}
+
+// TMS9918A measured on a NTSC display
+const NTSC: [u32; 16] = [
+    0x000000, 0x010101, 0x3eb849, 0x74d07d, 0x5955e0, 0x8076f1, 0xb95e51, 0x65dbef, 0xdb6559,
+    0xff897d, 0xccc35e, 0xded087, 0x3aa241, 0xb766b5, 0xcccccc, 0xffffff,
+];
+
+// TMS9929A through the PAL encoder, less saturated
+const PAL: [u32; 16] = [
+    0x000000, 0x000000, 0x66cc66, 0x88ee88, 0x4444dd, 0x7777ff, 0xbb5555, 0x77dddd, 0xdd6666,
+    0xff7777, 0xcccc55, 0xeeee88, 0x55aa55, 0xbb55bb, 0xcccccc, 0xeeeeee,
+];
+
+fn rgb(color: u32) -> (u8, u8, u8) {
+    ((color >> 16) as u8, (color >> 8) as u8, color as u8)
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self::ntsc()
+    }
+}
+
+impl Palette {
+    pub fn ntsc() -> Self {
+        Self {
+            name: "ntsc".to_string(),
+            colors: NTSC.map(rgb),
+        }
+    }
+
+    pub fn pal() -> Self {
+        Self {
+            name: "pal".to_string(),
+            colors: PAL.map(rgb),
+        }
+    }
+
+    // Palette the V9938 starts with
+    pub fn v9938() -> Self {
+        let level = |v: u16| ((v & 0x07) * 255 / 7) as u8;
+        Self {
+            name: "v9938".to_string(),
+            colors: DEFAULT_PALETTE.map(|c| (level(c >> 4), level(c >> 8), level(c))),
+        }
+    }
+
+    // Luminance of the NTSC palette, as on a monochrome monitor
+    pub fn greyscale() -> Self {
+        Self {
+            name: "grey".to_string(),
+            colors: NTSC.map(|c| {
+                let (r, g, b) = rgb(c);
+                let y = (0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32).round() as u8;
+                (y, y, y)
+            }),
+        }
+    }
+
+    // Palettes that come with the emulator, in the order the UI cycles them
+    pub fn builtin() -> Vec<Self> {
+        vec![Self::ntsc(), Self::pal(), Self::v9938(), Self::greyscale()]
+    }
+
+    // A built-in palette by name, or else a palette file
+    pub fn find(name: &str) -> Result<Self, String> {
+        match Self::builtin().into_iter().find(|p| p.name == name) {
+            Some(palette) => Ok(palette),
+            None => Self::load(name),
+        }
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let content = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        Self::parse(path, &content)
+    }
+
+    pub fn parse(name: &str, content: &str) -> Result<Self, String> {
+        let mut colors = Vec::with_capacity(16);
+        for (n, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(';') {
+                continue;
+            }
+            let hex = line.trim_start_matches('#');
+            match u32::from_str_radix(hex, 16) {
+                Ok(color) if hex.len() == 6 => colors.push(rgb(color)),
+                _ => return Err(format!("{}:{}: bad colour {}", name, n + 1, line)),
+            }
+        }
+        let colors: [(u8, u8, u8); 16] = colors
+            .try_into()
+            .map_err(|c: Vec<_>| format!("{}: {} colours instead of 16", name, c.len()))?;
+        Ok(Self {
+            name: name.to_string(),
+            colors,
+        })
+    }
+}
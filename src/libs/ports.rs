@@ -6,11 +6,46 @@ pub struct Ports {
     vdp: Rc<RefCell<Vdp>>,
     ppi: Rc<RefCell<PPI>>,
     psg: PSG,
+    // Cycle of the CPU the VDP has run up to
+    vdp_cycles: u64,
 }
 
 impl Ports {
     pub fn new(vdp: Rc<RefCell<Vdp>>, ppi: Rc<RefCell<PPI>>, psg: PSG) -> Self {
-        Self { vdp, ppi, psg }
+        Self {
+            vdp,
+            ppi,
+            psg,
+            vdp_cycles: 0,
+        }
+    }
+
+    // Runs the sound chips for the cycles of the last instruction
+    pub fn run_sound(&mut self, cycles: u32) {
+        self.psg.run(cycles);
+    }
+
+    // Brings the sound chips up to the last instruction
+    pub fn flush_sound(&mut self) {
+        self.psg.flush();
+    }
+
+    // Runs the VDP command engine up to the given cycle of the CPU
+    pub fn run_vdp(&mut self, cycles: u64) {
+        if cycles > self.vdp_cycles {
+            let elapsed = (cycles - self.vdp_cycles) as u32;
+            self.vdp.borrow_mut().run(elapsed);
+            self.vdp_cycles = cycles;
+        }
+    }
+
+    pub fn set_vdp_cycles(&mut self, cycles: u64) {
+        self.vdp_cycles = cycles;
+    }
+
+    // Only the VDP interrupts the MSX
+    pub fn int_line(&self) -> bool {
+        self.vdp.borrow().int_line()
     }
 
     pub fn read_port(&self, address: u16) -> u8 {
@@ -19,6 +54,10 @@ impl Ports {
             0xa8..=0xab => self.ppi.borrow().read_port(ad),
             0xa0..=0xa2 => self.psg.read_port(ad),
             0x98..=0x9b => self.vdp.borrow_mut().read_port(ad),
+            0xb4..=0xb5 | 0xfc..=0xff => {
+                // RTC and memory mapper of the MSX2, not emulated
+                0xff
+            }
             _ => {
                 log::error!("ReadPort: {:02x}\n", ad);
                 0
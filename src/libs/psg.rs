@@ -17,42 +17,55 @@
 
 use std::{cell::RefCell, rc::Rc};
 
-use super::{
-    sound::{SoundDriver, SoundType},
-    tone_generator::ToneGenerator,
-};
+use super::{audio_mixer::AudioMixer, ay8910::AY8910, scc::SCC, sound::SoundType};
 
 pub struct PSG {
-    sound: Rc<RefCell<dyn SoundDriver>>,
     registers: [u8; 16],
     reg_next: u8,
     // bytes_cass: Vec<u8>,
-    sound_tones: [ToneGenerator; 3],
+    ay: AY8910,
+    // SCC of the cartridge, mixed into the same output
+    scc: Option<Rc<RefCell<SCC>>>,
+    mixer: AudioMixer,
 }
 
 impl PSG {
     pub fn new(sound_type: SoundType) -> Self {
-        let sound = sound_type.create();
-        for i in 0..3 {
-            let _ = sound.borrow_mut().add_channel(i);
-        }
         Self {
-            sound,
             registers: [0; 16],
             reg_next: 0,
             // bytes_cass: vec![0_u8],
-            sound_tones: [
-                ToneGenerator::new(),
-                ToneGenerator::new(),
-                ToneGenerator::new(),
-            ],
+            ay: AY8910::new(),
+            scc: None,
+            mixer: AudioMixer::new(sound_type.create()),
+        }
+    }
+    // Plays the SCC of a cartridge through the same sound output
+    pub fn attach_scc(&mut self, scc: Rc<RefCell<SCC>>) {
+        self.scc = Some(scc);
+    }
+
+    // Generates the sound of the cycles run by the CPU
+    pub fn run(&mut self, cycles: u32) {
+        match &self.scc {
+            Some(scc) => {
+                let mut scc = scc.borrow_mut();
+                self.mixer.run(cycles, &mut [&mut self.ay, &mut *scc]);
+            }
+            None => self.mixer.run(cycles, &mut [&mut self.ay]),
+        }
+    }
+
+    // Catches up before a register write changes the sound
+    pub fn flush(&mut self) {
+        match &self.scc {
+            Some(scc) => {
+                let mut scc = scc.borrow_mut();
+                self.mixer.flush(&mut [&mut self.ay, &mut *scc]);
+            }
+            None => self.mixer.flush(&mut [&mut self.ay]),
         }
     }
-    // pub fn feed_samples(&mut self, data: &mut [i16]) {
-    //     for i in 0..3 {
-    //         self.sound_tones[i].feed_samples(data);
-    //     }
-    // }
 
     pub fn write_port(&mut self, ad: u8, val: u8) {
         match ad {
@@ -62,13 +75,14 @@ impl PSG {
             }
             0xa1 => {
                 // Write value to port
-                self.registers[self.reg_next as usize] = val;
-                if self.reg_next < 14 {
-                    for i in 0..3 {
-                        self.do_tones(i)
-                    }
-
-                    // TODO: sound_doNoises()
+                let reg = (self.reg_next & 0x0f) as usize;
+                if reg < 14 {
+                    self.flush();
+                    self.ay.write_register(reg, val);
+                    // Unused bits read back as 0
+                    self.registers[reg] = self.ay.read_register(reg);
+                } else {
+                    self.registers[reg] = val;
                 }
             }
             _ => {
@@ -91,7 +105,7 @@ impl PSG {
                 // TODO: improve
                 return 0;
             }
-            return self.registers[self.reg_next as usize];
+            return self.registers[(self.reg_next & 0x0f) as usize];
         }
 
         log::error!("Sound, not implemented: in({:02x})", ad);
@@ -102,43 +116,6 @@ impl PSG {
         // log.Println("NextByte")
         0
     }
-
-    // TODO: envelopes
-    pub fn do_tones(&mut self, chn: usize) {
-        let freq =
-            (((self.registers[chn * 2 + 1] & 0x0f) as u16) << 8) | (self.registers[chn * 2]) as u16;
-        let envelope_enabled = (self.registers[8 + chn] & 0x10) != 0;
-        if freq > 0 {
-            let real_freq = 111861_f32 / (freq as f32);
-            if envelope_enabled {
-                // envFreq := (uint16(psg.registers[12]) << 8) | uint16(psg.registers[11])
-                // envShape := psg.registers[13] & 0x0F
-                // sound_tones[chn].setEnvelope(envFreq, envShape)
-            } else {
-                let volume = (self.registers[8 + chn] & 0x0F) as f32;
-                self.sound_tones[chn].set_volume(volume);
-                self.sound_tones[chn].set_frequency(real_freq);
-            }
-        }
-        let is_active = self.registers[7] & (0x01 << chn) == 0;
-        self.sound_tones[chn].activate(is_active);
-        if is_active {
-            if freq > 0 {
-                self.feed_samples(chn);
-                let _ = self.sound.borrow_mut().play(chn);
-            }
-        } else {
-            // unreachable!();
-            let _ = self.sound.borrow_mut().pause(chn);
-        }
-    }
-    fn feed_samples(&mut self, chn: usize) {
-        let freq = self.sound_tones[chn].get_frequency();
-        let _ = self
-            .sound
-            .borrow_mut()
-            .feed_samples(chn, freq, self.sound_tones[chn].get_volume());
-    }
 }
 
 // func psg_loadCassette(fileName string) {
@@ -150,40 +127,3 @@ impl PSG {
 // 	}
 // 	log.Println("PSG: Loaded cassete:", fileName)
 // }
-
-// func sound_doNoises() {
-// 	// if (self.registers[7] & 0x38) == 0x38 {
-// 	// 	// sound_noise.activate(false)
-// 	// } else {
-// 	// 	freq := int(self.registers[6] & 0x1F)
-// 	//
-// 	// 	if freq > 0 {
-// 	// 		realFreq := float32(111861) / float32(freq)
-// 	//
-// 	// 		var vol float32 = 0
-// 	// 		if (self.registers[7] & 0x20) == 0 {
-// 	// 			v := float32(self.registers[8] & 0x0F)
-// 	// 			if v > vol {
-// 	// 				vol = v
-// 	// 			}
-// 	// 		}
-// 	//
-// 	// 		if (self.registers[7] & 0x10) == 0 {
-// 	// 			v := float32(self.registers[9] & 0x0F)
-// 	// 			if v > vol {
-// 	// 				vol = v
-// 	// 			}
-// 	// 		}
-// 	//
-// 	// 		if (self.registers[7] & 0x04) == 0 {
-// 	// 			v := float32(self.registers[10] & 0x0F)
-// 	// 			if v > vol {
-// 	// 				vol = v
-// 	// 			}
-// 	// 		}
-// 	//
-// 	// 		// sound_noise.setParameters(realFreq, vol)
-// 	// 		// sound_noise.activate(true)
-// 	// 	}
-// 	// }
-// }
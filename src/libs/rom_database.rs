@@ -3,78 +3,161 @@ use quick_xml::reader::Reader;
 use std::collections::HashMap;
 use std::fs;
 
-const XML_DATABASE: &str = "softwaredb.xml";
+pub const XML_DATABASE: &str = "softwaredb.xml";
 
-pub fn search_in_rom_database(rom_to_search: &str) -> Result<String, String> {
-    let content = fs::read_to_string(XML_DATABASE).expect("something went wrong reading the file");
-    let xml = content.as_str();
-    let mut reader = Reader::from_str(xml);
-    reader.config_mut().trim_text(true);
+// One <dump> of a <software> in softwaredb.xml
+#[derive(Debug, Clone, Default)]
+pub struct RomEntry {
+    pub title: String,
+    pub company: String,
+    pub year: String,
+    pub country: String,
+    pub genmsxid: Option<u32>,
+    // <original value="..."> of the dump, None if not present
+    pub original: Option<bool>,
+    // Text of <original>, e.g. "GoodMSX" or "translated"
+    pub dump_status: String,
+    // Dump kind: "rom", "megarom" or "sccpluscart"
+    pub kind: String,
+    // Mapper type as written in the database ("NORMAL" if not given)
+    pub mapper: String,
+    // Start address hint (<start>) for plain ROMs
+    pub start: Option<u16>,
+    // Boot hint (<boot>), e.g. "scc+"
+    pub boot: Option<String>,
+    pub remark: String,
+    pub hash: String,
+}
+
+pub struct RomDatabase {
+    entries: Vec<RomEntry>,
+    by_hash: HashMap<String, usize>,
+}
+
+impl Default for RomDatabase {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RomDatabase {
+    // An empty database: every lookup misses
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            by_hash: HashMap::new(),
+        }
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let content = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        Self::parse(&content)
+    }
+
+    pub fn parse(xml: &str) -> Result<Self, String> {
+        let mut reader = Reader::from_str(xml);
+        reader.config_mut().trim_text(true);
 
-    let mut buf = Vec::new();
-    let mut dict: HashMap<String, String> = HashMap::new();
-    let mut is_in_rom = false;
-    let mut is_in_megarom = false;
-    let mut is_in_hash = false;
-    let mut is_in_type = false;
-    let mut d_hash = String::new();
-    let mut d_type = String::new();
-    // The `Reader` does not implement `Iterator` because it outputs borrowed data (`Cow`s)
-    loop {
-        // NOTE: this is the generic case when we don't know about the input BufRead.
-        // when the input is a &str or a &[u8], we don't actually need to use another
-        // buffer, we could directly call `reader.read_event()`
-        match reader.read_event_into(&mut buf) {
-            Err(e) => panic!("Error at position {}: {:?}", reader.error_position(), e),
-            // exits the loop when reaching end of file
-            Ok(Event::Eof) => break,
-            Ok(Event::Start(e)) => match e.name().as_ref() {
-                b"rom" => is_in_rom = true,
-                b"megarom" => is_in_megarom = true,
-                b"hash" => is_in_hash = true,
-                b"type" => is_in_type = true,
-                _ => {}
-            },
-            Ok(Event::End(e)) => match e.name().as_ref() {
-                b"dump" => {
-                    dict.insert(
-                        d_hash.clone(),
-                        if d_type.is_empty() {
-                            "NORMAL".to_string()
-                        } else {
-                            d_type.clone()
-                        },
-                    );
-                    d_hash.clear();
-                    d_type.clear();
+        let mut db = Self::new();
+        let mut software = RomEntry::default();
+        let mut dump = RomEntry::default();
+        let mut in_dump = false;
+        let mut tag: Vec<u8> = Vec::new();
+        // The `Reader` does not implement `Iterator` because it outputs borrowed data (`Cow`s)
+        loop {
+            match reader.read_event() {
+                Err(e) => {
+                    return Err(format!(
+                        "Error at position {}: {:?}",
+                        reader.error_position(),
+                        e
+                    ))
                 }
-                b"rom" => is_in_rom = false,
-                b"megarom" => is_in_megarom = false,
-                b"hash" => is_in_hash = false,
-                b"type" => is_in_type = false,
-                _ => {}
-            },
-            Ok(Event::Text(e)) => {
-                if is_in_rom && is_in_hash {
-                    d_hash = e.unescape().unwrap().into_owned();
+                // exits the loop when reaching end of file
+                Ok(Event::Eof) => break,
+                Ok(Event::Start(e)) => {
+                    match e.name().as_ref() {
+                        b"software" => software = RomEntry::default(),
+                        b"dump" => {
+                            in_dump = true;
+                            dump = software.clone();
+                        }
+                        b"rom" | b"megarom" | b"sccpluscart" if in_dump => {
+                            dump.kind = String::from_utf8_lossy(e.name().as_ref()).into_owned();
+                        }
+                        b"original" if in_dump => {
+                            if let Ok(Some(attr)) = e.try_get_attribute("value") {
+                                dump.original = Some(attr.value.as_ref() == b"true");
+                            }
+                        }
+                        _ => {}
+                    }
+                    tag = e.name().as_ref().to_vec();
+                }
+                Ok(Event::End(e)) => {
+                    if e.name().as_ref() == b"dump" {
+                        in_dump = false;
+                        if dump.mapper.is_empty() {
+                            dump.mapper = "NORMAL".to_string();
+                        }
+                        if !dump.hash.is_empty() {
+                            db.by_hash.insert(dump.hash.clone(), db.entries.len());
+                            db.entries.push(std::mem::take(&mut dump));
+                        }
+                    }
+                    tag.clear();
                 }
-                if is_in_megarom {
-                    if is_in_hash {
-                        d_hash = e.unescape().unwrap().into_owned();
-                    } else if is_in_type {
-                        d_type = e.unescape().unwrap().into_owned();
+                Ok(Event::Text(e)) => {
+                    let text = e.unescape().map_err(|e| e.to_string())?.into_owned();
+                    let entry = if in_dump { &mut dump } else { &mut software };
+                    match tag.as_slice() {
+                        b"title" => entry.title = text,
+                        b"company" => entry.company = text,
+                        b"year" => entry.year = text,
+                        b"country" => entry.country = text,
+                        b"genmsxid" => entry.genmsxid = text.parse().ok(),
+                        b"original" => entry.dump_status = text,
+                        b"type" => entry.mapper = text,
+                        b"start" => {
+                            entry.start =
+                                u16::from_str_radix(text.trim_start_matches("0x"), 16).ok()
+                        }
+                        b"boot" => entry.boot = Some(text),
+                        b"remark" => entry.remark = text,
+                        b"hash" => entry.hash = text,
+                        _ => {}
                     }
                 }
+                // There are several other `Event`s we do not consider here
+                _ => (),
             }
-            // There are several other `Event`s we do not consider here
-            _ => (),
         }
-        // if we don't keep a borrow elsewhere, we can clear the buffer to keep memory usage low
-        buf.clear();
+        Ok(db)
+    }
+
+    // Looks up a dump by its SHA1 (lowercase hex)
+    pub fn find(&self, hash: &str) -> Option<&RomEntry> {
+        self.by_hash.get(hash).map(|i| &self.entries[*i])
+    }
+
+    // All dumps whose title contains text (case insensitive)
+    pub fn search_title(&self, text: &str) -> Vec<&RomEntry> {
+        let text = text.to_lowercase();
+        self.entries
+            .iter()
+            .filter(|e| e.title.to_lowercase().contains(&text))
+            .collect()
+    }
+
+    pub fn entries(&self) -> &[RomEntry] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
     }
 
-    if dict.contains_key(rom_to_search) {
-        return Ok(dict.get(rom_to_search).unwrap().clone());
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
     }
-    Err("Not found".to_string())
 }
@@ -0,0 +1,150 @@
+/*
+
+    Konami SCC (Sound Creative Chip)
+
+    Registers, mirrored every 0x100 bytes in the 0x9800-0x9FFF window:
+
+    0x00-0x7f   waveforms of channels 1-4 (32 signed samples each)
+                channel 5 shares the waveform of channel 4
+    0x80-0x89   frequency of channels 1-5 (12 bits, low byte first)
+    0x8a-0x8e   volume of channels 1-5 (4 bits)
+    0x8f        channel enable (bits 0-4)
+    0x90-0x9f   mirror of 0x80-0x8f
+    0xa0-0xbf   read: waveform of channel 5
+    0xe0-0xff   deformation register (not emulated)
+
+*/
+
+use super::audio_mixer::SoundChip;
+
+pub const SCC_CHANNELS: usize = 5;
+
+const SCC_CLOCK: f32 = 3579545.0;
+
+pub struct SCC {
+    waveforms: [[u8; 32]; 4],
+    periods: [u16; SCC_CHANNELS],
+    volumes: [u8; SCC_CHANNELS],
+    enabled: u8,
+    // Position in the waveform and cycles spent on it
+    positions: [usize; SCC_CHANNELS],
+    counters: [u32; SCC_CHANNELS],
+    // Output integrated over the cycles since the last sample
+    output_sum: f32,
+    output_cycles: u32,
+}
+
+impl Default for SCC {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SCC {
+    pub fn new() -> Self {
+        Self {
+            waveforms: [[0; 32]; 4],
+            periods: [0; SCC_CHANNELS],
+            volumes: [0; SCC_CHANNELS],
+            enabled: 0,
+            positions: [0; SCC_CHANNELS],
+            counters: [0; SCC_CHANNELS],
+            output_sum: 0.0,
+            output_cycles: 0,
+        }
+    }
+
+    // address is relative to 0x9800
+    pub fn read_byte(&self, address: u16) -> u8 {
+        let reg = (address & 0xff) as usize;
+        match reg {
+            0x00..=0x7f => self.waveforms[reg >> 5][reg & 0x1f],
+            0xa0..=0xbf => self.waveforms[3][reg & 0x1f],
+            _ => 0xff,
+        }
+    }
+
+    // address is relative to 0x9800
+    pub fn write_byte(&mut self, address: u16, value: u8) {
+        let reg = (address & 0xff) as usize;
+        match reg {
+            0x00..=0x7f => {
+                self.waveforms[reg >> 5][reg & 0x1f] = value;
+            }
+            0x80..=0x9f => {
+                let reg = reg & 0x0f;
+                match reg {
+                    0x00..=0x09 => {
+                        let chn = reg >> 1;
+                        if reg & 0x01 == 0 {
+                            self.periods[chn] = (self.periods[chn] & 0xf00) | value as u16;
+                        } else {
+                            self.periods[chn] =
+                                (self.periods[chn] & 0x0ff) | (((value & 0x0f) as u16) << 8);
+                        }
+                    }
+                    0x0a..=0x0e => {
+                        let chn = reg - 0x0a;
+                        self.volumes[chn] = value & 0x0f;
+                    }
+                    _ => {
+                        self.enabled = value & 0x1f;
+                    }
+                }
+            }
+            _ => {
+                // Channel 5 waveform is read only, deformation is ignored
+            }
+        }
+    }
+
+    pub fn get_frequency(&self, chn: usize) -> f32 {
+        SCC_CLOCK / (32.0 * (self.periods[chn] as f32 + 1.0))
+    }
+
+    // Periods below 9 are too high to be heard
+    fn is_active(&self, chn: usize) -> bool {
+        self.enabled & (0x01 << chn) != 0 && self.periods[chn] > 8
+    }
+
+    // Current output of a channel, between -1 and 1
+    fn channel_output(&self, chn: usize) -> f32 {
+        // Channel 5 shares the waveform of channel 4
+        let sample = self.waveforms[chn.min(3)][self.positions[chn]] as i8;
+        sample as f32 / 128.0 * self.volumes[chn] as f32 / 15.0
+    }
+}
+
+impl SoundChip for SCC {
+    fn advance(&mut self, cycles: u32) {
+        for chn in 0..SCC_CHANNELS {
+            if !self.is_active(chn) {
+                continue;
+            }
+            // The position moves every period + 1 cycles
+            let step = self.periods[chn] as u32 + 1;
+            let mut cycles = cycles;
+            while cycles > 0 {
+                let run = cycles.min(step.saturating_sub(self.counters[chn]).max(1));
+                self.output_sum += self.channel_output(chn) * run as f32;
+                self.counters[chn] += run;
+                cycles -= run;
+                if self.counters[chn] >= step {
+                    self.counters[chn] = 0;
+                    self.positions[chn] = (self.positions[chn] + 1) & 0x1f;
+                }
+            }
+        }
+        self.output_cycles += cycles;
+    }
+
+    fn take_sample(&mut self) -> f32 {
+        if self.output_cycles == 0 {
+            return 0.0;
+        }
+        let sample = self.output_sum / (self.output_cycles as f32 * SCC_CHANNELS as f32);
+        self.output_sum = 0.0;
+        self.output_cycles = 0;
+        sample
+    }
+}
@@ -1,7 +1,15 @@
 pub const FREQUENCY: i32 = 22050;
 // pub const FREQUENCY: i32 = 44100;
 
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+// Samples kept in the ring buffer at most (250 msec)
+const RING_CAPACITY: usize = FREQUENCY as usize / 4;
+
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    rc::Rc,
+    sync::{Arc, Mutex},
+};
 
 use sdl2::{
     audio::{AudioCallback, AudioDevice, AudioSpecDesired},
@@ -21,12 +29,14 @@ impl SoundType {
     }
 }
 
+// A single mono stream of samples between -1 and 1
 pub trait SoundDriver {
-    fn add_channel(&mut self, channel: usize) -> Result<String, String>;
-    // pub fn feed_samples(&mut self, channel: usize, wave: &[i16]) -> Result<String, String> {
-    fn feed_samples(&mut self, channel: usize, freq: f32, volume: f32) -> Result<String, String>;
-    fn pause(&mut self, channel: usize) -> Result<String, String>;
-    fn play(&mut self, channel: usize) -> Result<String, String>;
+    // Appends samples to the stream
+    fn queue_samples(&mut self, samples: &[f32]) -> Result<String, String>;
+    // Samples waiting to be played
+    fn queued(&self) -> usize;
+    fn pause(&mut self) -> Result<String, String>;
+    fn play(&mut self) -> Result<String, String>;
 }
 
 pub struct NullSound {}
@@ -43,79 +53,77 @@ impl NullSound {
     }
 }
 impl SoundDriver for NullSound {
-    fn add_channel(&mut self, _channel: usize) -> Result<String, String> {
+    fn queue_samples(&mut self, _samples: &[f32]) -> Result<String, String> {
         Ok("null sound driver".to_string())
     }
-    fn feed_samples(
-        &mut self,
-        _channel: usize,
-        _freq: f32,
-        _volume: f32,
-    ) -> Result<String, String> {
-        Ok("null sound driver".to_string())
+    fn queued(&self) -> usize {
+        0
     }
-    fn pause(&mut self, _channel: usize) -> Result<String, String> {
+    fn pause(&mut self) -> Result<String, String> {
         Ok("null sound driver".to_string())
     }
-    fn play(&mut self, _channel: usize) -> Result<String, String> {
+    fn play(&mut self) -> Result<String, String> {
         Ok("null sound driver".to_string())
     }
 }
 
-#[allow(dead_code)]
-enum Tone {
-    Square,
-    Sin,
+// Samples produced by the emulation and consumed by the audio callback
+pub struct SampleRing {
+    samples: VecDeque<f32>,
+    capacity: usize,
 }
 
-struct Oscillator {
-    current_step: f32,
-    step_size: f32,
-    volume: f32,
-    mode: Tone,
-}
-impl Oscillator {
-    pub fn new(rate: f32, volume: f32, mode: Tone) -> Self {
+impl SampleRing {
+    pub fn new(capacity: usize) -> Self {
         Self {
-            current_step: 0.0,
-            volume,
-            step_size: 2.0 * std::f32::consts::PI / rate,
-            mode,
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
         }
     }
-    pub fn next(&mut self) -> f32 {
-        self.current_step += self.step_size;
-        match self.mode {
-            Tone::Square => {
-                if f32::sin(self.current_step) > 0.0 {
-                    self.volume
-                } else {
-                    -self.volume
-                }
-            }
-            Tone::Sin => f32::sin(self.current_step) * self.volume,
-        }
+
+    // Samples that do not fit are dropped
+    pub fn push(&mut self, samples: &[f32]) {
+        let room = self.capacity - self.samples.len();
+        self.samples.extend(samples.iter().take(room));
     }
-    pub fn modify(&mut self, rate: f32, volume: f32) {
-        self.step_size = 2.0 * std::f32::consts::PI / rate;
-        self.volume = volume;
+
+    pub fn pop(&mut self) -> Option<f32> {
+        self.samples.pop_front()
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
     }
 }
 
-impl AudioCallback for Oscillator {
+struct Stream {
+    ring: Arc<Mutex<SampleRing>>,
+    // Repeated on under-runs, so they do not click
+    last: f32,
+}
+
+impl AudioCallback for Stream {
     type Channel = f32;
 
     fn callback(&mut self, out: &mut [Self::Channel]) {
+        let mut ring = self.ring.lock().unwrap();
         for x in out.iter_mut() {
-            *x = self.next();
+            if let Some(sample) = ring.pop() {
+                self.last = sample;
+            }
+            *x = self.last;
         }
     }
 }
 
 pub struct Sound {
-    audio_subsystem: AudioSubsystem,
-    // devices: HashMap<usize, AudioQueue<i16>>,
-    devices: HashMap<usize, AudioDevice<Oscillator>>,
+    _audio_subsystem: AudioSubsystem,
+    device: AudioDevice<Stream>,
+    ring: Arc<Mutex<SampleRing>>,
 }
 
 impl Default for Sound {
@@ -128,65 +136,40 @@ impl Sound {
     pub fn new() -> Self {
         let sdl_context = sdl2::init().unwrap();
         let audio_subsystem = sdl_context.audio().unwrap();
-        Self {
-            audio_subsystem,
-            devices: HashMap::new(),
-        }
-    }
-}
-impl SoundDriver for Sound {
-    fn add_channel(&mut self, channel: usize) -> Result<String, String> {
-        // log::info!("add_channel channel:{}", channel);
-        if self.devices.contains_key(&channel) {
-            return Err(format!("cannot add same channel {}", channel));
-        }
+        let ring = Arc::new(Mutex::new(SampleRing::new(RING_CAPACITY)));
         let desired_spec = AudioSpecDesired {
             freq: Some(FREQUENCY),
             channels: Some(1), // mono
             samples: Some(512),
         };
-
-        let device = self
-            .audio_subsystem
-            .open_playback(None, &desired_spec, |_spec| {
-                // initialize the audio callback
-                Oscillator::new(1.0, 0.0, Tone::Sin)
-            })?;
-        self.devices.insert(channel, device);
-        Ok("channel added".to_string())
-    }
-    // pub fn feed_samples(&mut self, channel: usize, wave: &[i16]) -> Result<String, String> {
-    fn feed_samples(&mut self, channel: usize, freq: f32, volume: f32) -> Result<String, String> {
-        let audio_queue = self
-            .devices
-            .get_mut(&channel)
-            .ok_or(format!("no such channel {}", channel))?;
-
-        {
-            audio_queue
-                .lock()
-                .modify(FREQUENCY as f32 / freq, volume / 15.0);
+        let device = audio_subsystem
+            .open_playback(None, &desired_spec, |_spec| Stream {
+                ring: ring.clone(),
+                last: 0.0,
+            })
+            .unwrap();
+        Self {
+            _audio_subsystem: audio_subsystem,
+            device,
+            ring,
         }
-
+    }
+}
+impl SoundDriver for Sound {
+    fn queue_samples(&mut self, samples: &[f32]) -> Result<String, String> {
+        self.ring.lock().unwrap().push(samples);
         Ok("sound queue".to_string())
     }
-    fn pause(&mut self, channel: usize) -> Result<String, String> {
-        let audio_queue = self
-            .devices
-            .get(&channel)
-            .ok_or(format!("no such channel {}", channel))?;
-        audio_queue.pause();
+    fn queued(&self) -> usize {
+        self.ring.lock().unwrap().len()
+    }
+    fn pause(&mut self) -> Result<String, String> {
+        self.device.pause();
         Ok("sound pause".to_string())
     }
-    fn play(&mut self, channel: usize) -> Result<String, String> {
-        let audio_queue = self
-            .devices
-            .get_mut(&channel)
-            .ok_or(format!("no such channel {}", channel))?;
-
+    fn play(&mut self) -> Result<String, String> {
         // Start playback
-        audio_queue.resume();
-
+        self.device.resume();
         Ok("sound play".to_string())
     }
 }
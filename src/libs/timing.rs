@@ -0,0 +1,57 @@
+/*
+
+    Video timing of the machine
+
+    The VDP draws a line every 1368 of its cycles, 228 Z80 cycles, on both
+    systems. A NTSC frame has 262 lines (59.92Hz, sold as 60Hz), a PAL
+    frame 313 lines (50.16Hz). The frame interrupt comes at the end of the
+    active display, so every frame starts with the bottom border and the
+    vertical blanking.
+
+*/
+
+use super::audio_mixer::Z80_CLOCK;
+
+// Z80 cycles of a line of the display
+pub const CYCLES_PER_LINE: u64 = 228;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Timing {
+    // 60Hz machines (Japan, USA, Brazil...)
+    #[default]
+    NTSC,
+    // 50Hz machines (Europe)
+    PAL,
+}
+
+impl Timing {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.to_lowercase().as_str() {
+            "ntsc" | "60" => Ok(Timing::NTSC),
+            "pal" | "50" => Ok(Timing::PAL),
+            _ => Err(format!("unknown video timing {}", name)),
+        }
+    }
+
+    pub const fn lines_per_frame(self) -> usize {
+        match self {
+            Timing::NTSC => 262,
+            Timing::PAL => 313,
+        }
+    }
+
+    pub const fn cycles_per_frame(self) -> u64 {
+        CYCLES_PER_LINE * self.lines_per_frame() as u64
+    }
+
+    // Frames per second
+    pub fn frame_rate(self) -> f64 {
+        Z80_CLOCK as f64 / self.cycles_per_frame() as f64
+    }
+
+    // First line of a display with the given lines, counted from the
+    // interrupt
+    pub const fn first_active_line(self, active_lines: usize) -> usize {
+        self.lines_per_frame() - active_lines
+    }
+}
@@ -2,25 +2,143 @@ use serde::{Deserialize, Serialize};
 use std::{cell::RefCell, rc::Rc};
 
 use super::graphics::{GraphicsDriver, GraphicsType};
+use super::palette::Palette;
+use super::timing::Timing;
+use super::vdp_command::{CommandEngine, CommandMode};
 
 pub(crate) const SCREEN0: u8 = 0;
 pub(crate) const SCREEN1: u8 = 1;
 pub(crate) const SCREEN2: u8 = 2;
 pub(crate) const SCREEN3: u8 = 3;
+// V9938 modes
+pub(crate) const SCREEN4: u8 = 4;
+pub(crate) const SCREEN5: u8 = 5;
+pub(crate) const SCREEN6: u8 = 6;
+pub(crate) const SCREEN7: u8 = 7;
+pub(crate) const SCREEN8: u8 = 8;
+pub(crate) const MODE_TEXT80: u8 = 0x20; // M1+M4
+                                         // Undocumented TMS9918 modes
+pub(crate) const MODE_TEXT_BITMAP: u8 = 0x10; // M1+M3
+pub(crate) const MODE_TEXT_STRIPES: u8 = 0x11; // M1+M2(+M3)
+pub(crate) const MODE_MULTICOLOR_BITMAP: u8 = 0x12; // M2+M3
+
+// NTSC: 192 active lines, bottom border and blanking, top border
+pub const LINES_PER_FRAME: usize = Timing::NTSC.lines_per_frame();
+pub const ACTIVE_LINES: usize = 192;
+// With LN set the V9938 shows 212 lines
+pub const ACTIVE_LINES_212: usize = 212;
+// First line of the active display of a NTSC frame, counted from the
+// interrupt
+pub const FIRST_ACTIVE_LINE: usize = Timing::NTSC.first_active_line(ACTIVE_LINES);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VdpModel {
+    // MSX1
+    TMS9918,
+    // MSX2
+    V9938,
+}
+
+// Border around the active display, in pixels and lines
+#[derive(Clone, Copy, Debug)]
+struct Border {
+    left: usize,
+    right: usize,
+    top: usize,
+    bottom: usize,
+}
+
+const BORDER: Border = Border {
+    left: 8,
+    right: 8,
+    top: 8,
+    bottom: 8,
+};
+
+// Whole border of the TMS9918 on a NTSC screen
+const OVERSCAN: Border = Border {
+    left: 13,
+    right: 15,
+    top: 27,
+    bottom: 24,
+};
+
+// Widest line, with the V9938 high resolution pixels
+const MAX_LINE_WIDTH: usize = (13 + 256 + 15) * 2;
+
+// Status register flags
+const STATUS_5S: u8 = 0x40;
+const STATUS_COLLISION: u8 = 0x20;
+// S#1
+const STATUS_FH: u8 = 0x01;
+// S#2, the command engine adds TR, BD and CE
+const STATUS_VR: u8 = 0x40;
+const STATUS_HR: u8 = 0x20;
+const STATUS_2_FIXED: u8 = 0x0c;
+
+const MAX_SPRITES_PER_LINE: usize = 4;
+const MAX_SPRITES_PER_LINE_MODE2: usize = 8;
+
+// Palette of the V9938 after reset, 0RRR0BBB and 00000GGG
+pub(crate) const DEFAULT_PALETTE: [u16; 16] = [
+    0x000, 0x000, 0x611, 0x733, 0x117, 0x327, 0x151, 0x627, 0x171, 0x373, 0x661, 0x664, 0x411,
+    0x265, 0x555, 0x777,
+];
+
+// Part of a sprite that falls on the line being drawn
+struct SpriteLine {
+    x: i32,
+    // Pixels of the line, from bit 15
+    pattern: u16,
+    size: usize,
+    magnif: usize,
+    color: u8,
+    // Sprite mode 2: colours are ORed with the sprite above (CC), and
+    // collisions are ignored (IC)
+    cc: bool,
+    ic: bool,
+}
+
+impl SpriteLine {
+    fn covers(&self, x: i32) -> bool {
+        let offset = x - self.x;
+        offset >= 0
+            && offset < (self.size * self.magnif) as i32
+            && self.pattern & (0x8000 >> (offset as usize / self.magnif)) != 0
+    }
+}
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
 pub struct VdpData {
     screen_enabled: bool,
     screen_mode: u8,
     value_read: u8,
     write_state: bool,
     pub enabled_interrupts: bool,
-    registers: [u8; 10],
+    registers: Vec<u8>,
     write_to_vram: bool,
-    vram: Vec<u8>, //[u8; 0x10000]),
-    pointer_vram: u16,
+    vram: Vec<u8>, //[u8; 0x20000]),
+    pointer_vram: u32,
+    read_buffer: u8,
     status_reg: u8,
-    magnif_num: u8,
+    // V9938
+    palette: [u16; 16],
+    // First byte written to the palette port
+    palette_latch: Option<u8>,
+    // S#1
+    status_fh: bool,
+    // Toggled by every read of S#2
+    status_hr: bool,
+    // Outside of the active display
+    retrace: bool,
+    // S#3-S#6, where the first collision happened
+    collision_x: u16,
+    collision_y: u16,
+    // Text 80 columns: blink colours shown, frames to the next change
+    blink_on: bool,
+    blink_frames: u8,
+    command: CommandEngine,
 }
 impl Default for VdpData {
     fn default() -> Self {
@@ -36,12 +154,22 @@ impl VdpData {
             value_read: 0,
             write_state: false,
             enabled_interrupts: false,
-            registers: [0; 10],
+            registers: vec![0; 64],
             write_to_vram: false,
-            vram: vec![0; 0x10000],
+            vram: vec![0; 0x20000],
             pointer_vram: 0,
+            read_buffer: 0,
             status_reg: 0,
-            magnif_num: 0,
+            palette: DEFAULT_PALETTE,
+            palette_latch: None,
+            status_fh: false,
+            status_hr: false,
+            retrace: false,
+            collision_x: 0,
+            collision_y: 0,
+            blink_on: false,
+            blink_frames: 0,
+            command: CommandEngine::new(),
         }
     }
 }
@@ -49,19 +177,57 @@ impl VdpData {
 pub struct Vdp {
     pub(crate) data: VdpData,
     graphics: Rc<RefCell<dyn GraphicsDriver>>,
+    border: Border,
+    model: VdpModel,
+    timing: Timing,
 }
 
 impl Vdp {
     pub fn new(graphics_type: GraphicsType, quality: bool) -> Self {
-        let graphics = graphics_type.create(quality);
+        Self::with_driver(graphics_type.create(quality))
+    }
+
+    pub fn with_driver(graphics: Rc<RefCell<dyn GraphicsDriver>>) -> Self {
         Self {
             data: VdpData::default(),
             graphics,
+            border: BORDER,
+            model: VdpModel::TMS9918,
+            timing: Timing::NTSC,
         }
     }
 
     pub fn init_graphics(&mut self) {
-        self.graphics.borrow_mut().init();
+        let (width, height) = (self.frame_width() as u32, self.frame_height() as u32);
+        let mut graphics = self.graphics.borrow_mut();
+        graphics.set_frame_size(width, height);
+        graphics.init();
+    }
+
+    // Selects the chip, before init_graphics
+    pub fn set_model(&mut self, model: VdpModel) {
+        self.model = model;
+        if model == VdpModel::V9938 {
+            self.data.palette = DEFAULT_PALETTE;
+            self.update_palette();
+        }
+    }
+
+    pub fn get_model(&self) -> VdpModel {
+        self.model
+    }
+
+    // 50 or 60Hz frames
+    pub fn set_timing(&mut self, timing: Timing) {
+        self.timing = timing;
+    }
+
+    pub fn get_timing(&self) -> Timing {
+        self.timing
+    }
+
+    fn is_v9938(&self) -> bool {
+        self.model == VdpModel::V9938
     }
 
     // pub fn save_state(&self) -> Vdp {
@@ -79,13 +245,20 @@ impl Vdp {
     //     self.data.vram = vdp2.vram.clone();
     //     self.data.pointer_vram = vdp2.pointer_vram;
     //     self.data.status_reg = vdp2.status_reg;
-    //     self.data.magnif_num = vdp2.magnif_num;
     // }
 
     pub fn set_frame_flag(&mut self) {
         self.data.status_reg |= 0x80;
     }
 
+    // The VDP asserts INT while F is set with IE0, or FH with IE1, until
+    // the status register with the flag is read
+    pub fn int_line(&self) -> bool {
+        let frame = self.data.status_reg & 0x80 != 0 && self.data.enabled_interrupts;
+        let line = self.data.status_fh && self.is_v9938() && self.data.registers[0] & 0x10 != 0;
+        frame || line
+    }
+
     pub fn update_registers(&mut self) {
         self.data.screen_enabled = self.data.registers[1] & 0x40 != 0;
         self.data.enabled_interrupts = self.data.registers[1] & 0x20 != 0;
@@ -97,17 +270,29 @@ impl Vdp {
         let scm = self.data.screen_mode;
 
         if !m4 && !m5 {
-            if !m1 && !m2 && !m3 {
-                self.data.screen_mode = SCREEN1;
-            }
-            if m1 && !m2 && !m3 {
-                self.data.screen_mode = SCREEN0;
-            }
-            if !m1 && m2 && !m3 {
-                self.data.screen_mode = SCREEN3;
-            }
-            if !m1 && !m2 && m3 {
-                self.data.screen_mode = SCREEN2;
+            self.data.screen_mode = match (m1, m2, m3) {
+                (false, false, false) => SCREEN1,
+                (true, false, false) => SCREEN0,
+                (false, true, false) => SCREEN3,
+                (false, false, true) => SCREEN2,
+                // Undocumented combinations, M1 wins over M2
+                (true, false, true) => MODE_TEXT_BITMAP,
+                (true, true, _) => MODE_TEXT_STRIPES,
+                (false, true, true) => MODE_MULTICOLOR_BITMAP,
+            };
+        } else if self.is_v9938() {
+            let mode = match (m1, m5, m4, m3) {
+                (true, false, true, false) => Some(MODE_TEXT80),
+                (false, false, true, false) => Some(SCREEN4),
+                (false, false, true, true) => Some(SCREEN5),
+                (false, true, false, false) => Some(SCREEN6),
+                (false, true, false, true) => Some(SCREEN7),
+                (false, true, true, true) => Some(SCREEN8),
+                _ => None,
+            };
+            match mode {
+                Some(mode) => self.data.screen_mode = mode,
+                None => log::warn!("VDP: unknown screen mode"),
             }
         }
         if scm != self.data.screen_mode {
@@ -118,35 +303,92 @@ impl Vdp {
         }
     }
 
-    pub fn write_port(&mut self, ad: u8, mut val: u8) {
+    fn write_register(&mut self, reg: usize, value: u8) {
+        // R#47 and above do not exist
+        if reg >= 47 {
+            return;
+        }
+        self.data.registers[reg] = value;
+        match reg {
+            0 | 1 => self.update_registers(),
+            14 => {
+                self.data.pointer_vram =
+                    (self.data.pointer_vram & 0x3fff) | ((value as u32 & 0x07) << 14)
+            }
+            16 => self.data.palette_latch = None,
+            32..=46 => {
+                let mode = self.command_mode();
+                self.data.command.write_register(reg - 32, value, mode);
+            }
+            _ => {}
+        }
+    }
+
+    pub fn write_port(&mut self, ad: u8, val: u8) {
         // log::info!("VDP: Out({:02x}, {:02x})", ad, val);
         match ad {
             0x99 => {
                 if !self.data.write_state {
                     self.data.value_read = val;
                     self.data.write_state = true;
+                    if !self.is_v9938() {
+                        // The first byte goes straight to the low byte of
+                        // the address
+                        self.data.pointer_vram = (self.data.pointer_vram & 0x3f00) | val as u32;
+                    }
                 } else {
                     self.data.write_state = false;
                     // Bit 7 must be 1 for write
                     if val & 0x80 != 0 {
-                        let regn = val - 128;
-                        self.data.registers[regn as usize] = self.data.value_read;
-                        self.update_registers();
+                        // Only 3 bits of the register number are decoded by
+                        // the TMS9918
+                        let regn = if self.is_v9938() {
+                            val & 0x3f
+                        } else {
+                            val & 0x07
+                        };
+                        self.write_register(regn as usize, self.data.value_read);
                     } else {
                         self.data.write_to_vram = val & 0x40 != 0;
-                        val &= 0xBF;
-                        self.data.pointer_vram = 0;
-                        self.data.pointer_vram |= self.data.value_read as u16;
-                        self.data.pointer_vram |= (val as u16) << 8;
+                        // R#14 holds the high bits of the address
+                        let high = if self.is_v9938() {
+                            (self.data.registers[14] as u32 & 0x07) << 14
+                        } else {
+                            0
+                        };
+                        self.data.pointer_vram =
+                            high | (((val & 0x3f) as u32) << 8) | self.data.value_read as u32;
+                        if !self.data.write_to_vram {
+                            // Setting a read address fetches the first byte
+                            self.read_ahead();
+                        }
                     }
                 }
             }
             0x98 => {
                 // Writing to VRAM
                 // log::info!("Writing to VRAM: {:04x} -> {:02x}", self.data.pointer_vram, val);
+                self.data.write_state = false;
                 self.data.vram[self.data.pointer_vram as usize] = val;
-                self.data.pointer_vram += 1;
+                // The written value also ends up in the read buffer
+                self.data.read_buffer = val;
+                self.increment_pointer();
             }
+            0x9a if self.is_v9938() => self.write_palette(val),
+            0x9b if self.is_v9938() => {
+                // Indirect access to the register set in R#17, which
+                // moves to the next one unless bit 7 is set
+                let r17 = self.data.registers[17];
+                let reg = (r17 & 0x3f) as usize;
+                if reg != 17 {
+                    self.write_register(reg, val);
+                }
+                if r17 & 0x80 == 0 {
+                    self.data.registers[17] = (r17 + 1) & 0x3f;
+                }
+            }
+            // The TMS9918 does not decode these ports
+            0x9a | 0x9b => {}
             _ => {
                 log::error!("Not implemented: VDP: Out({:02x}, {:02x})", ad, val);
                 unimplemented!()
@@ -158,18 +400,25 @@ impl Vdp {
         match ad {
             0x98 => {
                 // Reading from VRAM
-                //log.Printf("Reading from VRAM: %04x", vdp.pointerVRAM)
-                let r = self.data.vram[self.data.pointer_vram as usize];
-                self.data.pointer_vram += 1;
+                // The byte was fetched by the previous access
+                self.data.write_state = false;
+                let r = self.data.read_buffer;
+                self.read_ahead();
                 r
             }
             0x99 => {
                 // Reading status register
-                // TODO: look at it carefully....
+                self.data.write_state = false;
+                if self.is_v9938() && self.data.registers[15] & 0x0f != 0 {
+                    return self.read_status(self.data.registers[15] & 0x0f);
+                }
                 let r = self.data.status_reg;
-                self.data.status_reg &= 0x7F; // Clear frame flag
+                // Clear frame, 5th sprite and collision flags
+                self.data.status_reg &= 0x1F;
                 r
             }
+            // Nothing drives the bus on the TMS9918 either
+            0x9a | 0x9b => 0xff,
             _ => {
                 log::error!("Not implemented: VDP: In({:02x})", ad);
                 0
@@ -177,300 +426,748 @@ impl Vdp {
         }
     }
 
+    // S#1-S#9 of the V9938
+    fn read_status(&mut self, reg: u8) -> u8 {
+        match reg {
+            1 => {
+                // ID 0 is the V9938
+                let r = if self.data.status_fh { STATUS_FH } else { 0 };
+                self.data.status_fh = false;
+                r
+            }
+            2 => {
+                // Without a beam position, HR toggles so polling loops end
+                self.data.status_hr = !self.data.status_hr;
+                let mut r = STATUS_2_FIXED | self.data.command.status();
+                if self.data.retrace {
+                    r |= STATUS_VR;
+                }
+                if self.data.status_hr {
+                    r |= STATUS_HR;
+                }
+                r
+            }
+            3 => self.data.collision_x as u8,
+            4 => 0xfe | (self.data.collision_x >> 8) as u8,
+            5 => {
+                // Reading S#5 resets the coordinates
+                let r = self.data.collision_y as u8;
+                self.data.collision_x = 0;
+                self.data.collision_y = 0;
+                r
+            }
+            6 => 0xfc | (self.data.collision_y >> 8) as u8,
+            7 => self.data.command.read_color(),
+            8 => self.data.command.border_x() as u8,
+            9 => 0xfe | (self.data.command.border_x() >> 8) as u8,
+            _ => 0xff,
+        }
+    }
+
+    fn write_palette(&mut self, val: u8) {
+        match self.data.palette_latch.take() {
+            None => self.data.palette_latch = Some(val),
+            Some(first) => {
+                let index = (self.data.registers[16] & 0x0f) as usize;
+                self.data.palette[index] = ((val as u16 & 0x07) << 8) | (first & 0x77) as u16;
+                self.set_palette_color(index);
+                self.data.registers[16] = (index as u8 + 1) & 0x0f;
+            }
+        }
+    }
+
+    fn set_palette_color(&self, index: usize) {
+        let color = self.data.palette[index];
+        let level = |v: u16| ((v & 0x07) * 255 / 7) as u8;
+        self.graphics.borrow_mut().set_palette_color(
+            index,
+            level(color >> 4),
+            level(color >> 8),
+            level(color),
+        );
+    }
+
+    // Sends the palette and the fixed SCREEN8 colours (GGGRRRBB) to the
+    // driver
+    fn update_palette(&self) {
+        for index in 0..16 {
+            self.set_palette_color(index);
+        }
+        let mut graphics = self.graphics.borrow_mut();
+        for b in 0..256_u16 {
+            graphics.set_palette_color(
+                16 + b as usize,
+                ((b >> 2 & 0x07) * 255 / 7) as u8,
+                ((b >> 5) * 255 / 7) as u8,
+                ((b & 0x03) * 255 / 3) as u8,
+            );
+        }
+    }
+
+    fn read_ahead(&mut self) {
+        self.data.read_buffer = self.data.vram[self.data.pointer_vram as usize];
+        self.increment_pointer();
+    }
+
+    // The address counter is 14 bits wide. The V9938 carries into R#14 in
+    // the modes that use more than 16KB.
+    fn increment_pointer(&mut self) {
+        let pointer = self.data.pointer_vram;
+        self.data.pointer_vram = if self.is_v9938() && self.uses_128k() {
+            let pointer = (pointer + 1) & 0x1ffff;
+            self.data.registers[14] = (pointer >> 14) as u8;
+            pointer
+        } else {
+            (pointer & 0x1c000) | ((pointer + 1) & 0x3fff)
+        };
+    }
+
+    fn uses_128k(&self) -> bool {
+        matches!(
+            self.data.screen_mode,
+            MODE_TEXT80 | SCREEN5 | SCREEN6 | SCREEN7 | SCREEN8
+        )
+    }
+
+    // Layout the command engine uses in the current mode. The commands are
+    // meant for the bitmap modes, the others are handled as SCREEN8.
+    fn command_mode(&self) -> CommandMode {
+        match self.data.screen_mode {
+            SCREEN5 => CommandMode::Graphic4,
+            SCREEN6 => CommandMode::Graphic5,
+            SCREEN7 => CommandMode::Graphic6,
+            _ => CommandMode::Graphic7,
+        }
+    }
+
+    // Runs the command engine for the cycles of the last instruction
+    pub fn run(&mut self, cycles: u32) {
+        self.data.command.run(cycles, &mut self.data.vram);
+    }
+
+    // Colours of the TMS9918. The V9938 keeps its own palette.
+    pub fn set_palette(&mut self, palette: &Palette) {
+        if self.is_v9938() {
+            log::info!("Palette {} not used: the V9938 has its own", palette.name);
+            return;
+        }
+        self.graphics.borrow_mut().set_palette(palette);
+    }
+
+    pub fn save_screenshot(&self, path: &str) -> Result<(), String> {
+        self.graphics.borrow().save_screenshot(path)
+    }
+
     pub fn graphics_render(&mut self) {
         self.graphics.borrow_mut().render();
     }
-    pub fn update_buffer(&mut self) {
-        if !self.data.screen_enabled {
+    // Shows the whole border instead of a thin frame around the display
+    pub fn set_overscan(&mut self, overscan: bool) {
+        self.border = if overscan { OVERSCAN } else { BORDER };
+        self.graphics
+            .borrow_mut()
+            .set_frame_size(self.frame_width() as u32, self.frame_height() as u32);
+    }
+
+    // The V9938 frame has room for 512 pixels per line; the 256 pixel
+    // modes are drawn twice as wide
+    fn pixel_scale(&self) -> usize {
+        if self.is_v9938() {
+            2
+        } else {
+            1
+        }
+    }
+
+    fn max_active_lines(&self) -> usize {
+        if self.is_v9938() {
+            ACTIVE_LINES_212
+        } else {
+            ACTIVE_LINES
+        }
+    }
+
+    fn active_lines(&self) -> usize {
+        if self.is_v9938() && self.data.registers[9] & 0x80 != 0 {
+            ACTIVE_LINES_212
+        } else {
+            ACTIVE_LINES
+        }
+    }
+
+    pub fn frame_width(&self) -> usize {
+        (self.border.left + 256 + self.border.right) * self.pixel_scale()
+    }
+
+    pub fn frame_height(&self) -> usize {
+        self.border.top + self.max_active_lines() + self.border.bottom
+    }
+
+    // Composes one line of the frame with the current registers and VRAM.
+    // Frame lines start after the vertical blanking interrupt, with the
+    // bottom border, so the top border and the active display are at the
+    // end of the frame.
+    pub fn render_line(&mut self, frame_line: usize) {
+        let border = self.border;
+        let lines = self.active_lines();
+        let lines_per_frame = self.timing.lines_per_frame();
+        let first_active = self.timing.first_active_line(lines);
+        // Border rows below a 192 line display
+        let extra = self.max_active_lines() - lines;
+        if frame_line == 0 {
+            self.update_blink();
+        }
+        self.data.retrace = frame_line < first_active;
+        let (row, active_y) = if frame_line < border.bottom + extra {
+            (border.top + lines + frame_line, None)
+        } else if (first_active..lines_per_frame).contains(&frame_line) {
+            let y = frame_line - first_active;
+            (border.top + y, Some(y))
+        } else if (first_active - border.top..first_active).contains(&frame_line) {
+            (frame_line + border.top - first_active, None)
+        } else {
             return;
+        };
+
+        let scale = self.pixel_scale();
+        let mut line = [self.backdrop(); MAX_LINE_WIDTH];
+        if let Some(y) = active_y {
+            self.check_line_interrupt(y);
+        }
+        if let (Some(y), true) = (active_y, self.data.screen_enabled) {
+            let mut active = [0_u16; 512];
+            let mut sprites = [0_u16; 256];
+            let hires = self.render_active(y, &mut active, &mut sprites);
+            // With TP, colour 0 is not transparent
+            let tp = self.is_v9938() && self.data.registers[8] & 0x20 != 0;
+            let left = border.left * scale;
+            for x in 0..256 * scale {
+                let mut color = if hires { active[x] } else { active[x / scale] };
+                if sprites[x / scale] != 0 {
+                    color = sprites[x / scale];
+                }
+                // Colour 0 is transparent and shows the backdrop
+                if color != 0 || tp {
+                    line[left + x] = color;
+                }
+            }
+        }
+        let mut graphics = self.graphics.borrow_mut();
+        for (x, color) in line.iter().take(self.frame_width()).enumerate() {
+            graphics.draw_pixel(x as u32, row as u32, *color as usize);
         }
-        let name_table_addr = (self.data.registers[2] as usize) << 10;
-        let pat_table_addr = (self.data.registers[4] as usize) << 11;
-        let color_table_addr = (self.data.registers[3] as usize) << 6;
+    }
 
+    fn backdrop(&self) -> u16 {
+        let r7 = self.data.registers[7] as u16;
+        if !self.is_v9938() {
+            return r7 & 0x0f;
+        }
         match self.data.screen_mode {
-            SCREEN0 => {
-                // Render SCREEN0 (40x24)
-                let color1 = (self.data.registers[7] & 0xF0) >> 4;
-                let color2 = self.data.registers[7] & 0x0F;
-                for y in 0..24 {
-                    for x in 0..40 {
-                        let name_table = &self.data.vram[name_table_addr..];
-                        let pt = name_table[(x + y * 40) as usize] as u16 * 8;
-                        self.draw_patterns_s0(x * 8, y * 8, pt, pat_table_addr, color1, color2);
-                    }
-                }
+            SCREEN6 => r7 & 0x03,
+            SCREEN8 => 16 + r7,
+            _ => r7 & 0x0f,
+        }
+    }
+
+    // The V9938 compares R#19 with the line counter, which follows the
+    // vertical scroll
+    fn check_line_interrupt(&mut self, y: usize) {
+        if !self.is_v9938() {
+            return;
+        }
+        let registers = &self.data.registers;
+        if (y as u8).wrapping_add(registers[23]) == registers[19] {
+            self.data.status_fh = true;
+        }
+    }
+
+    // R#13 has the frames, in tens, the blink colours of the text 80
+    // column mode are on (high nibble) and off
+    fn update_blink(&mut self) {
+        if self.data.blink_frames > 0 {
+            self.data.blink_frames -= 1;
+            return;
+        }
+        let r13 = self.data.registers[13];
+        let (on, off) = (r13 >> 4, r13 & 0x0f);
+        self.data.blink_on = match (on, off) {
+            (0, _) => false,
+            (_, 0) => true,
+            _ => !self.data.blink_on,
+        };
+        let time = if self.data.blink_on { on } else { off };
+        self.data.blink_frames = (time * 10).saturating_sub(1);
+    }
+
+    // Draws the background in `line` and the sprites in `sprites`.
+    // Returns true when the background has 512 pixels.
+    fn render_active(&mut self, y: usize, line: &mut [u16], sprites: &mut [u16]) -> bool {
+        // The V9938 scrolls the display by R#23 lines
+        let y = if self.is_v9938() {
+            (y + self.data.registers[23] as usize) & 0xff
+        } else {
+            y
+        };
+        match self.data.screen_mode {
+            SCREEN0 => self.line_text(y, line, false),
+            MODE_TEXT_BITMAP => self.line_text(y, line, true),
+            MODE_TEXT_STRIPES => self.line_stripes(line),
+            MODE_TEXT80 => {
+                self.line_text80(y, line);
+                return true;
             }
             SCREEN1 => {
-                // Render SCREEN1 (32x24)
-                for y in 0..24 {
-                    for x in 0..32 {
-                        let name_table = &self.data.vram[name_table_addr..];
-                        let pat = name_table[(x + y * 32) as usize];
-                        let color_table = &self.data.vram[color_table_addr..];
-                        let color = color_table[(pat / 8) as usize];
-                        self.draw_patterns_s1(x * 8, y * 8, pat as u16 * 8, pat_table_addr, color);
-                    }
-                }
-                self.draw_sprites();
+                self.line_s1(y, line);
+                self.line_sprites(y, sprites);
             }
             SCREEN2 => {
-                // Render SCREEN2
-                // Pattern table: 0000H to 17FFH
-                let pat_table_addr = ((self.data.registers[4] & 0x04) as usize) << 11;
-                let color_table_addr = ((self.data.registers[3] & 0x80) as usize) << 6;
-                for y in 0..24 {
-                    for x in 0..32 {
-                        let name_table = &self.data.vram[name_table_addr..];
-                        let pat = name_table[(x + y * 32) as usize];
-                        self.draw_patterns_s2(
-                            x * 8,
-                            y * 8,
-                            pat as u16 * 8,
-                            pat_table_addr,
-                            color_table_addr,
-                        );
-                    }
-                }
-                self.draw_sprites();
+                self.line_s2(y, line);
+                self.line_sprites(y, sprites);
             }
             SCREEN3 => {
-                // Render SCREEN3
-                log::error!("Drawing in screen3 not implemented yet");
+                self.line_s3(y, line, false);
+                self.line_sprites(y, sprites);
+            }
+            MODE_MULTICOLOR_BITMAP => {
+                self.line_s3(y, line, true);
+                self.line_sprites(y, sprites);
+            }
+            SCREEN4 => {
+                self.line_s2(y, line);
+                self.line_sprites2(y, sprites);
+            }
+            SCREEN5..=SCREEN8 => {
+                let hires = self.line_bitmap(y, line);
+                self.line_sprites2(y, sprites);
+                return hires;
             }
             _ => {
                 panic!("RenderScreen: impossible mode");
             }
+        };
+        false
+    }
+
+    fn vram(&self, address: usize) -> u8 {
+        if self.is_v9938() {
+            self.data.vram[address & 0x1ffff]
+        } else {
+            self.data.vram[address & 0x3fff]
         }
     }
 
-    pub fn draw_patterns_s0(
-        &mut self,
-        x: u16,
-        y: u16,
-        pt: u16,
-        pat_table_addr: usize,
-        color1: u8,
-        color2: u8,
-    ) {
-        let pat_table = &self.data.vram[pat_table_addr..];
-        for i in 0..8 {
-            let b = pat_table[(i + pt) as usize];
-            let mut xx = 0;
-            let mut mask = 0x80;
-            while mask > 0 {
-                if mask & b != 0 {
-                    self.graphics.borrow_mut().draw_pixel(
-                        (x + xx).into(),
-                        (y + i).into(),
-                        color1.into(),
-                    );
-                } else {
-                    self.graphics.borrow_mut().draw_pixel(
-                        (x + xx).into(),
-                        (y + i).into(),
-                        color2.into(),
-                    );
+    fn name_table_addr(&self) -> usize {
+        let r2 = self.data.registers[2] as usize;
+        if self.is_v9938() {
+            (r2 & 0x7f) << 10
+        } else {
+            (r2 & 0x0f) << 10
+        }
+    }
+
+    // In the bitmap modes, the bits of R#4 below the base address are an
+    // AND mask on the index: with some of them at 0, parts of the table
+    // are mirrored
+    fn pattern_addr(&self, idx: usize, bitmap: bool) -> usize {
+        let r4 = self.data.registers[4] as usize;
+        let high = if self.is_v9938() { r4 & 0x38 } else { 0 };
+        if bitmap {
+            ((high | (r4 & 0x04)) << 11) | (idx & (((r4 & 0x03) << 11) | 0x7ff))
+        } else {
+            ((high | (r4 & 0x07)) << 11) | (idx & 0x7ff)
+        }
+    }
+
+    // Same for R#3 and the colour table. The V9938 takes A14-A16 from R#10.
+    fn color_addr(&self, idx: usize, bitmap: bool) -> usize {
+        let r3 = self.data.registers[3] as usize;
+        let high = if self.is_v9938() {
+            (self.data.registers[10] as usize & 0x07) << 14
+        } else {
+            0
+        };
+        if bitmap {
+            high | ((r3 & 0x80) << 6) | (idx & (((r3 & 0x7f) << 6) | 0x3f))
+        } else {
+            high | (r3 << 6) | (idx & 0x3f)
+        }
+    }
+
+    // Draws the 8 pixels of a pattern byte
+    fn draw_byte(line: &mut [u16], x: usize, b: u8, color1: u8, color2: u8) {
+        for (xx, pixel) in line[x..x + 8].iter_mut().enumerate() {
+            *pixel = if b & (0x80 >> xx) != 0 {
+                color1 as u16
+            } else {
+                color2 as u16
+            };
+        }
+    }
+
+    fn line_text(&self, y: usize, line: &mut [u16], bitmap: bool) {
+        // Render SCREEN0 (40x24), 6 pixel wide characters centered in
+        // the line. With M3 the pattern table is split in thirds.
+        let name_table_addr = self.name_table_addr();
+        let color1 = ((self.data.registers[7] & 0xF0) >> 4) as u16;
+        let color2 = (self.data.registers[7] & 0x0F) as u16;
+        let third = if bitmap { (y / 64) * 2048 } else { 0 };
+        line.fill(color2);
+        for x in 0..40 {
+            let pat = self.vram(name_table_addr + x + (y / 8) * 40) as usize;
+            let b = self.vram(self.pattern_addr(third + pat * 8 + y % 8, bitmap));
+            for xx in 0..6 {
+                if b & (0x80 >> xx) != 0 {
+                    line[8 + x * 6 + xx] = color1;
                 }
-                xx += 1;
-                mask >>= 1;
-            }
-        }
-    }
-
-    fn draw_patterns_s1(&mut self, x: u16, y: u16, pt: u16, pat_table_addr: usize, color: u8) {
-        let color1 = (color & 0xF0) as usize >> 4;
-        let color2 = (color & 0x0F) as usize;
-        // let mask: u8 = 0;
-        let pat_table = &self.data.vram[pat_table_addr..];
-        for i in 0..8 {
-            let b = pat_table[(i + pt) as usize];
-            let mut xx = 0;
-            let mut mask = 0x80;
-            while mask > 0 {
-                if mask & b != 0 {
-                    self.graphics
-                        .borrow_mut()
-                        .draw_pixel((x + xx).into(), (y + i).into(), color1);
+            }
+        }
+    }
+
+    // Text 80 columns (V9938), in high resolution pixels. Characters with
+    // their bit set in the colour table use the R#12 colours while the
+    // blink is on.
+    fn line_text80(&self, y: usize, line: &mut [u16]) {
+        let name_table_addr = ((self.data.registers[2] & 0x7c) as usize) << 10;
+        let blink_table_addr = ((self.data.registers[10] as usize & 0x07) << 14)
+            | ((self.data.registers[3] as usize & 0xf8) << 6);
+        let (r7, r12) = (self.data.registers[7], self.data.registers[12]);
+        line.fill((r7 & 0x0f) as u16);
+        for x in 0..80 {
+            let n = (y / 8) * 80 + x;
+            let blink =
+                self.data.blink_on && self.vram(blink_table_addr + n / 8) & (0x80 >> (n % 8)) != 0;
+            let colors = if blink { r12 } else { r7 };
+            let (color1, color2) = ((colors >> 4) as u16, (colors & 0x0f) as u16);
+            let pat = self.vram(name_table_addr + n) as usize;
+            let b = self.vram(self.pattern_addr(pat * 8 + y % 8, false));
+            for xx in 0..6 {
+                line[16 + x * 6 + xx] = if b & (0x80 >> xx) != 0 {
+                    color1
                 } else {
-                    self.graphics
-                        .borrow_mut()
-                        .draw_pixel((x + xx).into(), (y + i).into(), color2);
+                    color2
+                };
+            }
+        }
+    }
+
+    // M1+M2: 40 columns of 4 foreground and 2 background pixels
+    fn line_stripes(&self, line: &mut [u16]) {
+        let color1 = ((self.data.registers[7] & 0xF0) >> 4) as u16;
+        let color2 = (self.data.registers[7] & 0x0F) as u16;
+        line.fill(color2);
+        for x in 0..40 {
+            line[8 + x * 6..12 + x * 6].fill(color1);
+        }
+    }
+
+    fn line_s1(&self, y: usize, line: &mut [u16]) {
+        // Render SCREEN1 (32x24)
+        let name_table_addr = self.name_table_addr();
+        for x in 0..32 {
+            let pat = self.vram(name_table_addr + x + (y / 8) * 32) as usize;
+            let b = self.vram(self.pattern_addr(pat * 8 + y % 8, false));
+            let color = self.vram(self.color_addr(pat / 8, false));
+            Self::draw_byte(line, x * 8, b, color >> 4, color & 0x0F);
+        }
+    }
+
+    fn line_s2(&self, y: usize, line: &mut [u16]) {
+        // Render SCREEN2
+        let name_table_addr = self.name_table_addr();
+        // Each third of the screen has its own 256 patterns
+        let third = (y / 64) * 2048;
+        for x in 0..32 {
+            let pat = self.vram(name_table_addr + x + (y / 8) * 32) as usize;
+            let idx = third + pat * 8 + y % 8;
+            let b = self.vram(self.pattern_addr(idx, true));
+            let color = self.vram(self.color_addr(idx, true));
+            Self::draw_byte(line, x * 8, b, color >> 4, color & 0x0F);
+        }
+    }
+
+    fn line_s3(&self, y: usize, line: &mut [u16], bitmap: bool) {
+        // Render SCREEN3 (64x48 blocks of 4x4 pixels)
+        let name_table_addr = self.name_table_addr();
+        // Each character row uses two bytes of the pattern, starting at
+        // byte 2 * (row % 4), and each byte is two blocks high
+        let byte = ((y / 8) % 4) * 2 + (y % 8) / 4;
+        // With M3 the pattern table is split in thirds
+        let third = if bitmap { (y / 64) * 2048 } else { 0 };
+        for x in 0..32 {
+            let pat = self.vram(name_table_addr + x + (y / 8) * 32) as usize;
+            let color = self.vram(self.pattern_addr(third + pat * 8 + byte, bitmap));
+            line[x * 8..x * 8 + 4].fill((color >> 4) as u16);
+            line[x * 8 + 4..x * 8 + 8].fill((color & 0x0F) as u16);
+        }
+    }
+
+    // SCREEN5-8 (G4-G7). Returns true for the 512 pixel modes.
+    fn line_bitmap(&self, y: usize, line: &mut [u16]) -> bool {
+        let r2 = self.data.registers[2] as usize;
+        match self.data.screen_mode {
+            SCREEN5 => {
+                // 128 bytes per line, 4 pages of 32KB
+                let addr = ((r2 & 0x60) << 10) + y * 128;
+                for x in 0..128 {
+                    let b = self.vram(addr + x);
+                    line[x * 2] = (b >> 4) as u16;
+                    line[x * 2 + 1] = (b & 0x0f) as u16;
                 }
-                xx += 1;
-                mask >>= 1;
-            }
-        }
-    }
-
-    fn draw_patterns_s2(
-        &mut self,
-        x: u16,
-        y: u16,
-        pt: u16,
-        pat_table_addr: usize,
-        color_table_addr: usize,
-    ) {
-        let mut b: u8;
-        let mut color: u8;
-        let pat_table = &self.data.vram[pat_table_addr..];
-        let color_table = &self.data.vram[color_table_addr..];
-        for i in 0..8 {
-            if y < 64 {
-                let idx = (i + pt) as usize;
-                b = pat_table[idx];
-                color = color_table[idx];
-            } else if y < 128 {
-                let idx = (i + pt + 2048) as usize;
-                b = pat_table[idx];
-                color = color_table[idx];
-            } else {
-                let idx = (i + pt + 2048 * 2) as usize;
-                b = pat_table[idx];
-                color = color_table[idx];
-            }
-            let color1 = (color & 0xF0) >> 4;
-            let color2 = color & 0x0F;
-            let mut xx = 0;
-            let mut mask = 0x80;
-            while mask > 0 {
-                if mask & b != 0 {
-                    self.graphics.borrow_mut().draw_pixel(
-                        (x + xx).into(),
-                        (y + i).into(),
-                        color1.into(),
-                    );
-                } else {
-                    self.graphics.borrow_mut().draw_pixel(
-                        (x + xx).into(),
-                        (y + i).into(),
-                        color2.into(),
-                    );
+                false
+            }
+            SCREEN6 => {
+                let addr = ((r2 & 0x60) << 10) + y * 128;
+                for x in 0..128 {
+                    let b = self.vram(addr + x);
+                    for p in 0..4 {
+                        line[x * 4 + p] = ((b >> (6 - p * 2)) & 0x03) as u16;
+                    }
+                }
+                true
+            }
+            SCREEN7 => {
+                // 256 bytes per line, 2 pages of 64KB
+                let addr = ((r2 & 0x20) << 11) + y * 256;
+                for x in 0..256 {
+                    let b = self.vram(addr + x);
+                    line[x * 2] = (b >> 4) as u16;
+                    line[x * 2 + 1] = (b & 0x0f) as u16;
+                }
+                true
+            }
+            _ => {
+                // SCREEN8 colours come after the palette
+                let addr = ((r2 & 0x20) << 11) + y * 256;
+                for (x, pixel) in line.iter_mut().take(256).enumerate() {
+                    let b = self.vram(addr + x) as u16;
+                    *pixel = if b == 0 { 0 } else { 16 + b };
                 }
-                xx += 1;
-                mask >>= 1;
+                false
             }
         }
     }
 
-    fn draw_sprites(&mut self) {
+    fn sprite_size(&self) -> (usize, usize) {
+        let magnif = if (self.data.registers[1] & 0x01) != 0 {
+            2
+        } else {
+            1
+        };
+        let size = if (self.data.registers[1] & 0x02) != 0 {
+            16
+        } else {
+            8
+        };
+        (size, magnif)
+    }
+
+    fn sprite_pattern_table_addr(&self) -> usize {
+        let r6 = self.data.registers[6] as usize;
+        if self.is_v9938() {
+            (r6 & 0x3f) << 11
+        } else {
+            (r6 & 0x07) << 11
+        }
+    }
+
+    // Row of a sprite pattern, from bit 15
+    fn sprite_pattern(&self, name: u8, row: usize, size: usize) -> u16 {
+        let name = if size == 16 { name & 0xfc } else { name };
+        let pattern_addr = self.sprite_pattern_table_addr() + name as usize * 8 + row;
+        // 16x16 sprites: the right half is 16 bytes after the left half
+        if size == 16 {
+            ((self.vram(pattern_addr) as u16) << 8) | self.vram(pattern_addr + 16) as u16
+        } else {
+            (self.vram(pattern_addr) as u16) << 8
+        }
+    }
+
+    fn line_sprites(&mut self, y: usize, sprites: &mut [u16]) {
         // Sprite name table: 1B00H to 1B7FH
         // Sprite pattern table: 3800H to 3FFFH
-        let spr_table_addr = (self.data.registers[5] as usize) << 7;
-        let spr_pat_table_addr = (self.data.registers[6] as usize) << 11;
-        let magnif = (self.data.registers[1] & 0x01) != 0;
-        let spr16x16 = (self.data.registers[1] & 0x02) != 0;
-        self.data.magnif_num = 0;
-        let mut i = 0;
-        let mut j = 0;
-        while i < 32 {
-            let ypos = self.data.vram[spr_table_addr + j] as u16;
-            if ypos == 0xd0 {
+        let spr_table_addr = if self.is_v9938() {
+            ((self.data.registers[11] as usize & 0x03) << 15)
+                | ((self.data.registers[5] as usize) << 7)
+        } else {
+            ((self.data.registers[5] & 0x7f) as usize) << 7
+        };
+        let (size, magnif) = self.sprite_size();
+
+        // Only the first 4 sprites of the line are shown
+        let mut visible = Vec::with_capacity(MAX_SPRITES_PER_LINE);
+        let mut last = 31;
+        for i in 0..32 {
+            let attr_addr = spr_table_addr + i * 4;
+            let attr = [0, 1, 2, 3].map(|n| self.vram(attr_addr + n));
+            if attr[0] == 0xd0 {
                 // Ignore all sprites
-                return;
-            }
-            let mut xpos = self.data.vram[spr_table_addr + j + 1] as u16;
-            let pattern = self.data.vram[spr_table_addr + j + 2];
-            let ec = (self.data.vram[spr_table_addr + j + 3] & 0x80) != 0;
-            if ec {
-                xpos -= 32
-            }
-            let color = self.data.vram[spr_table_addr + j + 3] & 0x0F;
-            if !spr16x16 {
-                let pattern_addr = spr_pat_table_addr + (pattern as usize) * 8;
-                // self.draw_spr(magnif, xpos, ypos, pattern_addr, ec, color);
-                self.draw_spr(magnif, xpos, ypos, pattern_addr, color);
-            } else {
-                let pattern_addr = spr_pat_table_addr + ((pattern >> 2) as usize) * 8 * 4;
-                // self.draw_spr(magnif, xpos, ypos, pattern_addr, ec, color);
-                // self.draw_spr(magnif, xpos, ypos + 8, pattern_addr + 8, ec, color);
-                // self.draw_spr(magnif, xpos + 8, ypos, pattern_addr + 16, ec, color);
-                // self.draw_spr(magnif, xpos + 8, ypos + 8, pattern_addr + 24, ec, color);
-                self.draw_spr(magnif, xpos, ypos, pattern_addr, color);
-                self.draw_spr(magnif, xpos, ypos + 8, pattern_addr + 8, color);
-                self.draw_spr(magnif, xpos + 8, ypos, pattern_addr + 16, color);
-                self.draw_spr(magnif, xpos + 8, ypos + 8, pattern_addr + 24, color);
-            }
-
-            i += 1;
-            j += 4;
-        }
-    }
-
-    fn draw_spr(
-        &mut self,
-        magnif: bool,
-        xpos: u16,
-        ypos: u16,
-        pattern_addr: usize,
-        // ec: bool,
-        color: u8,
-    ) {
-        if ypos > 191 {
-            return;
+                last = i;
+                break;
+            }
+            // Y is one line above the sprite, values past 0xd0 are above
+            // the screen so sprites can enter from the top
+            let mut top = attr[0] as i32 + 1;
+            if attr[0] > 0xd0 {
+                top -= 256;
+            }
+            let row = y as i32 - top;
+            if row < 0 || row >= (size * magnif) as i32 {
+                continue;
+            }
+            if visible.len() == MAX_SPRITES_PER_LINE {
+                if self.data.status_reg & STATUS_5S == 0 {
+                    self.data.status_reg = (self.data.status_reg & 0xe0) | STATUS_5S | i as u8;
+                }
+                return self.draw_sprites(&visible, sprites);
+            }
+            // Early clock shifts the sprite 32 pixels to the left
+            let ec = attr[3] & 0x80 != 0;
+            visible.push(SpriteLine {
+                x: attr[1] as i32 - if ec { 32 } else { 0 },
+                pattern: self.sprite_pattern(attr[2], row as usize / magnif, size),
+                size,
+                magnif,
+                color: attr[3] & 0x0F,
+                cc: false,
+                ic: false,
+            });
         }
+        // Without a 5th sprite, the number is the last sprite looked at
+        if self.data.status_reg & STATUS_5S == 0 {
+            self.data.status_reg = (self.data.status_reg & 0xe0) | last as u8;
+        }
+        self.draw_sprites(&visible, sprites);
+    }
 
-        let pattern = &self.data.vram[pattern_addr..];
-        for y in 0..8 {
-            let b = pattern[y as usize];
-            let mut x = 0;
-            let mut mask = 0x80;
-            while mask > 0 {
-                if magnif && x == 0 && y == 0 {
-                    if self.data.magnif_num == 4 {
-                        self.data.magnif_num = 1;
-                    } else {
-                        self.data.magnif_num += 1;
-                    }
+    // Sprites come in priority order, the first one is on top
+    fn draw_sprites(&mut self, sprites: &[SpriteLine], line: &mut [u16]) {
+        let mut used = [false; 256];
+        for sprite in sprites {
+            for bit in 0..sprite.size {
+                if sprite.pattern & (0x8000 >> bit) == 0 {
+                    continue;
                 }
-                if mask & b != 0 {
-                    if magnif {
-                        let mut x = x * 2;
-                        let mut y = y * 2;
-                        if self.data.magnif_num == 2 || self.data.magnif_num == 4 {
-                            y += 8;
-                        }
-                        if self.data.magnif_num == 3 || self.data.magnif_num == 4 {
-                            x += 8;
-                        }
-                        self.graphics.borrow_mut().draw_pixel(
-                            (xpos + x).into(),
-                            (ypos + y).into(),
-                            color.into(),
-                        );
-                        self.graphics.borrow_mut().draw_pixel(
-                            (xpos + x + 1).into(),
-                            (ypos + y).into(),
-                            color.into(),
-                        );
-                        self.graphics.borrow_mut().draw_pixel(
-                            (xpos + x).into(),
-                            (ypos + y + 1).into(),
-                            color.into(),
-                        );
-                        self.graphics.borrow_mut().draw_pixel(
-                            (xpos + x + 1).into(),
-                            (ypos + y + 1).into(),
-                            color.into(),
-                        );
-                    } else {
-                        self.graphics.borrow_mut().draw_pixel(
-                            (xpos + x).into(),
-                            (ypos + y).into(),
-                            color.into(),
-                        );
+                for m in 0..sprite.magnif {
+                    let x = sprite.x + (bit * sprite.magnif + m) as i32;
+                    if !(0..256).contains(&x) {
+                        continue;
                     }
+                    let x = x as usize;
+                    if used[x] {
+                        // Colour 0 sprites collide too
+                        self.data.status_reg |= STATUS_COLLISION;
+                        continue;
+                    }
+                    used[x] = true;
+                    // Colour 0 is transparent
+                    if sprite.color != 0 {
+                        line[x] = sprite.color as u16;
+                    }
+                }
+            }
+        }
+    }
+
+    // Sprite mode 2 (V9938): 8 sprites per line, with a colour and flags
+    // per line in the 512 bytes before the attribute table
+    fn line_sprites2(&mut self, y: usize, sprites: &mut [u16]) {
+        // SPD disables the sprites
+        if self.data.registers[8] & 0x02 != 0 {
+            return;
+        }
+        let attr_table_addr = ((self.data.registers[11] as usize & 0x03) << 15)
+            | ((self.data.registers[5] as usize & 0xfc) << 7);
+        // Wraps to the end of the VRAM with the attributes at 0
+        let color_table_addr = attr_table_addr.wrapping_sub(512) & 0x1ffff;
+        let (size, magnif) = self.sprite_size();
+
+        let mut visible = Vec::with_capacity(MAX_SPRITES_PER_LINE_MODE2);
+        let mut last = 31;
+        for i in 0..32 {
+            let attr_addr = attr_table_addr + i * 4;
+            let sprite_y = self.vram(attr_addr);
+            if sprite_y == 216 {
+                last = i;
+                break;
+            }
+            // Y is one line above the sprite, wrapping at 256
+            let row = (y as u8).wrapping_sub(sprite_y.wrapping_add(1)) as usize;
+            if row >= size * magnif {
+                continue;
+            }
+            if visible.len() == MAX_SPRITES_PER_LINE_MODE2 {
+                if self.data.status_reg & STATUS_5S == 0 {
+                    self.data.status_reg = (self.data.status_reg & 0xe0) | STATUS_5S | i as u8;
                 }
-                x += 1;
+                return self.draw_sprites2(&visible, y, sprites);
+            }
+            let color = self.vram(color_table_addr + i * 16 + row / magnif);
+            let ec = color & 0x80 != 0;
+            visible.push(SpriteLine {
+                x: self.vram(attr_addr + 1) as i32 - if ec { 32 } else { 0 },
+                pattern: self.sprite_pattern(self.vram(attr_addr + 2), row / magnif, size),
+                size,
+                magnif,
+                color: color & 0x0f,
+                cc: color & 0x40 != 0,
+                ic: color & 0x20 != 0,
+            });
+        }
+        if self.data.status_reg & STATUS_5S == 0 {
+            self.data.status_reg = (self.data.status_reg & 0xe0) | last as u8;
+        }
+        self.draw_sprites2(&visible, y, sprites);
+    }
 
-                mask >>= 1;
+    // A sprite with CC joins the sprite without CC above it: where they
+    // overlap their colours are ORed and they do not collide. CC sprites
+    // without such a sprite are not shown.
+    fn draw_sprites2(&mut self, sprites: &[SpriteLine], y: usize, line: &mut [u16]) {
+        for (x, pixel) in line.iter_mut().enumerate().take(256) {
+            let mut color = None;
+            let mut group_color: Option<u8> = None;
+            let mut in_group = false;
+            let mut colliding = 0;
+            for sprite in sprites {
+                if !sprite.cc {
+                    // Groups above win
+                    color = color.or(group_color);
+                    group_color = None;
+                    in_group = true;
+                }
+                if !in_group || !sprite.covers(x as i32) {
+                    continue;
+                }
+                if !sprite.cc && !sprite.ic {
+                    colliding += 1;
+                }
+                group_color = Some(group_color.unwrap_or(0) | sprite.color);
+            }
+            if colliding > 1 && self.data.status_reg & STATUS_COLLISION == 0 {
+                self.data.status_reg |= STATUS_COLLISION;
+                self.data.collision_x = x as u16 + 12;
+                self.data.collision_y = y as u16 + 8;
+            }
+            if let Some(color) = color.or(group_color) {
+                // Colour 0 is transparent
+                if color != 0 {
+                    *pixel = color as u16;
+                }
             }
         }
     }
+
     pub fn get_data(&self) -> VdpData {
         self.data.clone()
     }
-    pub fn set_data(&mut self, data: VdpData) {
+    pub fn set_data(&mut self, mut data: VdpData) {
+        // Older save files have 64KB of VRAM and fewer registers
+        data.vram.resize(0x20000, 0);
+        data.registers.resize(64, 0);
         self.data = data;
+        if self.is_v9938() {
+            self.update_palette();
+        }
     }
 }
@@ -0,0 +1,506 @@
+/*
+
+    V9938 command engine
+
+    Registers R#32-R#46, numbered from 0 here:
+
+    0-1     SX      source X (9 bits)
+    2-3     SY      source Y (10 bits)
+    4-5     DX      destination X (9 bits)
+    6-7     DY      destination Y (10 bits)
+    8-9     NX      dots in X (9 bits), long side of LINE
+    10-11   NY      dots in Y (10 bits), short side of LINE
+    12      CLR     colour, or data of the CPU transfers
+    13      ARG     bit 0 MAJ, bit 1 EQ, bit 2 DIX, bit 3 DIY
+    14      CMD     command in the high nibble, logical operation in the low one
+
+    Coordinates are pixels of the bitmap mode. The high speed commands
+    (HMMx, YMMM) move whole bytes and ignore the low bits of X.
+
+*/
+
+use serde::{Deserialize, Serialize};
+
+pub const CMD_STOP: u8 = 0x0;
+pub const CMD_POINT: u8 = 0x4;
+pub const CMD_PSET: u8 = 0x5;
+pub const CMD_SRCH: u8 = 0x6;
+pub const CMD_LINE: u8 = 0x7;
+pub const CMD_LMMV: u8 = 0x8;
+pub const CMD_LMMM: u8 = 0x9;
+pub const CMD_LMCM: u8 = 0xa;
+pub const CMD_LMMC: u8 = 0xb;
+pub const CMD_HMMV: u8 = 0xc;
+pub const CMD_HMMM: u8 = 0xd;
+pub const CMD_YMMM: u8 = 0xe;
+pub const CMD_HMMC: u8 = 0xf;
+
+// Flags of S#2
+pub const STATUS_TR: u8 = 0x80;
+pub const STATUS_BD: u8 = 0x10;
+pub const STATUS_CE: u8 = 0x01;
+
+const ARG_MAJ: u8 = 0x01;
+const ARG_EQ: u8 = 0x02;
+const ARG_DIX: u8 = 0x04;
+const ARG_DIY: u8 = 0x08;
+
+const REG_SX: usize = 0;
+const REG_SY: usize = 2;
+const REG_DX: usize = 4;
+const REG_DY: usize = 6;
+const REG_NX: usize = 8;
+const REG_NY: usize = 10;
+const REG_CLR: usize = 12;
+const REG_ARG: usize = 13;
+const REG_CMD: usize = 14;
+
+// Pixel layout of the bitmap mode the commands work on
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandMode {
+    // SCREEN5: 256 pixels of 4 bits
+    Graphic4,
+    // SCREEN6: 512 pixels of 2 bits
+    Graphic5,
+    // SCREEN7: 512 pixels of 4 bits
+    Graphic6,
+    // SCREEN8: 256 pixels of 8 bits
+    Graphic7,
+}
+
+impl CommandMode {
+    pub fn width(self) -> i32 {
+        match self {
+            CommandMode::Graphic4 | CommandMode::Graphic7 => 256,
+            CommandMode::Graphic5 | CommandMode::Graphic6 => 512,
+        }
+    }
+
+    fn pixels_per_byte(self) -> i32 {
+        match self {
+            CommandMode::Graphic4 | CommandMode::Graphic6 => 2,
+            CommandMode::Graphic5 => 4,
+            CommandMode::Graphic7 => 1,
+        }
+    }
+
+    fn bytes_per_line(self) -> i32 {
+        self.width() / self.pixels_per_byte()
+    }
+
+    // Lines that fit in the 128KB of VRAM
+    fn lines(self) -> i32 {
+        0x20000 / self.bytes_per_line()
+    }
+
+    fn color_mask(self) -> u8 {
+        match self {
+            CommandMode::Graphic4 | CommandMode::Graphic6 => 0x0f,
+            CommandMode::Graphic5 => 0x03,
+            CommandMode::Graphic7 => 0xff,
+        }
+    }
+
+    // VRAM address of a byte of the bitmap
+    pub fn byte_address(self, xb: i32, y: i32) -> usize {
+        ((y & (self.lines() - 1)) * self.bytes_per_line() + (xb & (self.bytes_per_line() - 1)))
+            as usize
+    }
+
+    // Address and shift of the bits of a pixel
+    fn pixel_address(self, x: i32, y: i32) -> (usize, u32) {
+        let ppb = self.pixels_per_byte();
+        let x = x & (self.width() - 1);
+        let shift = (ppb - 1 - x % ppb) * (8 / ppb);
+        (self.byte_address(x / ppb, y), shift as u32)
+    }
+
+    pub fn point(self, vram: &[u8], x: i32, y: i32) -> u8 {
+        let (address, shift) = self.pixel_address(x, y);
+        (vram[address] >> shift) & self.color_mask()
+    }
+
+    pub fn pset(self, vram: &mut [u8], x: i32, y: i32, color: u8, op: u8) {
+        let (address, shift) = self.pixel_address(x, y);
+        let mask = self.color_mask();
+        let dst = (vram[address] >> shift) & mask;
+        if let Some(color) = logical_op(op, color & mask, dst) {
+            vram[address] = (vram[address] & !(mask << shift)) | ((color & mask) << shift);
+        }
+    }
+}
+
+// Result of a logical operation, None when a T operation skips the pixel
+fn logical_op(op: u8, src: u8, dst: u8) -> Option<u8> {
+    if op & 0x08 != 0 && src == 0 {
+        return None;
+    }
+    Some(match op & 0x07 {
+        0 => src,
+        1 => src & dst,
+        2 => src | dst,
+        3 => src ^ dst,
+        4 => !src,
+        _ => dst,
+    })
+}
+
+// VDP clock cycles per Z80 cycle (21.48MHz / 3.58MHz)
+pub const VDP_CYCLES_PER_Z80: i32 = 6;
+
+// VDP cycles taken by each pixel, byte or dot, with the display and the
+// sprites on. They are averages: the chip waits for the VRAM accesses
+// left free by the display.
+fn unit_cycles(command: u8) -> i32 {
+    match command {
+        CMD_HMMV | CMD_HMMC => 65,
+        CMD_YMMM | CMD_SRCH => 125,
+        CMD_HMMM => 136,
+        CMD_POINT | CMD_PSET | CMD_LMMV | CMD_LMCM | CMD_LMMC => 137,
+        CMD_LINE => 147,
+        _ => 197, // LMMM
+    }
+}
+
+// Executes the commands over a VRAM buffer, as the Z80 cycles go by.
+// Software sees CE set in S#2 for as long as the real chip would be busy.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CommandEngine {
+    registers: [u8; 15],
+    // TR, BD and CE flags of S#2
+    status: u8,
+    // S#7
+    color: u8,
+    // S#8-S#9, X where SRCH found the border colour
+    border_x: u16,
+    // Command in progress
+    command: u8,
+    op: u8,
+    mode: CommandMode,
+    // Corners of the block and its size, in bytes for the high speed
+    // commands. LINE moves DX and DY along the line.
+    sx: i32,
+    sy: i32,
+    dx: i32,
+    dy: i32,
+    nx: i32,
+    ny: i32,
+    // Directions
+    tx: i32,
+    ty: i32,
+    // Position inside the block, or dots drawn by LINE
+    cx: i32,
+    cy: i32,
+    // LINE error term
+    err: i32,
+    // Byte written by the CPU for HMMC and LMMC
+    data: Option<u8>,
+    // VDP cycles available to the command
+    cycles: i32,
+}
+
+impl Default for CommandEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CommandEngine {
+    pub fn new() -> Self {
+        Self {
+            registers: [0; 15],
+            status: 0,
+            color: 0,
+            border_x: 0,
+            command: CMD_STOP,
+            op: 0,
+            mode: CommandMode::Graphic4,
+            sx: 0,
+            sy: 0,
+            dx: 0,
+            dy: 0,
+            nx: 0,
+            ny: 0,
+            tx: 1,
+            ty: 1,
+            cx: 0,
+            cy: 0,
+            err: 0,
+            data: None,
+            cycles: 0,
+        }
+    }
+
+    // Writes R#(32 + reg). Writing CMD starts a command, writing CLR
+    // feeds the CPU to VRAM transfers.
+    pub fn write_register(&mut self, reg: usize, value: u8, mode: CommandMode) {
+        self.registers[reg] = value;
+        match reg {
+            REG_CMD => self.start(mode),
+            REG_CLR if self.command == CMD_HMMC || self.command == CMD_LMMC => {
+                self.data = Some(value);
+                self.status &= !STATUS_TR;
+            }
+            _ => {}
+        }
+    }
+
+    // TR, BD and CE bits of S#2
+    pub fn status(&self) -> u8 {
+        self.status
+    }
+
+    pub fn is_busy(&self) -> bool {
+        self.status & STATUS_CE != 0
+    }
+
+    // Reads S#7. During LMCM this tells the engine to fetch the next pixel.
+    pub fn read_color(&mut self) -> u8 {
+        if self.command != CMD_HMMC && self.command != CMD_LMMC {
+            self.status &= !STATUS_TR;
+        }
+        self.color
+    }
+
+    pub fn border_x(&self) -> u16 {
+        self.border_x
+    }
+
+    // Runs the command for the Z80 cycles of the last instruction
+    pub fn run(&mut self, cycles: u32, vram: &mut [u8]) {
+        if !self.is_busy() {
+            return;
+        }
+        let cost = unit_cycles(self.command);
+        self.cycles += cycles as i32 * VDP_CYCLES_PER_Z80;
+        while self.is_busy() && self.cycles >= cost {
+            if self.waits_for_cpu() {
+                // Time spent waiting for the CPU is lost, the next unit
+                // starts as soon as it arrives
+                self.cycles = cost;
+                return;
+            }
+            self.cycles -= cost;
+            self.step(vram);
+        }
+    }
+
+    fn waits_for_cpu(&self) -> bool {
+        match self.command {
+            CMD_HMMC | CMD_LMMC => self.data.is_none(),
+            CMD_LMCM => self.status & STATUS_TR != 0,
+            _ => false,
+        }
+    }
+
+    fn word(&self, reg: usize, mask: u16) -> i32 {
+        ((((self.registers[reg + 1] as u16) << 8) | self.registers[reg] as u16) & mask) as i32
+    }
+
+    fn set_word(&mut self, reg: usize, value: i32) {
+        self.registers[reg] = value as u8;
+        self.registers[reg + 1] = ((value >> 8) & 0x03) as u8;
+    }
+
+    fn start(&mut self, mode: CommandMode) {
+        let command = self.registers[REG_CMD] >> 4;
+        let arg = self.registers[REG_ARG];
+        self.command = CMD_STOP;
+        self.status &= !(STATUS_TR | STATUS_CE);
+        self.op = self.registers[REG_CMD] & 0x0f;
+        self.mode = mode;
+        self.tx = if arg & ARG_DIX != 0 { -1 } else { 1 };
+        self.ty = if arg & ARG_DIY != 0 { -1 } else { 1 };
+        self.sx = self.word(REG_SX, 0x1ff);
+        self.sy = self.word(REG_SY, 0x3ff);
+        self.dx = self.word(REG_DX, 0x1ff);
+        self.dy = self.word(REG_DY, 0x3ff);
+        // 0 stands for the largest size
+        self.nx = match self.word(REG_NX, 0x1ff) {
+            0 => 512,
+            nx => nx,
+        };
+        self.ny = match self.word(REG_NY, 0x3ff) {
+            0 => 1024,
+            ny => ny,
+        };
+        self.cx = 0;
+        self.cy = 0;
+        self.data = None;
+        self.cycles = 0;
+
+        match command {
+            CMD_STOP => return,
+            CMD_POINT | CMD_PSET => {}
+            CMD_SRCH => {
+                self.status &= !STATUS_BD;
+                self.sx &= mode.width() - 1;
+            }
+            CMD_LINE => {
+                // NX and NY are the long and short sides here
+                self.nx = self.word(REG_NX, 0x3ff);
+                self.ny = self.word(REG_NY, 0x3ff);
+                self.err = (self.nx - 1) >> 1;
+            }
+            CMD_LMMV | CMD_LMMM | CMD_LMCM | CMD_LMMC | CMD_HMMV | CMD_HMMM | CMD_YMMM
+            | CMD_HMMC => {
+                self.command = command;
+                self.start_block();
+                if command == CMD_LMMC || command == CMD_HMMC {
+                    // The first data is already in CLR
+                    self.data = Some(self.registers[REG_CLR]);
+                }
+            }
+            _ => {
+                log::warn!("VDP command {:x} not implemented", command);
+                return;
+            }
+        }
+        self.command = command;
+        self.status |= STATUS_CE;
+    }
+
+    // Sets the block up, clipped at the left or right edge
+    fn start_block(&mut self) {
+        let mut width = self.mode.width();
+        if matches!(self.command, CMD_HMMV | CMD_HMMM | CMD_YMMM | CMD_HMMC) {
+            let ppb = self.mode.pixels_per_byte();
+            width /= ppb;
+            self.sx = (self.sx & (self.mode.width() - 1)) / ppb;
+            self.dx = (self.dx & (self.mode.width() - 1)) / ppb;
+            self.nx = (self.nx / ppb).max(1);
+        } else {
+            self.sx &= width - 1;
+            self.dx &= width - 1;
+        }
+        let room = |x: i32| if self.tx > 0 { width - x } else { x + 1 };
+        self.nx = match self.command {
+            // YMMM moves from DX to the edge
+            CMD_YMMM => {
+                self.sx = self.dx;
+                room(self.dx)
+            }
+            CMD_LMMM | CMD_HMMM => self.nx.min(room(self.sx)).min(room(self.dx)),
+            CMD_LMCM => self.nx.min(room(self.sx)),
+            _ => self.nx.min(room(self.dx)),
+        };
+    }
+
+    // Moves one pixel or byte, or draws one dot
+    fn step(&mut self, vram: &mut [u8]) {
+        let mode = self.mode;
+        let clr = self.registers[REG_CLR];
+        match self.command {
+            CMD_POINT => {
+                self.color = mode.point(vram, self.sx, self.sy);
+                return self.finish();
+            }
+            CMD_PSET => {
+                mode.pset(vram, self.dx, self.dy, clr, self.op);
+                return self.finish();
+            }
+            CMD_SRCH => return self.search_step(vram),
+            CMD_LINE => return self.line_step(vram),
+            _ => {}
+        }
+
+        let (sx, sy) = (self.sx + self.cx * self.tx, self.sy + self.cy * self.ty);
+        let (dx, dy) = (self.dx + self.cx * self.tx, self.dy + self.cy * self.ty);
+        match self.command {
+            CMD_LMMV => mode.pset(vram, dx, dy, clr, self.op),
+            CMD_LMMM => {
+                let color = mode.point(vram, sx, sy);
+                mode.pset(vram, dx, dy, color, self.op);
+            }
+            CMD_LMCM => {
+                self.color = mode.point(vram, sx, sy);
+                self.status |= STATUS_TR;
+            }
+            CMD_LMMC => {
+                let data = self.data.take().unwrap_or(clr);
+                mode.pset(vram, dx, dy, data, self.op);
+                self.status |= STATUS_TR;
+            }
+            CMD_HMMV => vram[mode.byte_address(dx, dy)] = clr,
+            CMD_HMMM | CMD_YMMM => {
+                vram[mode.byte_address(dx, dy)] = vram[mode.byte_address(sx, sy)]
+            }
+            CMD_HMMC => {
+                vram[mode.byte_address(dx, dy)] = self.data.take().unwrap_or(clr);
+                self.status |= STATUS_TR;
+            }
+            _ => return,
+        }
+
+        self.cx += 1;
+        if self.cx == self.nx {
+            self.cx = 0;
+            self.cy += 1;
+            if self.cy == self.ny {
+                // SY and DY are left on the line after the block
+                let ny = self.ny * self.ty;
+                if matches!(self.command, CMD_LMMM | CMD_LMCM | CMD_HMMM | CMD_YMMM) {
+                    self.set_word(REG_SY, self.sy + ny);
+                }
+                if self.command != CMD_LMCM {
+                    self.set_word(REG_DY, self.dy + ny);
+                }
+                self.finish();
+            }
+        }
+    }
+
+    fn finish(&mut self) {
+        // The last pixel of LMCM is still waiting for the CPU
+        if self.command != CMD_LMCM {
+            self.status &= !STATUS_TR;
+        }
+        self.status &= !STATUS_CE;
+        self.command = CMD_STOP;
+    }
+
+    // Looks for the border colour (or any other, with EQ) from SX to the
+    // edge of the screen, one dot at a time
+    fn search_step(&mut self, vram: &[u8]) {
+        let x = self.sx + self.cx * self.tx;
+        if !(0..self.mode.width()).contains(&x) {
+            return self.finish();
+        }
+        let clr = self.registers[REG_CLR] & self.mode.color_mask();
+        let found = self.mode.point(vram, x, self.sy) == clr;
+        if found != (self.registers[REG_ARG] & ARG_EQ != 0) {
+            self.status |= STATUS_BD;
+            self.border_x = x as u16;
+            return self.finish();
+        }
+        self.cx += 1;
+    }
+
+    // Draws NX + 1 dots, moving along the short side once the error term
+    // runs out
+    fn line_step(&mut self, vram: &mut [u8]) {
+        let clr = self.registers[REG_CLR];
+        self.mode.pset(vram, self.dx, self.dy, clr, self.op);
+        let minor_step = self.err < self.ny;
+        if minor_step {
+            self.err += self.nx;
+        }
+        self.err -= self.ny;
+        if self.registers[REG_ARG] & ARG_MAJ == 0 {
+            self.dx += self.tx;
+            if minor_step {
+                self.dy += self.ty;
+            }
+        } else {
+            self.dy += self.ty;
+            if minor_step {
+                self.dx += self.tx;
+            }
+        }
+        self.cx += 1;
+        if self.cx > self.nx || !(0..self.mode.width()).contains(&self.dx) {
+            self.set_word(REG_DY, self.dy & 0x3ff);
+            self.finish();
+        }
+    }
+}
@@ -1,7 +1,7 @@
 pub mod opcodes_disassembler;
 pub mod opcodes_map;
-pub mod opcodes_timings_msx;
 pub mod z80_base;
+pub mod z80_bus;
 pub mod z80_gen;
 pub mod z80_hook_controls;
 pub mod z80_hook_func;
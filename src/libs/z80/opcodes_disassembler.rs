@@ -24,10 +24,11 @@
 use super::z80_base::{
     join_bytes, SHIFT_0X_CB, SHIFT_0X_DD, SHIFT_0X_DDCB, SHIFT_0X_ED, SHIFT_0X_FD, Z80,
 };
+use super::z80_bus::Bus;
 
 // */
 #[allow(non_snake_case)]
-impl Z80 {
+impl<B: Bus> Z80<B> {
     pub fn disassemble_map(&mut self, opcode: u16) {
         match opcode {
             0 => {
@@ -5211,8 +5212,8 @@ impl Z80 {
     /* LD BC,nnnn */
     fn disassemble__LD_BC_NNNN(&mut self) {
         let address = self.PC() - 1;
-        let b1 = self.memory.read_byte(address + 1);
-        let b2 = self.memory.read_byte(address + 2);
+        let b1 = self.bus.read_byte(address + 1);
+        let b2 = self.bus.read_byte(address + 2);
         let nnnn = join_bytes(b2, b1);
         println!("({:04x}) LD BC,0x{:04x}", address, nnnn);
     }
@@ -5310,8 +5311,8 @@ impl Z80 {
     /* LD DE,nnnn */
     fn disassemble__LD_DE_NNNN(&mut self) {
         let address = self.PC() - 1;
-        let b1 = self.memory.read_byte(address + 1);
-        let b2 = self.memory.read_byte(address + 2);
+        let b1 = self.bus.read_byte(address + 1);
+        let b2 = self.bus.read_byte(address + 2);
         let nnnn = join_bytes(b2, b1);
         println!("({:04x}) LD DE,0x{:04x}", address, nnnn);
     }
@@ -5355,7 +5356,7 @@ impl Z80 {
     /* JR offset */
     fn disassemble__JR_OFFSET(&mut self) {
         let address = self.PC() - 1;
-        let offset = self.memory.read_byte(address + 1);
+        let offset = self.bus.read_byte(address + 1);
         println!("({:04x}) JR 0x{:02x}", address, offset);
     }
 
@@ -5404,15 +5405,15 @@ impl Z80 {
     /* JR NZ,offset */
     fn disassemble__JR_NZ_OFFSET(&mut self) {
         let address = self.PC() - 1;
-        let offset = self.memory.read_byte(address + 1);
+        let offset = self.bus.read_byte(address + 1);
         println!("({:04x}) JR NZ,0x{:02x}", address, offset);
     }
 
     /* LD HL,nnnn */
     fn disassemble__LD_HL_NNNN(&mut self) {
         let address = self.PC() - 1;
-        let b1 = self.memory.read_byte(address + 1);
-        let b2 = self.memory.read_byte(address + 2);
+        let b1 = self.bus.read_byte(address + 1);
+        let b2 = self.bus.read_byte(address + 2);
         let nnnn = join_bytes(b2, b1);
         println!("({:04x}) LD HL,0x{:04x}", address, nnnn);
     }
@@ -5420,8 +5421,8 @@ impl Z80 {
     /* LD (nnnn),HL */
     fn disassemble__LD_iNNNN_HL(&mut self) {
         let address = self.PC() - 1;
-        let b1 = self.memory.read_byte(address + 1);
-        let b2 = self.memory.read_byte(address + 2);
+        let b1 = self.bus.read_byte(address + 1);
+        let b2 = self.bus.read_byte(address + 2);
         let nnnn = join_bytes(b2, b1);
         println!("({:04x}) LD (0x{:04x}),HL", address, nnnn);
     }
@@ -5459,7 +5460,7 @@ impl Z80 {
     /* JR Z,offset */
     fn disassemble__JR_Z_OFFSET(&mut self) {
         let address = self.PC() - 1;
-        let offset = self.memory.read_byte(address + 1);
+        let offset = self.bus.read_byte(address + 1);
         println!("({:04x}) JR Z,0x{:02x}", address, offset);
     }
 
@@ -5472,8 +5473,8 @@ impl Z80 {
     /* LD HL,(nnnn) */
     fn disassemble__LD_HL_iNNNN(&mut self) {
         let address = self.PC() - 1;
-        let b1 = self.memory.read_byte(address + 1);
-        let b2 = self.memory.read_byte(address + 2);
+        let b1 = self.bus.read_byte(address + 1);
+        let b2 = self.bus.read_byte(address + 2);
         let nnnn = join_bytes(b2, b1);
         println!("({:04x}) LD HL,(0x{:04x})", address, nnnn);
     }
@@ -5511,15 +5512,15 @@ impl Z80 {
     /* JR NC,offset */
     fn disassemble__JR_NC_OFFSET(&mut self) {
         let address = self.PC() - 1;
-        let offset = self.memory.read_byte(address + 1);
+        let offset = self.bus.read_byte(address + 1);
         println!("({:04x}) JR NC,0x{:02x}", address, offset);
     }
 
     /* LD SP,nnnn */
     fn disassemble__LD_SP_NNNN(&mut self) {
         let address = self.PC() - 1;
-        let b1 = self.memory.read_byte(address + 1);
-        let b2 = self.memory.read_byte(address + 2);
+        let b1 = self.bus.read_byte(address + 1);
+        let b2 = self.bus.read_byte(address + 2);
         let nnnn = join_bytes(b2, b1);
         println!("({:04x}) LD SP,0x{:04x}", address, nnnn);
     }
@@ -5527,8 +5528,8 @@ impl Z80 {
     /* LD (nnnn),A */
     fn disassemble__LD_iNNNN_A(&mut self) {
         let address = self.PC() - 1;
-        let b1 = self.memory.read_byte(address + 1);
-        let b2 = self.memory.read_byte(address + 2);
+        let b1 = self.bus.read_byte(address + 1);
+        let b2 = self.bus.read_byte(address + 2);
         let nnnn = join_bytes(b2, b1);
         println!("({:04x}) LD (0x{:04x}),A", address, nnnn);
     }
@@ -5566,7 +5567,7 @@ impl Z80 {
     /* JR C,offset */
     fn disassemble__JR_C_OFFSET(&mut self) {
         let address = self.PC() - 1;
-        let offset = self.memory.read_byte(address + 1);
+        let offset = self.bus.read_byte(address + 1);
         println!("({:04x}) JR C,0x{:02x}", address, offset);
     }
 
@@ -5579,8 +5580,8 @@ impl Z80 {
     /* LD A,(nnnn) */
     fn disassemble__LD_A_iNNNN(&mut self) {
         let address = self.PC() - 1;
-        let b1 = self.memory.read_byte(address + 1);
-        let b2 = self.memory.read_byte(address + 2);
+        let b1 = self.bus.read_byte(address + 1);
+        let b2 = self.bus.read_byte(address + 2);
         let nnnn = join_bytes(b2, b1);
         println!("({:04x}) LD A,(0x{:04x})", address, nnnn);
     }
@@ -5606,7 +5607,7 @@ impl Z80 {
     /* LD A,nn */
     fn disassemble__LD_A_NN(&mut self) {
         let address = self.PC() - 1;
-        let nn = self.memory.read_byte(address + 1);
+        let nn = self.bus.read_byte(address + 1);
         println!("({:04x}) LD A,0x{:02x}", address, nn);
     }
 
@@ -6399,8 +6400,8 @@ impl Z80 {
     /* JP NZ,nnnn */
     fn disassemble__JP_NZ_NNNN(&mut self) {
         let address = self.PC() - 1;
-        let b1 = self.memory.read_byte(address + 1);
-        let b2 = self.memory.read_byte(address + 2);
+        let b1 = self.bus.read_byte(address + 1);
+        let b2 = self.bus.read_byte(address + 2);
         let nnnn = join_bytes(b2, b1);
         println!("({:04x}) JP NZ,0x{:04x}", address, nnnn);
     }
@@ -6408,8 +6409,8 @@ impl Z80 {
     /* JP nnnn */
     fn disassemble__JP_NNNN(&mut self) {
         let address = self.PC() - 1;
-        let b1 = self.memory.read_byte(address + 1);
-        let b2 = self.memory.read_byte(address + 2);
+        let b1 = self.bus.read_byte(address + 1);
+        let b2 = self.bus.read_byte(address + 2);
         let nnnn = join_bytes(b2, b1);
         println!("({:04x}) JP 0x{:04x}", address, nnnn);
     }
@@ -6417,8 +6418,8 @@ impl Z80 {
     /* CALL NZ,nnnn */
     fn disassemble__CALL_NZ_NNNN(&mut self) {
         let address = self.PC() - 1;
-        let b1 = self.memory.read_byte(address + 1);
-        let b2 = self.memory.read_byte(address + 2);
+        let b1 = self.bus.read_byte(address + 1);
+        let b2 = self.bus.read_byte(address + 2);
         let nnnn = join_bytes(b2, b1);
         println!("({:04x}) CALL NZ,0x{:04x}", address, nnnn);
     }
@@ -6432,7 +6433,7 @@ impl Z80 {
     /* ADD A,nn */
     fn disassemble__ADD_A_NN(&mut self) {
         let address = self.PC() - 1;
-        let nn = self.memory.read_byte(address + 1);
+        let nn = self.bus.read_byte(address + 1);
         println!("({:04x}) ADD A,0x{:02x}", address, nn);
     }
 
@@ -6457,8 +6458,8 @@ impl Z80 {
     /* JP Z,nnnn */
     fn disassemble__JP_Z_NNNN(&mut self) {
         let address = self.PC() - 1;
-        let b1 = self.memory.read_byte(address + 1);
-        let b2 = self.memory.read_byte(address + 2);
+        let b1 = self.bus.read_byte(address + 1);
+        let b2 = self.bus.read_byte(address + 2);
         let nnnn = join_bytes(b2, b1);
         println!("({:04x}) JP Z,0x{:04x}", address, nnnn);
     }
@@ -6472,8 +6473,8 @@ impl Z80 {
     /* CALL Z,nnnn */
     fn disassemble__CALL_Z_NNNN(&mut self) {
         let address = self.PC() - 1;
-        let b1 = self.memory.read_byte(address + 1);
-        let b2 = self.memory.read_byte(address + 2);
+        let b1 = self.bus.read_byte(address + 1);
+        let b2 = self.bus.read_byte(address + 2);
         let nnnn = join_bytes(b2, b1);
         println!("({:04x}) CALL Z,0x{:04x}", address, nnnn);
     }
@@ -6481,8 +6482,8 @@ impl Z80 {
     /* CALL nnnn */
     fn disassemble__CALL_NNNN(&mut self) {
         let address = self.PC() - 1;
-        let b1 = self.memory.read_byte(address + 1);
-        let b2 = self.memory.read_byte(address + 2);
+        let b1 = self.bus.read_byte(address + 1);
+        let b2 = self.bus.read_byte(address + 2);
         let nnnn = join_bytes(b2, b1);
         println!("({:04x}) CALL 0x{:04x}", address, nnnn);
     }
@@ -6490,7 +6491,7 @@ impl Z80 {
     /* ADC A,nn */
     fn disassemble__ADC_A_NN(&mut self) {
         let address = self.PC() - 1;
-        let nn = self.memory.read_byte(address + 1);
+        let nn = self.bus.read_byte(address + 1);
         println!("({:04x}) ADC A,0x{:02x}", address, nn);
     }
 
@@ -6515,8 +6516,8 @@ impl Z80 {
     /* JP NC,nnnn */
     fn disassemble__JP_NC_NNNN(&mut self) {
         let address = self.PC() - 1;
-        let b1 = self.memory.read_byte(address + 1);
-        let b2 = self.memory.read_byte(address + 2);
+        let b1 = self.bus.read_byte(address + 1);
+        let b2 = self.bus.read_byte(address + 2);
         let nnnn = join_bytes(b2, b1);
         println!("({:04x}) JP NC,0x{:04x}", address, nnnn);
     }
@@ -6524,15 +6525,15 @@ impl Z80 {
     /* OUT (nn),A */
     fn disassemble__OUT_iNN_A(&mut self) {
         let address = self.PC() - 1;
-        let nn = self.memory.read_byte(address + 1);
+        let nn = self.bus.read_byte(address + 1);
         println!("({:04x}) OUT (0x{:02x}),A", address, nn);
     }
 
     /* CALL NC,nnnn */
     fn disassemble__CALL_NC_NNNN(&mut self) {
         let address = self.PC() - 1;
-        let b1 = self.memory.read_byte(address + 1);
-        let b2 = self.memory.read_byte(address + 2);
+        let b1 = self.bus.read_byte(address + 1);
+        let b2 = self.bus.read_byte(address + 2);
         let nnnn = join_bytes(b2, b1);
         println!("({:04x}) CALL NC,0x{:04x}", address, nnnn);
     }
@@ -6546,7 +6547,7 @@ impl Z80 {
     /* SUB nn */
     fn disassemble__SUB_NN(&mut self) {
         let address = self.PC() - 1;
-        let nn = self.memory.read_byte(address + 1);
+        let nn = self.bus.read_byte(address + 1);
         println!("({:04x}) SUB 0x{:02x}", address, nn);
     }
 
@@ -6571,8 +6572,8 @@ impl Z80 {
     /* JP C,nnnn */
     fn disassemble__JP_C_NNNN(&mut self) {
         let address = self.PC() - 1;
-        let b1 = self.memory.read_byte(address + 1);
-        let b2 = self.memory.read_byte(address + 2);
+        let b1 = self.bus.read_byte(address + 1);
+        let b2 = self.bus.read_byte(address + 2);
         let nnnn = join_bytes(b2, b1);
         println!("({:04x}) JP C,0x{:04x}", address, nnnn);
     }
@@ -6580,15 +6581,15 @@ impl Z80 {
     /* IN A,(nn) */
     fn disassemble__IN_A_iNN(&mut self) {
         let address = self.PC() - 1;
-        let nn = self.memory.read_byte(address + 1);
+        let nn = self.bus.read_byte(address + 1);
         println!("({:04x}) IN A,(0x{:02x})", address, nn);
     }
 
     /* CALL C,nnnn */
     fn disassemble__CALL_C_NNNN(&mut self) {
         let address = self.PC() - 1;
-        let b1 = self.memory.read_byte(address + 1);
-        let b2 = self.memory.read_byte(address + 2);
+        let b1 = self.bus.read_byte(address + 1);
+        let b2 = self.bus.read_byte(address + 2);
         let nnnn = join_bytes(b2, b1);
         println!("({:04x}) CALL C,0x{:04x}", address, nnnn);
     }
@@ -6602,7 +6603,7 @@ impl Z80 {
     /* SBC A,nn */
     fn disassemble__SBC_A_NN(&mut self) {
         let address = self.PC() - 1;
-        let nn = self.memory.read_byte(address + 1);
+        let nn = self.bus.read_byte(address + 1);
         println!("({:04x}) SBC A,0x{:02x}", address, nn);
     }
 
@@ -6627,8 +6628,8 @@ impl Z80 {
     /* JP PO,nnnn */
     fn disassemble__JP_PO_NNNN(&mut self) {
         let address = self.PC() - 1;
-        let b1 = self.memory.read_byte(address + 1);
-        let b2 = self.memory.read_byte(address + 2);
+        let b1 = self.bus.read_byte(address + 1);
+        let b2 = self.bus.read_byte(address + 2);
         let nnnn = join_bytes(b2, b1);
         println!("({:04x}) JP PO,0x{:04x}", address, nnnn);
     }
@@ -6642,8 +6643,8 @@ impl Z80 {
     /* CALL PO,nnnn */
     fn disassemble__CALL_PO_NNNN(&mut self) {
         let address = self.PC() - 1;
-        let b1 = self.memory.read_byte(address + 1);
-        let b2 = self.memory.read_byte(address + 2);
+        let b1 = self.bus.read_byte(address + 1);
+        let b2 = self.bus.read_byte(address + 2);
         let nnnn = join_bytes(b2, b1);
         println!("({:04x}) CALL PO,0x{:04x}", address, nnnn);
     }
@@ -6657,7 +6658,7 @@ impl Z80 {
     /* AND nn */
     fn disassemble__AND_NN(&mut self) {
         let address = self.PC() - 1;
-        let nn = self.memory.read_byte(address + 1);
+        let nn = self.bus.read_byte(address + 1);
         println!("({:04x}) AND 0x{:02x}", address, nn);
     }
 
@@ -6682,8 +6683,8 @@ impl Z80 {
     /* JP PE,nnnn */
     fn disassemble__JP_PE_NNNN(&mut self) {
         let address = self.PC() - 1;
-        let b1 = self.memory.read_byte(address + 1);
-        let b2 = self.memory.read_byte(address + 2);
+        let b1 = self.bus.read_byte(address + 1);
+        let b2 = self.bus.read_byte(address + 2);
         let nnnn = join_bytes(b2, b1);
         println!("({:04x}) JP PE,0x{:04x}", address, nnnn);
     }
@@ -6697,8 +6698,8 @@ impl Z80 {
     /* CALL PE,nnnn */
     fn disassemble__CALL_PE_NNNN(&mut self) {
         let address = self.PC() - 1;
-        let b1 = self.memory.read_byte(address + 1);
-        let b2 = self.memory.read_byte(address + 2);
+        let b1 = self.bus.read_byte(address + 1);
+        let b2 = self.bus.read_byte(address + 2);
         let nnnn = join_bytes(b2, b1);
         println!("({:04x}) CALL PE,0x{:04x}", address, nnnn);
     }
@@ -6712,7 +6713,7 @@ impl Z80 {
     /* XOR A,nn */
     fn disassemble__XOR_A_NN(&mut self) {
         let address = self.PC() - 1;
-        let nn = self.memory.read_byte(address + 1);
+        let nn = self.bus.read_byte(address + 1);
         println!("({:04x}) XOR A,0x{:02x}", address, nn);
     }
 
@@ -6737,8 +6738,8 @@ impl Z80 {
     /* JP P,nnnn */
     fn disassemble__JP_P_NNNN(&mut self) {
         let address = self.PC() - 1;
-        let b1 = self.memory.read_byte(address + 1);
-        let b2 = self.memory.read_byte(address + 2);
+        let b1 = self.bus.read_byte(address + 1);
+        let b2 = self.bus.read_byte(address + 2);
         let nnnn = join_bytes(b2, b1);
         println!("({:04x}) JP P,0x{:04x}", address, nnnn);
     }
@@ -6752,8 +6753,8 @@ impl Z80 {
     /* CALL P,nnnn */
     fn disassemble__CALL_P_NNNN(&mut self) {
         let address = self.PC() - 1;
-        let b1 = self.memory.read_byte(address + 1);
-        let b2 = self.memory.read_byte(address + 2);
+        let b1 = self.bus.read_byte(address + 1);
+        let b2 = self.bus.read_byte(address + 2);
         let nnnn = join_bytes(b2, b1);
         println!("({:04x}) CALL P,0x{:04x}", address, nnnn);
     }
@@ -6767,7 +6768,7 @@ impl Z80 {
     /* OR nn */
     fn disassemble__OR_NN(&mut self) {
         let address = self.PC() - 1;
-        let nn = self.memory.read_byte(address + 1);
+        let nn = self.bus.read_byte(address + 1);
         println!("({:04x}) OR 0x{:02x}", address, nn);
     }
 
@@ -6792,8 +6793,8 @@ impl Z80 {
     /* JP M,nnnn */
     fn disassemble__JP_M_NNNN(&mut self) {
         let address = self.PC() - 1;
-        let b1 = self.memory.read_byte(address + 1);
-        let b2 = self.memory.read_byte(address + 2);
+        let b1 = self.bus.read_byte(address + 1);
+        let b2 = self.bus.read_byte(address + 2);
         let nnnn = join_bytes(b2, b1);
         println!("({:04x}) JP M,0x{:04x}", address, nnnn);
     }
@@ -6807,8 +6808,8 @@ impl Z80 {
     /* CALL M,nnnn */
     fn disassemble__CALL_M_NNNN(&mut self) {
         let address = self.PC() - 1;
-        let b1 = self.memory.read_byte(address + 1);
-        let b2 = self.memory.read_byte(address + 2);
+        let b1 = self.bus.read_byte(address + 1);
+        let b2 = self.bus.read_byte(address + 2);
         let nnnn = join_bytes(b2, b1);
         println!("({:04x}) CALL M,0x{:04x}", address, nnnn);
     }
@@ -6822,7 +6823,7 @@ impl Z80 {
     /* CP nn */
     fn disassemble__CP_NN(&mut self) {
         let address = self.PC() - 1;
-        let nn = self.memory.read_byte(address + 1);
+        let nn = self.bus.read_byte(address + 1);
         println!("({:04x}) CP 0x{:02x}", address, nn);
     }
 
@@ -8389,8 +8390,8 @@ impl Z80 {
     /* LD (nnnn),BC */
     fn disassembleED__LD_iNNNN_BC(&mut self) {
         let address = self.PC() - 1;
-        let b1 = self.memory.read_byte(address + 1);
-        let b2 = self.memory.read_byte(address + 2);
+        let b1 = self.bus.read_byte(address + 1);
+        let b2 = self.bus.read_byte(address + 2);
         let nnnn = join_bytes(b2, b1);
         println!("({:04x}) LD (0x{:04x}),BC", address, nnnn);
     }
@@ -8440,8 +8441,8 @@ impl Z80 {
     /* LD BC,(nnnn) */
     fn disassembleED__LD_BC_iNNNN(&mut self) {
         let address = self.PC() - 1;
-        let b1 = self.memory.read_byte(address + 1);
-        let b2 = self.memory.read_byte(address + 2);
+        let b1 = self.bus.read_byte(address + 1);
+        let b2 = self.bus.read_byte(address + 2);
         let nnnn = join_bytes(b2, b1);
         println!("({:04x}) LD BC,(0x{:04x})", address, nnnn);
     }
@@ -8473,8 +8474,8 @@ impl Z80 {
     /* LD (nnnn),DE */
     fn disassembleED__LD_iNNNN_DE(&mut self) {
         let address = self.PC() - 1;
-        let b1 = self.memory.read_byte(address + 1);
-        let b2 = self.memory.read_byte(address + 2);
+        let b1 = self.bus.read_byte(address + 1);
+        let b2 = self.bus.read_byte(address + 2);
         let nnnn = join_bytes(b2, b1);
         println!("({:04x}) LD (0x{:04x}),DE", address, nnnn);
     }
@@ -8512,8 +8513,8 @@ impl Z80 {
     /* LD DE,(nnnn) */
     fn disassembleED__LD_DE_iNNNN(&mut self) {
         let address = self.PC() - 1;
-        let b1 = self.memory.read_byte(address + 1);
-        let b2 = self.memory.read_byte(address + 2);
+        let b1 = self.bus.read_byte(address + 1);
+        let b2 = self.bus.read_byte(address + 2);
         let nnnn = join_bytes(b2, b1);
         println!("({:04x}) LD DE,(0x{:04x})", address, nnnn);
     }
@@ -8551,8 +8552,8 @@ impl Z80 {
     /* LD (nnnn),HL */
     fn disassembleED__LD_iNNNN_HL(&mut self) {
         let address = self.PC() - 1;
-        let b1 = self.memory.read_byte(address + 1);
-        let b2 = self.memory.read_byte(address + 2);
+        let b1 = self.bus.read_byte(address + 1);
+        let b2 = self.bus.read_byte(address + 2);
         let nnnn = join_bytes(b2, b1);
         println!("({:04x}) LD (0x{:04x}),HL", address, nnnn);
     }
@@ -8584,8 +8585,8 @@ impl Z80 {
     /* LD HL,(nnnn) */
     fn disassembleED__LD_HL_iNNNN(&mut self) {
         let address = self.PC() - 1;
-        let b1 = self.memory.read_byte(address + 1);
-        let b2 = self.memory.read_byte(address + 2);
+        let b1 = self.bus.read_byte(address + 1);
+        let b2 = self.bus.read_byte(address + 2);
         let nnnn = join_bytes(b2, b1);
         println!("({:04x}) LD HL,(0x{:04x})", address, nnnn);
     }
@@ -8617,8 +8618,8 @@ impl Z80 {
     /* LD (nnnn),SP */
     fn disassembleED__LD_iNNNN_SP(&mut self) {
         let address = self.PC() - 1;
-        let b1 = self.memory.read_byte(address + 1);
-        let b2 = self.memory.read_byte(address + 2);
+        let b1 = self.bus.read_byte(address + 1);
+        let b2 = self.bus.read_byte(address + 2);
         let nnnn = join_bytes(b2, b1);
         println!("({:04x}) LD (0x{:04x}),SP", address, nnnn);
     }
@@ -8644,8 +8645,8 @@ impl Z80 {
     /* LD SP,(nnnn) */
     fn disassembleED__LD_SP_iNNNN(&mut self) {
         let address = self.PC() - 1;
-        let b1 = self.memory.read_byte(address + 1);
-        let b2 = self.memory.read_byte(address + 2);
+        let b1 = self.bus.read_byte(address + 1);
+        let b2 = self.bus.read_byte(address + 2);
         let nnnn = join_bytes(b2, b1);
         println!("({:04x}) LD SP,(0x{:04x})", address, nnnn);
     }
@@ -8767,8 +8768,8 @@ impl Z80 {
     /* LD REGISTER,nnnn */
     fn disassembleDD__LD_REG_NNNN(&mut self) {
         let address = self.PC() - 1;
-        let b1 = self.memory.read_byte(address + 1);
-        let b2 = self.memory.read_byte(address + 2);
+        let b1 = self.bus.read_byte(address + 1);
+        let b2 = self.bus.read_byte(address + 2);
         let nnnn = join_bytes(b2, b1);
         println!("({:04x}) LD ix,0x{:04x}", address, nnnn);
     }
@@ -8776,8 +8777,8 @@ impl Z80 {
     /* LD (nnnn),REGISTER */
     fn disassembleDD__LD_iNNNN_REG(&mut self) {
         let address = self.PC() - 1;
-        let b1 = self.memory.read_byte(address + 1);
-        let b2 = self.memory.read_byte(address + 2);
+        let b1 = self.bus.read_byte(address + 1);
+        let b2 = self.bus.read_byte(address + 2);
         let nnnn = join_bytes(b2, b1);
         println!("({:04x}) LD (0x{:04x}),ix", address, nnnn);
     }
@@ -8803,7 +8804,7 @@ impl Z80 {
     /* LD REGISTERH,nn */
     fn disassembleDD__LD_REGH_NN(&mut self) {
         let address = self.PC() - 1;
-        let nn = self.memory.read_byte(address + 1);
+        let nn = self.bus.read_byte(address + 1);
         println!("({:04x}) LD IXH,0x{:04x}", address, nn);
     }
 
@@ -8816,8 +8817,8 @@ impl Z80 {
     /* LD REGISTER,(nnnn) */
     fn disassembleDD__LD_REG_iNNNN(&mut self) {
         let address = self.PC() - 1;
-        let b1 = self.memory.read_byte(address + 1);
-        let b2 = self.memory.read_byte(address + 2);
+        let b1 = self.bus.read_byte(address + 1);
+        let b2 = self.bus.read_byte(address + 2);
         let nnnn = join_bytes(b2, b1);
         println!("({:04x}) LD ix,(0x{:04x})", address, nnnn);
     }
@@ -8843,7 +8844,7 @@ impl Z80 {
     /* LD REGISTERL,nn */
     fn disassembleDD__LD_REGL_NN(&mut self) {
         let address = self.PC() - 1;
-        let nn = self.memory.read_byte(address + 1);
+        let nn = self.bus.read_byte(address + 1);
         println!("({:04x}) LD IXL,0x{:02x}", address, nn);
     }
 
@@ -8862,8 +8863,8 @@ impl Z80 {
     /* LD (REGISTER+dd),nn */
     fn disassembleDD__LD_iREGpDD_NN(&mut self) {
         let address = self.PC() - 1;
-        let ix_dd = self.memory.read_byte(address + 1);
-        let nn = self.memory.read_byte(address + 2);
+        let ix_dd = self.bus.read_byte(address + 1);
+        let nn = self.bus.read_byte(address + 2);
         println!("({:04x}) LD (ix+0x{:02x}),0x{:02x}", address, ix_dd, nn);
     }
 
@@ -9302,8 +9303,8 @@ impl Z80 {
     /* LD (nnnn),REGISTER */
     fn disassembleFD__LD_iNNNN_REG(&mut self) {
         let address = self.PC() - 1;
-        let b1 = self.memory.read_byte(address + 1);
-        let b2 = self.memory.read_byte(address + 2);
+        let b1 = self.bus.read_byte(address + 1);
+        let b2 = self.bus.read_byte(address + 2);
         let nnnn = join_bytes(b2, b1);
         println!("({:04x}) LD (0x{:04x}),iy", address, nnnn);
     }
@@ -9329,7 +9330,7 @@ impl Z80 {
     /* LD REGISTERH,nn */
     fn disassembleFD__LD_REGH_NN(&mut self) {
         let address = self.PC() - 1;
-        let nn = self.memory.read_byte(address + 1);
+        let nn = self.bus.read_byte(address + 1);
         println!("({:04x}) LD IYH,0x{:02x}", address, nn);
     }
 
@@ -9342,8 +9343,8 @@ impl Z80 {
     /* LD REGISTER,(nnnn) */
     fn disassembleFD__LD_REG_iNNNN(&mut self) {
         let address = self.PC() - 1;
-        let b1 = self.memory.read_byte(address + 1);
-        let b2 = self.memory.read_byte(address + 2);
+        let b1 = self.bus.read_byte(address + 1);
+        let b2 = self.bus.read_byte(address + 2);
         let nnnn = join_bytes(b2, b1);
         println!("({:04x}) LD iy,(0x{:04x})", address, nnnn);
     }
@@ -9369,7 +9370,7 @@ impl Z80 {
     /* LD REGISTERL,nn */
     fn disassembleFD__LD_REGL_NN(&mut self) {
         let address = self.PC() - 1;
-        let nn = self.memory.read_byte(address + 1);
+        let nn = self.bus.read_byte(address + 1);
         println!("({:04x}) LD IYL,0x{:02x}", address, nn);
     }
 
@@ -9388,8 +9389,8 @@ impl Z80 {
     /* LD (REGISTER+dd),nn */
     fn disassembleFD__LD_iREGpDD_NN(&mut self) {
         let address = self.PC() - 1;
-        let iy_dd = self.memory.read_byte(address + 1);
-        let nn = self.memory.read_byte(address + 2);
+        let iy_dd = self.bus.read_byte(address + 1);
+        let nn = self.bus.read_byte(address + 2);
         println!("({:04x}) LD (iy+0x{:02x}),0x{:02x}", address, iy_dd, nn);
     }
 
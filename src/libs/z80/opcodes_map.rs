@@ -21,6 +21,7 @@
 // OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
 // WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 
+use super::z80_bus::Bus;
 use super::{
     z80_base::{
         join_bytes, sign_extend, split_word, tern_op_b, Register16, FLAG_3, FLAG_5, FLAG_C, FLAG_H,
@@ -34,10 +35,10 @@ macro_rules! fn_instr_ld_r16_nnnn {
     ($fn:tt, $r:ident, $fs:ident) => {
         pub(crate) fn $fn(&mut self) {
             let address = self.PC();
-            let b1 = self.memory.read_byte(address);
+            let b1 = self.read_byte(address);
             self.IncPC(1);
             let address = self.PC();
-            let b2 = self.memory.read_byte(address);
+            let b2 = self.read_byte(address);
             self.IncPC(1);
             self.$fs(join_bytes(b2, b1));
         }
@@ -55,7 +56,7 @@ macro_rules! fn_instr_ld_i_nnnn_r16 {
 macro_rules! fn_instr_ld_i_r16_r8 {
     ($fn:tt, $r16:ident, $r8:ident) => {
         pub(crate) fn $fn(&mut self) {
-            self.memory.write_byte(self.$r16(), self.data.$r8);
+            self.write_byte(self.$r16(), self.data.$r8);
         }
     };
 }
@@ -136,7 +137,7 @@ macro_rules! fn_instr_push_r16 {
     ($fn:tt, $rl:ident, $rh:ident) => {
         pub(crate) fn $fn(&mut self) {
             let _address = self.IR();
-            self.memory.contend_read_no_mreq(_address, 1);
+            self.contend_read_no_mreq(_address, 1);
             self.push16(self.data.$rl, self.data.$rh);
         }
     };
@@ -146,7 +147,27 @@ macro_rules! fn_instr_ld_a_r16 {
     ($fn:tt, $r16:ident) => {
         pub(crate) fn $fn(&mut self) {
             let address = self.$r16();
-            self.data.A = self.memory.read_byte(address);
+            self.data.A = self.read_byte(address);
+        }
+    };
+}
+
+macro_rules! fn_instr_ld_a_i_r16 {
+    ($fn:tt, $r16:ident) => {
+        pub(crate) fn $fn(&mut self) {
+            let address = self.$r16();
+            self.data.A = self.read_byte(address);
+            self.data.memptr = address.wrapping_add(1);
+        }
+    };
+}
+
+macro_rules! fn_instr_ld_i_r16_a {
+    ($fn:tt, $r16:ident) => {
+        pub(crate) fn $fn(&mut self) {
+            let address = self.$r16();
+            self.write_byte(address, self.data.A);
+            self.data.memptr = join_bytes(self.data.A, address.wrapping_add(1) as u8);
         }
     };
 }
@@ -163,7 +184,7 @@ macro_rules! fn_instr_op_16 {
     ($fn:tt, $op:ident) => {
         pub(crate) fn $fn(&mut self) {
             let _address = self.IR();
-            self.memory.contend_read_no_mreq_loop(_address, 1, 2);
+            self.contend_read_no_mreq_loop(_address, 1, 2);
             self.$op();
         }
     };
@@ -181,7 +202,7 @@ macro_rules! fn_instr_ld_r8_nn {
     ($fn:tt, $r:ident) => {
         pub(crate) fn $fn(&mut self) {
             let address = self.PC();
-            self.data.$r = self.memory.read_byte(address);
+            self.data.$r = self.read_byte(address);
             self.IncPC(1);
         }
     };
@@ -191,7 +212,7 @@ macro_rules! fn_instr_add_hl_r16 {
     ($fn:tt, $r16:ident) => {
         pub(crate) fn $fn(&mut self) {
             let _address = self.IR();
-            self.memory.contend_read_no_mreq_loop(_address, 1, 7);
+            self.contend_read_no_mreq_loop(_address, 1, 7);
             let mut hl = Register16::new(self.data.H, self.data.L);
             let value2 = self.$r16();
             self.add16(&mut hl, value2);
@@ -212,12 +233,12 @@ macro_rules! fn_instr_ld_i_reg_p_dd_r8 {
     ($fn:tt, $ri:ident, $r:ident) => {
         pub(crate) fn $fn(&mut self) {
             let address = self.PC();
-            let offset = self.memory.read_byte(address);
+            let offset = self.read_byte(address);
             let _address = self.PC();
-            self.memory.contend_read_no_mreq_loop(_address, 1, 5);
+            self.contend_read_no_mreq_loop(_address, 1, 5);
             self.IncPC(1);
-            self.memory
-                .write_byte(self.$ri() + (sign_extend(offset) as u16), self.data.$r)
+            self.data.memptr = self.$ri().wrapping_add(sign_extend(offset) as u16);
+            self.write_byte(self.data.memptr, self.data.$r)
         }
     };
 }
@@ -226,13 +247,12 @@ macro_rules! fn_instr_op_a_i_reg_p_dd {
     ($fn:tt, $r:ident, $op:ident) => {
         pub(crate) fn $fn(&mut self) {
             let address = self.PC();
-            let offset: u8 = self.memory.read_byte(address);
+            let offset: u8 = self.read_byte(address);
             let _address = self.PC();
-            self.memory.contend_read_no_mreq_loop(_address, 1, 5);
+            self.contend_read_no_mreq_loop(_address, 1, 5);
             self.IncPC(1);
-            let byte_temp: u8 = self
-                .memory
-                .read_byte(self.$r() + (sign_extend(offset) as u16));
+            self.data.memptr = self.$r().wrapping_add(sign_extend(offset) as u16);
+            let byte_temp: u8 = self.read_byte(self.data.memptr);
             self.$op(byte_temp)
         }
     };
@@ -254,15 +274,16 @@ macro_rules! fn_instr_op_i_reg_p_dd {
     ($fn:tt, $ri:ident, $op:ident) => {
         pub(crate) fn $fn(&mut self) {
             let address = self.PC();
-            let offset: u8 = self.memory.read_byte(address);
+            let offset: u8 = self.read_byte(address);
             let _address = self.PC();
-            self.memory.contend_read_no_mreq_loop(_address, 1, 5);
+            self.contend_read_no_mreq_loop(_address, 1, 5);
             self.IncPC(1);
-            let word_temp: u16 = self.$ri() + sign_extend(offset) as u16;
-            let mut byte_temp: u8 = self.memory.read_byte(word_temp);
-            self.memory.contend_read_no_mreq(word_temp, 1);
+            let word_temp: u16 = self.$ri().wrapping_add(sign_extend(offset) as u16);
+            self.data.memptr = word_temp;
+            let mut byte_temp: u8 = self.read_byte(word_temp);
+            self.contend_read_no_mreq(word_temp, 1);
             self.$op(&mut byte_temp);
-            self.memory.write_byte(word_temp, byte_temp)
+            self.write_byte(word_temp, byte_temp)
         }
     };
 }
@@ -283,13 +304,12 @@ macro_rules! fn_instr_ld_r_i_reg_p_dd {
     ($fn:tt, $r:ident, $ri:ident) => {
         pub(crate) fn $fn(&mut self) {
             let address = self.PC();
-            let offset: u8 = self.memory.read_byte(address);
+            let offset: u8 = self.read_byte(address);
             let _address = self.PC();
-            self.memory.contend_read_no_mreq_loop(_address, 1, 5);
+            self.contend_read_no_mreq_loop(_address, 1, 5);
             self.IncPC(1);
-            self.data.$r = self
-                .memory
-                .read_byte(self.$ri() + (sign_extend(offset) as u16));
+            self.data.memptr = self.$ri().wrapping_add(sign_extend(offset) as u16);
+            self.data.$r = self.read_byte(self.data.memptr);
         }
     };
 }
@@ -309,10 +329,10 @@ macro_rules! fn_instr_fd_ld_r_i_reg_p_dd {
 macro_rules! fn_instr_ddcb_op_i_reg_p_dd {
     ($fn:tt, $op:ident) => {
         pub(crate) fn $fn(&mut self) {
-            let mut byte_temp: u8 = self.memory.read_byte(self.data.temp_addr);
-            self.memory.contend_read_no_mreq(self.data.temp_addr, 1);
+            let mut byte_temp: u8 = self.read_byte(self.data.memptr);
+            self.contend_read_no_mreq(self.data.memptr, 1);
             byte_temp = self.$op(byte_temp);
-            self.memory.write_byte(self.data.temp_addr, byte_temp);
+            self.write_byte(self.data.memptr, byte_temp);
         }
     };
 }
@@ -320,10 +340,10 @@ macro_rules! fn_instr_ddcb_op_i_reg_p_dd {
 macro_rules! fn_instr_ddcb_ld_r_rlc_i_reg_p_dd {
     ($fn:tt, $r:ident) => {
         pub(crate) fn $fn(&mut self) {
-            self.data.$r = self.memory.read_byte(self.data.temp_addr);
-            self.memory.contend_read_no_mreq(self.data.temp_addr, 1);
+            self.data.$r = self.read_byte(self.data.memptr);
+            self.contend_read_no_mreq(self.data.memptr, 1);
             self.data.$r = self.rlc(self.data.$r);
-            self.memory.write_byte(self.data.temp_addr, self.data.$r);
+            self.write_byte(self.data.memptr, self.data.$r);
         }
     };
 }
@@ -331,10 +351,10 @@ macro_rules! fn_instr_ddcb_ld_r_rlc_i_reg_p_dd {
 macro_rules! fn_instr_ddcb_ld_r_rrc_i_reg_p_dd {
     ($fn:tt, $r:ident) => {
         pub(crate) fn $fn(&mut self) {
-            self.data.$r = self.memory.read_byte(self.data.temp_addr);
-            self.memory.contend_read_no_mreq(self.data.temp_addr, 1);
+            self.data.$r = self.read_byte(self.data.memptr);
+            self.contend_read_no_mreq(self.data.memptr, 1);
             self.data.$r = self.rrc(self.data.$r);
-            self.memory.write_byte(self.data.temp_addr, self.data.$r);
+            self.write_byte(self.data.memptr, self.data.$r);
         }
     };
 }
@@ -342,10 +362,10 @@ macro_rules! fn_instr_ddcb_ld_r_rrc_i_reg_p_dd {
 macro_rules! fn_instr_ddcb_ld_r_rl_i_reg_p_dd {
     ($fn:tt, $r:ident) => {
         pub(crate) fn $fn(&mut self) {
-            self.data.$r = self.memory.read_byte(self.data.temp_addr);
-            self.memory.contend_read_no_mreq(self.data.temp_addr, 1);
+            self.data.$r = self.read_byte(self.data.memptr);
+            self.contend_read_no_mreq(self.data.memptr, 1);
             self.data.$r = self.rl(self.data.$r);
-            self.memory.write_byte(self.data.temp_addr, self.data.$r);
+            self.write_byte(self.data.memptr, self.data.$r);
         }
     };
 }
@@ -353,10 +373,10 @@ macro_rules! fn_instr_ddcb_ld_r_rl_i_reg_p_dd {
 macro_rules! fn_instr_ddcb_ld_r_rr_i_reg_p_dd {
     ($fn:tt, $r:ident) => {
         pub(crate) fn $fn(&mut self) {
-            self.data.$r = self.memory.read_byte(self.data.temp_addr);
-            self.memory.contend_read_no_mreq(self.data.temp_addr, 1);
+            self.data.$r = self.read_byte(self.data.memptr);
+            self.contend_read_no_mreq(self.data.memptr, 1);
             self.data.$r = self.rr(self.data.$r);
-            self.memory.write_byte(self.data.temp_addr, self.data.$r);
+            self.write_byte(self.data.memptr, self.data.$r);
         }
     };
 }
@@ -364,10 +384,10 @@ macro_rules! fn_instr_ddcb_ld_r_rr_i_reg_p_dd {
 macro_rules! fn_instr_ddcb_ld_r_sla_i_reg_p_dd {
     ($fn:tt, $r:ident) => {
         pub(crate) fn $fn(&mut self) {
-            self.data.$r = self.memory.read_byte(self.data.temp_addr);
-            self.memory.contend_read_no_mreq(self.data.temp_addr, 1);
+            self.data.$r = self.read_byte(self.data.memptr);
+            self.contend_read_no_mreq(self.data.memptr, 1);
             self.data.$r = self.sla(self.data.$r);
-            self.memory.write_byte(self.data.temp_addr, self.data.$r);
+            self.write_byte(self.data.memptr, self.data.$r);
         }
     };
 }
@@ -375,10 +395,10 @@ macro_rules! fn_instr_ddcb_ld_r_sla_i_reg_p_dd {
 macro_rules! fn_instr_ddcb_ld_r_sra_i_reg_p_dd {
     ($fn:tt, $r:ident) => {
         pub(crate) fn $fn(&mut self) {
-            self.data.$r = self.memory.read_byte(self.data.temp_addr);
-            self.memory.contend_read_no_mreq(self.data.temp_addr, 1);
+            self.data.$r = self.read_byte(self.data.memptr);
+            self.contend_read_no_mreq(self.data.memptr, 1);
             self.data.$r = self.sra(self.data.$r);
-            self.memory.write_byte(self.data.temp_addr, self.data.$r);
+            self.write_byte(self.data.memptr, self.data.$r);
         }
     };
 }
@@ -386,10 +406,10 @@ macro_rules! fn_instr_ddcb_ld_r_sra_i_reg_p_dd {
 macro_rules! fn_instr_ddcb_ld_r_sll_i_reg_p_dd {
     ($fn:tt, $r:ident) => {
         pub(crate) fn $fn(&mut self) {
-            self.data.$r = self.memory.read_byte(self.data.temp_addr);
-            self.memory.contend_read_no_mreq(self.data.temp_addr, 1);
+            self.data.$r = self.read_byte(self.data.memptr);
+            self.contend_read_no_mreq(self.data.memptr, 1);
             self.data.$r = self.sll(self.data.$r);
-            self.memory.write_byte(self.data.temp_addr, self.data.$r);
+            self.write_byte(self.data.memptr, self.data.$r);
         }
     };
 }
@@ -397,10 +417,10 @@ macro_rules! fn_instr_ddcb_ld_r_sll_i_reg_p_dd {
 macro_rules! fn_instr_ddcb_ld_r_srl_i_reg_p_dd {
     ($fn:tt, $r:ident) => {
         pub(crate) fn $fn(&mut self) {
-            self.data.$r = self.memory.read_byte(self.data.temp_addr);
-            self.memory.contend_read_no_mreq(self.data.temp_addr, 1);
+            self.data.$r = self.read_byte(self.data.memptr);
+            self.contend_read_no_mreq(self.data.memptr, 1);
             self.data.$r = self.srl(self.data.$r);
-            self.memory.write_byte(self.data.temp_addr, self.data.$r);
+            self.write_byte(self.data.memptr, self.data.$r);
         }
     };
 }
@@ -408,9 +428,9 @@ macro_rules! fn_instr_ddcb_ld_r_srl_i_reg_p_dd {
 macro_rules! fn_instr_ddcb_bit_n_i_reg_p_dd {
     ($fn:tt, $r:expr) => {
         pub(crate) fn $fn(&mut self) {
-            let byte_temp = self.memory.read_byte(self.data.temp_addr);
-            self.memory.contend_read_no_mreq(self.data.temp_addr, 1);
-            self.biti($r, byte_temp, self.data.temp_addr);
+            let byte_temp = self.read_byte(self.data.memptr);
+            self.contend_read_no_mreq(self.data.memptr, 1);
+            self.biti($r, byte_temp, self.data.memptr);
         }
     };
 }
@@ -418,9 +438,9 @@ macro_rules! fn_instr_ddcb_bit_n_i_reg_p_dd {
 macro_rules! fn_instr_ddcb_ld_r_res_n_i_reg_p_dd {
     ($fn:tt, $r:ident, $mask:expr) => {
         pub(crate) fn $fn(&mut self) {
-            self.data.$r = self.memory.read_byte(self.data.temp_addr) & $mask;
-            self.memory.contend_read_no_mreq(self.data.temp_addr, 1);
-            self.memory.write_byte(self.data.temp_addr, self.data.$r);
+            self.data.$r = self.read_byte(self.data.memptr) & $mask;
+            self.contend_read_no_mreq(self.data.memptr, 1);
+            self.write_byte(self.data.memptr, self.data.$r);
         }
     };
 }
@@ -428,10 +448,9 @@ macro_rules! fn_instr_ddcb_ld_r_res_n_i_reg_p_dd {
 macro_rules! fn_instr_ddcb_res_n_i_reg_p_dd {
     ($fn:tt, $mask:expr) => {
         pub(crate) fn $fn(&mut self) {
-            let byte_temp: u8 = self.memory.read_byte(self.data.temp_addr);
-            self.memory.contend_read_no_mreq(self.data.temp_addr, 1);
-            self.memory
-                .write_byte(self.data.temp_addr, byte_temp & $mask);
+            let byte_temp: u8 = self.read_byte(self.data.memptr);
+            self.contend_read_no_mreq(self.data.memptr, 1);
+            self.write_byte(self.data.memptr, byte_temp & $mask);
         }
     };
 }
@@ -439,10 +458,9 @@ macro_rules! fn_instr_ddcb_res_n_i_reg_p_dd {
 macro_rules! fn_instr_ddcb_set_n_i_reg_p_dd {
     ($fn:tt, $mask:expr) => {
         pub(crate) fn $fn(&mut self) {
-            let byte_temp: u8 = self.memory.read_byte(self.data.temp_addr);
-            self.memory.contend_read_no_mreq(self.data.temp_addr, 1);
-            self.memory
-                .write_byte(self.data.temp_addr, byte_temp | $mask);
+            let byte_temp: u8 = self.read_byte(self.data.memptr);
+            self.contend_read_no_mreq(self.data.memptr, 1);
+            self.write_byte(self.data.memptr, byte_temp | $mask);
         }
     };
 }
@@ -498,9 +516,9 @@ macro_rules! fn_instr_ddcb_ld_r_res_7_i_reg_p_dd {
 macro_rules! fn_instr_ddcb_ld_r_set_n_i_reg_p_dd {
     ($fn:tt, $r:ident, $mask:expr) => {
         pub(crate) fn $fn(&mut self) {
-            self.data.$r = self.memory.read_byte(self.data.temp_addr) | $mask;
-            self.memory.contend_read_no_mreq(self.data.temp_addr, 1);
-            self.memory.write_byte(self.data.temp_addr, self.data.$r);
+            self.data.$r = self.read_byte(self.data.memptr) | $mask;
+            self.contend_read_no_mreq(self.data.memptr, 1);
+            self.write_byte(self.data.memptr, self.data.$r);
         }
     };
 }
@@ -555,7 +573,7 @@ macro_rules! fn_instr_ddcb_ld_r_set_7_i_reg_p_dd {
 
 // */
 #[allow(non_snake_case)]
-impl Z80 {
+impl<B: Bus> Z80<B> {
     pub fn execute_opcode(&mut self, opcode: u16) -> bool {
         match opcode {
             0 => {
@@ -6753,10 +6771,7 @@ impl Z80 {
     fn_instr_ld_r16_nnnn!(instr__LD_BC_NNNN, BC, SetBC);
 
     /* LD (BC),A */
-    pub(crate) fn instr__LD_iBC_A(&mut self) {
-        let address = self.BC();
-        self.memory.write_byte(address, self.data.A);
-    }
+    fn_instr_ld_i_r16_a!(instr__LD_iBC_A, BC);
 
     /* INC BC */
     fn_instr_op_16!(instr__INC_BC, IncBC);
@@ -6791,7 +6806,7 @@ impl Z80 {
     fn_instr_add_hl_r16!(instr__ADD_HL_BC, BC);
 
     /* LD A,(BC) */
-    fn_instr_ld_a_r16!(instr__LD_A_iBC, BC);
+    fn_instr_ld_a_i_r16!(instr__LD_A_iBC, BC);
 
     /* DEC BC */
     fn_instr_op_16!(instr__DEC_BC, DecBC);
@@ -6815,15 +6830,13 @@ impl Z80 {
     /* DJNZ offset */
     pub(crate) fn instr__DJNZ_OFFSET(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq(_address, 1);
+        self.contend_read_no_mreq(_address, 1);
         self.data.B = self.data.B.wrapping_sub(1);
         if self.data.B != 0 {
             self.jr();
-            self.data.cycles += 14;
         } else {
             let _address = self.PC();
-            self.memory.contend_read(_address, 3);
-            self.data.cycles += 9;
+            self.contend_read(_address, 3);
         }
         self.IncPC(1);
     }
@@ -6832,7 +6845,7 @@ impl Z80 {
     fn_instr_ld_r16_nnnn!(instr__LD_DE_NNNN, DE, SetDE);
 
     /* LD (DE),A */
-    fn_instr_ld_i_r16_r8!(instr__LD_iDE_A, DE, A);
+    fn_instr_ld_i_r16_a!(instr__LD_iDE_A, DE);
 
     /* INC DE */
     fn_instr_op_16!(instr__INC_DE, IncDE);
@@ -6865,7 +6878,7 @@ impl Z80 {
     fn_instr_add_hl_r16!(instr__ADD_HL_DE, DE);
 
     /* LD A,(DE) */
-    fn_instr_ld_a_r16!(instr__LD_A_iDE, DE);
+    fn_instr_ld_a_i_r16!(instr__LD_A_iDE, DE);
 
     /* DEC DE */
     fn_instr_op_16!(instr__DEC_DE, DecDE);
@@ -6894,7 +6907,7 @@ impl Z80 {
             self.jr();
         } else {
             let _address = self.PC();
-            self.memory.contend_read(_address, 3);
+            self.contend_read(_address, 3);
         }
         self.IncPC(1);
     }
@@ -6947,7 +6960,7 @@ impl Z80 {
             self.jr();
         } else {
             let _address = self.PC();
-            self.memory.contend_read(_address, 3);
+            self.contend_read(_address, 3);
         }
         self.IncPC(1);
     }
@@ -6984,7 +6997,7 @@ impl Z80 {
             self.jr();
         } else {
             let _address = self.PC();
-            self.memory.contend_read(_address, 3);
+            self.contend_read(_address, 3);
         }
         self.IncPC(1);
     }
@@ -6995,12 +7008,13 @@ impl Z80 {
     /* LD (nnnn),A */
     pub(crate) fn instr__LD_iNNNN_A(&mut self) {
         let address = self.PC();
-        let mut word_temp: u16 = (self.memory.read_byte(address)) as u16;
+        let mut word_temp: u16 = (self.read_byte(address)) as u16;
         self.IncPC(1);
         let address = self.PC();
-        word_temp |= (self.memory.read_byte(address) as u16) << 8;
+        word_temp |= (self.read_byte(address) as u16) << 8;
         self.IncPC(1);
-        self.memory.write_byte(word_temp, self.data.A);
+        self.write_byte(word_temp, self.data.A);
+        self.data.memptr = join_bytes(self.data.A, word_temp.wrapping_add(1) as u8);
     }
 
     /* INC SP */
@@ -7008,35 +7022,34 @@ impl Z80 {
 
     /* INC (HL) */
     pub(crate) fn instr__INC_iHL(&mut self) {
-        let mut byte_temp: u8 = self.memory.read_byte(self.HL());
-        self.memory.contend_read_no_mreq(self.HL(), 1);
+        let mut byte_temp: u8 = self.read_byte(self.HL());
+        self.contend_read_no_mreq(self.HL(), 1);
         self.inc(&mut byte_temp);
-        self.memory.write_byte(self.HL(), byte_temp);
+        self.write_byte(self.HL(), byte_temp);
     }
 
     /* DEC (HL) */
     pub(crate) fn instr__DEC_iHL(&mut self) {
         let address = self.HL();
-        let mut byte_temp: u8 = self.memory.read_byte(address);
+        let mut byte_temp: u8 = self.read_byte(address);
         let _address = self.HL();
-        self.memory.contend_read_no_mreq(_address, 1);
+        self.contend_read_no_mreq(_address, 1);
         self.dec(&mut byte_temp);
-        self.memory.write_byte(self.HL(), byte_temp);
+        self.write_byte(self.HL(), byte_temp);
     }
 
     /* LD (HL),nn */
     pub(crate) fn instr__LD_iHL_NN(&mut self) {
         let address = self.PC();
-        let value = self.memory.read_byte(address);
+        let value = self.read_byte(address);
         let address = self.HL();
-        self.memory.write_byte(address, value);
+        self.write_byte(address, value);
         self.IncPC(1);
     }
 
     /* SCF */
     pub(crate) fn instr__SCF(&mut self) {
-        self.data.F =
-            (self.data.F & (FLAG_P | FLAG_Z | FLAG_S)) | (self.data.A & (FLAG_3 | FLAG_5)) | FLAG_C;
+        self.data.F = (self.data.F & (FLAG_P | FLAG_Z | FLAG_S)) | self.scf_ccf_xy() | FLAG_C;
     }
 
     /* JR C,offset */
@@ -7045,7 +7058,7 @@ impl Z80 {
             self.jr();
         } else {
             let _address = self.PC();
-            self.memory.contend_read(_address, 3);
+            self.contend_read(_address, 3);
         }
         self.IncPC(1);
     }
@@ -7056,12 +7069,13 @@ impl Z80 {
     /* LD A,(nnnn) */
     pub(crate) fn instr__LD_A_iNNNN(&mut self) {
         let address = self.PC();
-        let mut word_temp: u16 = self.memory.read_byte(address) as u16;
+        let mut word_temp: u16 = self.read_byte(address) as u16;
         self.IncPC(1);
         let address = self.PC();
-        word_temp |= (self.memory.read_byte(address) as u16) << 8;
+        word_temp |= (self.read_byte(address) as u16) << 8;
         self.IncPC(1);
-        self.data.A = self.memory.read_byte(word_temp);
+        self.data.A = self.read_byte(word_temp);
+        self.data.memptr = word_temp.wrapping_add(1);
     }
 
     /* DEC SP */
@@ -7080,7 +7094,7 @@ impl Z80 {
     pub(crate) fn instr__CCF(&mut self) {
         self.data.F = self.data.F & (FLAG_P | FLAG_Z | FLAG_S)
             | tern_op_b(self.data.F & FLAG_C != 0, FLAG_H, FLAG_C)
-            | self.data.A & (FLAG_3 | FLAG_5);
+            | self.scf_ccf_xy();
     }
 
     /* LD B,B */
@@ -7113,7 +7127,7 @@ impl Z80 {
 
     /* LD B,(HL) */
     pub(crate) fn instr__LD_B_iHL(&mut self) {
-        self.data.B = self.memory.read_byte(self.HL());
+        self.data.B = self.read_byte(self.HL());
     }
 
     /* LD B,A */
@@ -7151,7 +7165,7 @@ impl Z80 {
 
     /* LD C,(HL) */
     pub(crate) fn instr__LD_C_iHL(&mut self) {
-        self.data.C = self.memory.read_byte(self.HL());
+        self.data.C = self.read_byte(self.HL());
     }
 
     /* LD C,A */
@@ -7189,7 +7203,7 @@ impl Z80 {
 
     /* LD D,(HL) */
     pub(crate) fn instr__LD_D_iHL(&mut self) {
-        self.data.D = self.memory.read_byte(self.HL());
+        self.data.D = self.read_byte(self.HL());
     }
 
     /* LD D,A */
@@ -7227,7 +7241,7 @@ impl Z80 {
 
     /* LD E,(HL) */
     pub(crate) fn instr__LD_E_iHL(&mut self) {
-        self.data.E = self.memory.read_byte(self.HL());
+        self.data.E = self.read_byte(self.HL());
     }
 
     /* LD E,A */
@@ -7265,7 +7279,7 @@ impl Z80 {
 
     /* LD H,(HL) */
     pub(crate) fn instr__LD_H_iHL(&mut self) {
-        self.data.H = self.memory.read_byte(self.HL());
+        self.data.H = self.read_byte(self.HL());
     }
 
     /* LD H,A */
@@ -7303,7 +7317,7 @@ impl Z80 {
 
     /* LD L,(HL) */
     pub(crate) fn instr__LD_L_iHL(&mut self) {
-        self.data.L = self.memory.read_byte(self.HL());
+        self.data.L = self.read_byte(self.HL());
     }
 
     /* LD L,A */
@@ -7382,7 +7396,7 @@ impl Z80 {
 
     /* ADD A,(HL) */
     pub(crate) fn instr__ADD_A_iHL(&mut self) {
-        let byte_temp: u8 = self.memory.read_byte(self.HL());
+        let byte_temp: u8 = self.read_byte(self.HL());
 
         self.add(byte_temp);
     }
@@ -7410,7 +7424,7 @@ impl Z80 {
 
     /* ADC A,(HL) */
     pub(crate) fn instr__ADC_A_iHL(&mut self) {
-        let byte_temp: u8 = self.memory.read_byte(self.HL());
+        let byte_temp: u8 = self.read_byte(self.HL());
 
         self.adc(byte_temp);
     }
@@ -7438,7 +7452,7 @@ impl Z80 {
 
     /* SUB A,(HL) */
     pub(crate) fn instr__SUB_A_iHL(&mut self) {
-        let byte_temp: u8 = self.memory.read_byte(self.HL());
+        let byte_temp: u8 = self.read_byte(self.HL());
 
         self.sub(byte_temp);
     }
@@ -7466,7 +7480,7 @@ impl Z80 {
 
     /* SBC A,(HL) */
     pub(crate) fn instr__SBC_A_iHL(&mut self) {
-        let byte_temp: u8 = self.memory.read_byte(self.HL());
+        let byte_temp: u8 = self.read_byte(self.HL());
 
         self.sbc(byte_temp);
     }
@@ -7494,7 +7508,7 @@ impl Z80 {
 
     /* AND A,(HL) */
     pub(crate) fn instr__AND_A_iHL(&mut self) {
-        let byte_temp: u8 = self.memory.read_byte(self.HL());
+        let byte_temp: u8 = self.read_byte(self.HL());
 
         self.and(byte_temp);
     }
@@ -7522,7 +7536,7 @@ impl Z80 {
 
     /* XOR A,(HL) */
     pub(crate) fn instr__XOR_A_iHL(&mut self) {
-        let byte_temp: u8 = self.memory.read_byte(self.HL());
+        let byte_temp: u8 = self.read_byte(self.HL());
 
         self.xor(byte_temp);
     }
@@ -7550,7 +7564,7 @@ impl Z80 {
 
     /* OR A,(HL) */
     pub(crate) fn instr__OR_A_iHL(&mut self) {
-        let byte_temp: u8 = self.memory.read_byte(self.HL());
+        let byte_temp: u8 = self.read_byte(self.HL());
 
         self.or(byte_temp);
     }
@@ -7579,7 +7593,7 @@ impl Z80 {
     /* CP (HL) */
     pub(crate) fn instr__CP_iHL(&mut self) {
         let address = self.HL();
-        let byte_temp: u8 = self.memory.read_byte(address);
+        let byte_temp: u8 = self.read_byte(address);
 
         self.cp(byte_temp);
     }
@@ -7590,7 +7604,7 @@ impl Z80 {
     /* RET NZ */
     pub(crate) fn instr__RET_NZ(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq(_address, 1);
+        self.contend_read_no_mreq(_address, 1);
         if (self.data.F & FLAG_Z) == 0 {
             self.ret();
         }
@@ -7604,11 +7618,7 @@ impl Z80 {
         if (self.data.F & FLAG_Z) == 0 {
             self.jp();
         } else {
-            let _address = self.PC();
-            self.memory.contend_read(_address, 3);
-            let pc = self.PC();
-            self.memory.contend_read(pc + 1, 3);
-            self.IncPC(2);
+            self.skip_address();
         }
     }
 
@@ -7622,11 +7632,7 @@ impl Z80 {
         if (self.data.F & FLAG_Z) == 0 {
             self.call();
         } else {
-            let _address = self.PC();
-            self.memory.contend_read(_address, 3);
-            let pc = self.PC();
-            self.memory.contend_read(pc + 1, 3);
-            self.IncPC(2);
+            self.skip_address();
         }
     }
 
@@ -7636,7 +7642,7 @@ impl Z80 {
     /* ADD A,nn */
     pub(crate) fn instr__ADD_A_NN(&mut self) {
         let address = self.PC();
-        let byte_temp: u8 = self.memory.read_byte(address);
+        let byte_temp: u8 = self.read_byte(address);
         self.IncPC(1);
         self.add(byte_temp);
     }
@@ -7644,14 +7650,14 @@ impl Z80 {
     /* RST 00 */
     pub(crate) fn instr__RST_00(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq(_address, 1);
+        self.contend_read_no_mreq(_address, 1);
         self.rst(0x00);
     }
 
     /* RET Z */
     pub(crate) fn instr__RET_Z(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq(_address, 1);
+        self.contend_read_no_mreq(_address, 1);
         if (self.data.F & FLAG_Z) != 0 {
             self.ret();
         }
@@ -7667,11 +7673,7 @@ impl Z80 {
         if (self.data.F & FLAG_Z) != 0 {
             self.jp();
         } else {
-            let _address = self.PC();
-            self.memory.contend_read(_address, 3);
-            let pc = self.PC();
-            self.memory.contend_read(pc + 1, 3);
-            self.IncPC(2);
+            self.skip_address();
         }
     }
 
@@ -7683,11 +7685,7 @@ impl Z80 {
         if (self.data.F & FLAG_Z) != 0 {
             self.call();
         } else {
-            let _address = self.PC();
-            self.memory.contend_read(_address, 3);
-            let pc = self.PC();
-            self.memory.contend_read(pc + 1, 3);
-            self.IncPC(2);
+            self.skip_address();
         }
     }
 
@@ -7699,7 +7697,7 @@ impl Z80 {
     /* ADC A,nn */
     pub(crate) fn instr__ADC_A_NN(&mut self) {
         let address = self.PC();
-        let byte_temp: u8 = self.memory.read_byte(address);
+        let byte_temp: u8 = self.read_byte(address);
         self.IncPC(1);
         self.adc(byte_temp);
     }
@@ -7707,14 +7705,14 @@ impl Z80 {
     /* RST 8 */
     pub(crate) fn instr__RST_8(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq(_address, 1);
+        self.contend_read_no_mreq(_address, 1);
         self.rst(0x8);
     }
 
     /* RET NC */
     pub(crate) fn instr__RET_NC(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq(_address, 1);
+        self.contend_read_no_mreq(_address, 1);
         if self.data.F & FLAG_C == 0 {
             self.ret();
         }
@@ -7728,20 +7726,17 @@ impl Z80 {
         if self.data.F & FLAG_C == 0 {
             self.jp();
         } else {
-            let _address = self.PC();
-            self.memory.contend_read(_address, 3);
-            let pc = self.PC();
-            self.memory.contend_read(pc + 1, 3);
-            self.IncPC(2);
+            self.skip_address();
         }
     }
 
     /* OUT (nn),A */
     pub(crate) fn instr__OUT_iNN_A(&mut self) {
         let address = self.PC();
-        let out_temp: u16 = (self.memory.read_byte(address) as u16) + ((self.data.A as u16) << 8);
+        let out_temp: u16 = (self.read_byte(address) as u16) + ((self.data.A as u16) << 8);
         self.IncPC(1);
         self.write_port(out_temp, self.data.A);
+        self.data.memptr = join_bytes(self.data.A, out_temp.wrapping_add(1) as u8);
     }
 
     /* CALL NC,nnnn */
@@ -7749,11 +7744,7 @@ impl Z80 {
         if self.data.F & FLAG_C == 0 {
             self.call();
         } else {
-            let _address = self.PC();
-            self.memory.contend_read(_address, 3);
-            let pc = self.PC();
-            self.memory.contend_read(pc + 1, 3);
-            self.IncPC(2);
+            self.skip_address();
         }
     }
 
@@ -7763,7 +7754,7 @@ impl Z80 {
     /* SUB nn */
     pub(crate) fn instr__SUB_NN(&mut self) {
         let address = self.PC();
-        let byte_temp: u8 = self.memory.read_byte(address);
+        let byte_temp: u8 = self.read_byte(address);
         self.IncPC(1);
         self.sub(byte_temp);
     }
@@ -7771,14 +7762,14 @@ impl Z80 {
     /* RST 10 */
     pub(crate) fn instr__RST_10(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq(_address, 1);
+        self.contend_read_no_mreq(_address, 1);
         self.rst(0x10);
     }
 
     /* RET C */
     pub(crate) fn instr__RET_C(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq(_address, 1);
+        self.contend_read_no_mreq(_address, 1);
         if self.data.F & FLAG_C != 0 {
             self.ret();
         }
@@ -7804,20 +7795,17 @@ impl Z80 {
         if self.data.F & FLAG_C != 0 {
             self.jp();
         } else {
-            let _address = self.PC();
-            self.memory.contend_read(_address, 3);
-            let pc = self.PC();
-            self.memory.contend_read(pc + 1, 3);
-            self.IncPC(2);
+            self.skip_address();
         }
     }
 
     /* IN A,(nn) */
     pub(crate) fn instr__IN_A_iNN(&mut self) {
         let address = self.PC();
-        let in_temp: u16 = (self.memory.read_byte(address) as u16) + ((self.data.A as u16) << 8);
+        let in_temp: u16 = (self.read_byte(address) as u16) + ((self.data.A as u16) << 8);
         self.IncPC(1);
         self.data.A = self.read_port(in_temp);
+        self.data.memptr = in_temp.wrapping_add(1);
     }
 
     /* CALL C,nnnn */
@@ -7825,11 +7813,7 @@ impl Z80 {
         if self.data.F & FLAG_C != 0 {
             self.call();
         } else {
-            let _address = self.PC();
-            self.memory.contend_read(_address, 3);
-            let pc = self.PC();
-            self.memory.contend_read(pc + 1, 3);
-            self.IncPC(2);
+            self.skip_address();
         }
     }
 
@@ -7839,7 +7823,7 @@ impl Z80 {
     /* SBC A,nn */
     pub(crate) fn instr__SBC_A_NN(&mut self) {
         let address = self.PC();
-        let byte_temp: u8 = self.memory.read_byte(address);
+        let byte_temp: u8 = self.read_byte(address);
         self.IncPC(1);
         self.sbc(byte_temp);
     }
@@ -7847,14 +7831,14 @@ impl Z80 {
     /* RST 18 */
     pub(crate) fn instr__RST_18(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq(_address, 1);
+        self.contend_read_no_mreq(_address, 1);
         self.rst(0x18);
     }
 
     /* RET PO */
     pub(crate) fn instr__RET_PO(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq(_address, 1);
+        self.contend_read_no_mreq(_address, 1);
         if (self.data.F & FLAG_P) == 0 {
             self.ret();
         }
@@ -7868,30 +7852,27 @@ impl Z80 {
         if (self.data.F & FLAG_P) == 0 {
             self.jp();
         } else {
-            let _address = self.PC();
-            self.memory.contend_read(_address, 3);
-            let pc = self.PC();
-            self.memory.contend_read(pc + 1, 3);
-            self.IncPC(2);
+            self.skip_address();
         }
     }
 
     /* EX (SP),HL */
     pub(crate) fn instr__EX_iSP_HL(&mut self) {
         let address = self.SP();
-        let byte_temp_l = self.memory.read_byte(address);
+        let byte_temp_l = self.read_byte(address);
         let sp = self.SP();
-        let byte_temp_h = self.memory.read_byte(sp + 1);
+        let byte_temp_h = self.read_byte(sp + 1);
         let sp = self.SP();
-        self.memory.contend_read_no_mreq(sp + 1, 1);
+        self.contend_read_no_mreq(sp + 1, 1);
         let sp = self.SP();
-        self.memory.write_byte(sp + 1, self.data.H);
+        self.write_byte(sp + 1, self.data.H);
         let address = self.SP();
-        self.memory.write_byte(address, self.data.L);
+        self.write_byte(address, self.data.L);
         let _address = self.SP();
-        self.memory.contend_write_no_mreq_loop(_address, 1, 2);
+        self.contend_write_no_mreq_loop(_address, 1, 2);
         self.data.L = byte_temp_l;
         self.data.H = byte_temp_h;
+        self.data.memptr = join_bytes(byte_temp_h, byte_temp_l);
     }
 
     /* CALL PO,nnnn */
@@ -7899,11 +7880,7 @@ impl Z80 {
         if (self.data.F & FLAG_P) == 0 {
             self.call();
         } else {
-            let _address = self.PC();
-            self.memory.contend_read(_address, 3);
-            let pc = self.PC();
-            self.memory.contend_read(pc + 1, 3);
-            self.IncPC(2);
+            self.skip_address();
         }
     }
 
@@ -7913,7 +7890,7 @@ impl Z80 {
     /* AND nn */
     pub(crate) fn instr__AND_NN(&mut self) {
         let address = self.PC();
-        let byte_temp: u8 = self.memory.read_byte(address);
+        let byte_temp: u8 = self.read_byte(address);
         self.IncPC(1);
         self.and(byte_temp);
     }
@@ -7921,14 +7898,14 @@ impl Z80 {
     /* RST 20 */
     pub(crate) fn instr__RST_20(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq(_address, 1);
+        self.contend_read_no_mreq(_address, 1);
         self.rst(0x20);
     }
 
     /* RET PE */
     pub(crate) fn instr__RET_PE(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq(_address, 1);
+        self.contend_read_no_mreq(_address, 1);
         if (self.data.F & FLAG_P) != 0 {
             self.ret();
         }
@@ -7946,11 +7923,7 @@ impl Z80 {
         if (self.data.F & FLAG_P) != 0 {
             self.jp();
         } else {
-            let _address = self.PC();
-            self.memory.contend_read(_address, 3);
-            let pc = self.PC();
-            self.memory.contend_read(pc + 1, 3);
-            self.IncPC(2);
+            self.skip_address();
         }
     }
 
@@ -7966,11 +7939,7 @@ impl Z80 {
         if (self.data.F & FLAG_P) != 0 {
             self.call();
         } else {
-            let _address = self.PC();
-            self.memory.contend_read(_address, 3);
-            let pc = self.PC();
-            self.memory.contend_read(pc + 1, 3);
-            self.IncPC(2);
+            self.skip_address();
         }
     }
 
@@ -7980,7 +7949,7 @@ impl Z80 {
     /* XOR A,nn */
     pub(crate) fn instr__XOR_A_NN(&mut self) {
         let address = self.PC();
-        let byte_temp: u8 = self.memory.read_byte(address);
+        let byte_temp: u8 = self.read_byte(address);
         self.IncPC(1);
         self.xor(byte_temp);
     }
@@ -7988,14 +7957,14 @@ impl Z80 {
     /* RST 28 */
     pub(crate) fn instr__RST_28(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq(_address, 1);
+        self.contend_read_no_mreq(_address, 1);
         self.rst(0x28);
     }
 
     /* RET P */
     pub(crate) fn instr__RET_P(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq(_address, 1);
+        self.contend_read_no_mreq(_address, 1);
         if (self.data.F & FLAG_S) == 0 {
             self.ret();
         }
@@ -8009,11 +7978,7 @@ impl Z80 {
         if (self.data.F & FLAG_S) == 0 {
             self.jp();
         } else {
-            let _address = self.PC();
-            self.memory.contend_read(_address, 3);
-            let pc = self.PC();
-            self.memory.contend_read(pc + 1, 3);
-            self.IncPC(2);
+            self.skip_address();
         }
     }
 
@@ -8027,11 +7992,7 @@ impl Z80 {
         if (self.data.F & FLAG_S) == 0 {
             self.call();
         } else {
-            let _address = self.PC();
-            self.memory.contend_read(_address, 3);
-            let pc = self.PC();
-            self.memory.contend_read(pc + 1, 3);
-            self.IncPC(2);
+            self.skip_address();
         }
     }
 
@@ -8041,7 +8002,7 @@ impl Z80 {
     /* OR nn */
     pub(crate) fn instr__OR_NN(&mut self) {
         let address = self.PC();
-        let byte_temp: u8 = self.memory.read_byte(address);
+        let byte_temp: u8 = self.read_byte(address);
         self.IncPC(1);
         self.or(byte_temp);
     }
@@ -8049,14 +8010,14 @@ impl Z80 {
     /* RST 30 */
     pub(crate) fn instr__RST_30(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq(_address, 1);
+        self.contend_read_no_mreq(_address, 1);
         self.rst(0x30);
     }
 
     /* RET M */
     pub(crate) fn instr__RET_M(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq(_address, 1);
+        self.contend_read_no_mreq(_address, 1);
         if (self.data.F & FLAG_S) != 0 {
             self.ret();
         }
@@ -8065,7 +8026,7 @@ impl Z80 {
     /* LD SP,HL */
     pub(crate) fn instr__LD_SP_HL(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq_loop(_address, 1, 2);
+        self.contend_read_no_mreq_loop(_address, 1, 2);
         self.SetSP(self.HL());
     }
 
@@ -8074,11 +8035,7 @@ impl Z80 {
         if (self.data.F & FLAG_S) != 0 {
             self.jp();
         } else {
-            let _address = self.PC();
-            self.memory.contend_read(_address, 3);
-            let pc = self.PC();
-            self.memory.contend_read(pc + 1, 3);
-            self.IncPC(2);
+            self.skip_address();
         }
     }
 
@@ -8087,8 +8044,7 @@ impl Z80 {
         /* Interrupts are not accepted immediately after an EI, but are
         accepted after the next instruction */
         (self.data.IFF1, self.data.IFF2) = (1, 1);
-        self.data.interrupts_enabled_at = self.data.t_states;
-        // eventAdd(self.Tstates + 1, z80InterruptEvent)
+        self.data.ei_delay = true;
     }
 
     /* CALL M,nnnn */
@@ -8096,11 +8052,7 @@ impl Z80 {
         if (self.data.F & FLAG_S) != 0 {
             self.call()
         } else {
-            let _address = self.PC();
-            self.memory.contend_read(_address, 3);
-            let pc = self.PC();
-            self.memory.contend_read(pc + 1, 3);
-            self.IncPC(2);
+            self.skip_address();
         }
     }
 
@@ -8110,7 +8062,7 @@ impl Z80 {
     /* CP nn */
     pub(crate) fn instr__CP_NN(&mut self) {
         let address = self.PC();
-        let byte_temp: u8 = self.memory.read_byte(address);
+        let byte_temp: u8 = self.read_byte(address);
         self.IncPC(1);
         self.cp(byte_temp);
     }
@@ -8118,7 +8070,7 @@ impl Z80 {
     /* RST 38 */
     pub(crate) fn instr__RST_38(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq(_address, 1);
+        self.contend_read_no_mreq(_address, 1);
         self.rst(0x38);
     }
 
@@ -8154,10 +8106,10 @@ impl Z80 {
 
     /* RLC (HL) */
     pub(crate) fn instrCB__RLC_iHL(&mut self) {
-        let mut byte_temp: u8 = self.memory.read_byte(self.HL());
-        self.memory.contend_read_no_mreq(self.HL(), 1);
+        let mut byte_temp: u8 = self.read_byte(self.HL());
+        self.contend_read_no_mreq(self.HL(), 1);
         byte_temp = self.rlc(byte_temp);
-        self.memory.write_byte(self.HL(), byte_temp);
+        self.write_byte(self.HL(), byte_temp);
     }
 
     /* RLC A */
@@ -8197,10 +8149,10 @@ impl Z80 {
 
     /* RRC (HL) */
     pub(crate) fn instrCB__RRC_iHL(&mut self) {
-        let mut byte_temp: u8 = self.memory.read_byte(self.HL());
-        self.memory.contend_read_no_mreq(self.HL(), 1);
+        let mut byte_temp: u8 = self.read_byte(self.HL());
+        self.contend_read_no_mreq(self.HL(), 1);
         byte_temp = self.rrc(byte_temp);
-        self.memory.write_byte(self.HL(), byte_temp);
+        self.write_byte(self.HL(), byte_temp);
     }
 
     /* RRC A */
@@ -8240,10 +8192,10 @@ impl Z80 {
 
     /* RL (HL) */
     pub(crate) fn instrCB__RL_iHL(&mut self) {
-        let mut byte_temp: u8 = self.memory.read_byte(self.HL());
-        self.memory.contend_read_no_mreq(self.HL(), 1);
+        let mut byte_temp: u8 = self.read_byte(self.HL());
+        self.contend_read_no_mreq(self.HL(), 1);
         byte_temp = self.rl(byte_temp);
-        self.memory.write_byte(self.HL(), byte_temp);
+        self.write_byte(self.HL(), byte_temp);
     }
 
     /* RL A */
@@ -8283,10 +8235,10 @@ impl Z80 {
 
     /* RR (HL) */
     pub(crate) fn instrCB__RR_iHL(&mut self) {
-        let mut byte_temp: u8 = self.memory.read_byte(self.HL());
-        self.memory.contend_read_no_mreq(self.HL(), 1);
+        let mut byte_temp: u8 = self.read_byte(self.HL());
+        self.contend_read_no_mreq(self.HL(), 1);
         byte_temp = self.rr(byte_temp);
-        self.memory.write_byte(self.HL(), byte_temp);
+        self.write_byte(self.HL(), byte_temp);
     }
 
     /* RR A */
@@ -8326,10 +8278,10 @@ impl Z80 {
 
     /* SLA (HL) */
     pub(crate) fn instrCB__SLA_iHL(&mut self) {
-        let mut byte_temp: u8 = self.memory.read_byte(self.HL());
-        self.memory.contend_read_no_mreq(self.HL(), 1);
+        let mut byte_temp: u8 = self.read_byte(self.HL());
+        self.contend_read_no_mreq(self.HL(), 1);
         byte_temp = self.sla(byte_temp);
-        self.memory.write_byte(self.HL(), byte_temp);
+        self.write_byte(self.HL(), byte_temp);
     }
 
     /* SLA A */
@@ -8369,10 +8321,10 @@ impl Z80 {
 
     /* SRA (HL) */
     pub(crate) fn instrCB__SRA_iHL(&mut self) {
-        let mut byte_temp: u8 = self.memory.read_byte(self.HL());
-        self.memory.contend_read_no_mreq(self.HL(), 1);
+        let mut byte_temp: u8 = self.read_byte(self.HL());
+        self.contend_read_no_mreq(self.HL(), 1);
         byte_temp = self.sra(byte_temp);
-        self.memory.write_byte(self.HL(), byte_temp);
+        self.write_byte(self.HL(), byte_temp);
     }
 
     /* SRA A */
@@ -8412,10 +8364,10 @@ impl Z80 {
 
     /* SLL (HL) */
     pub(crate) fn instrCB__SLL_iHL(&mut self) {
-        let mut byte_temp: u8 = self.memory.read_byte(self.HL());
-        self.memory.contend_read_no_mreq(self.HL(), 1);
+        let mut byte_temp: u8 = self.read_byte(self.HL());
+        self.contend_read_no_mreq(self.HL(), 1);
         byte_temp = self.sll(byte_temp);
-        self.memory.write_byte(self.HL(), byte_temp);
+        self.write_byte(self.HL(), byte_temp);
     }
 
     /* SLL A */
@@ -8455,10 +8407,10 @@ impl Z80 {
 
     /* SRL (HL) */
     pub(crate) fn instrCB__SRL_iHL(&mut self) {
-        let mut byte_temp: u8 = self.memory.read_byte(self.HL());
-        self.memory.contend_read_no_mreq(self.HL(), 1);
+        let mut byte_temp: u8 = self.read_byte(self.HL());
+        self.contend_read_no_mreq(self.HL(), 1);
         byte_temp = self.srl(byte_temp);
-        self.memory.write_byte(self.HL(), byte_temp);
+        self.write_byte(self.HL(), byte_temp);
     }
 
     /* SRL A */
@@ -8498,9 +8450,9 @@ impl Z80 {
 
     /* BIT 0,(HL) */
     pub(crate) fn instrCB__BIT_0_iHL(&mut self) {
-        let byte_temp = self.memory.read_byte(self.HL());
-        self.memory.contend_read_no_mreq(self.HL(), 1);
-        self.bit(0, byte_temp)
+        let byte_temp = self.read_byte(self.HL());
+        self.contend_read_no_mreq(self.HL(), 1);
+        self.biti(0, byte_temp, self.data.memptr)
     }
 
     /* BIT 0,A */
@@ -8540,9 +8492,9 @@ impl Z80 {
 
     /* BIT 1,(HL) */
     pub(crate) fn instrCB__BIT_1_iHL(&mut self) {
-        let byte_temp = self.memory.read_byte(self.HL());
-        self.memory.contend_read_no_mreq(self.HL(), 1);
-        self.bit(1, byte_temp);
+        let byte_temp = self.read_byte(self.HL());
+        self.contend_read_no_mreq(self.HL(), 1);
+        self.biti(1, byte_temp, self.data.memptr);
     }
 
     /* BIT 1,A */
@@ -8582,9 +8534,9 @@ impl Z80 {
 
     /* BIT 2,(HL) */
     pub(crate) fn instrCB__BIT_2_iHL(&mut self) {
-        let byte_temp = self.memory.read_byte(self.HL());
-        self.memory.contend_read_no_mreq(self.HL(), 1);
-        self.bit(2, byte_temp);
+        let byte_temp = self.read_byte(self.HL());
+        self.contend_read_no_mreq(self.HL(), 1);
+        self.biti(2, byte_temp, self.data.memptr);
     }
 
     /* BIT 2,A */
@@ -8624,9 +8576,9 @@ impl Z80 {
 
     /* BIT 3,(HL) */
     pub(crate) fn instrCB__BIT_3_iHL(&mut self) {
-        let byte_temp = self.memory.read_byte(self.HL());
-        self.memory.contend_read_no_mreq(self.HL(), 1);
-        self.bit(3, byte_temp);
+        let byte_temp = self.read_byte(self.HL());
+        self.contend_read_no_mreq(self.HL(), 1);
+        self.biti(3, byte_temp, self.data.memptr);
     }
 
     /* BIT 3,A */
@@ -8666,9 +8618,9 @@ impl Z80 {
 
     /* BIT 4,(HL) */
     pub(crate) fn instrCB__BIT_4_iHL(&mut self) {
-        let byte_temp = self.memory.read_byte(self.HL());
-        self.memory.contend_read_no_mreq(self.HL(), 1);
-        self.bit(4, byte_temp);
+        let byte_temp = self.read_byte(self.HL());
+        self.contend_read_no_mreq(self.HL(), 1);
+        self.biti(4, byte_temp, self.data.memptr);
     }
 
     /* BIT 4,A */
@@ -8708,9 +8660,9 @@ impl Z80 {
 
     /* BIT 5,(HL) */
     pub(crate) fn instrCB__BIT_5_iHL(&mut self) {
-        let byte_temp = self.memory.read_byte(self.HL());
-        self.memory.contend_read_no_mreq(self.HL(), 1);
-        self.bit(5, byte_temp);
+        let byte_temp = self.read_byte(self.HL());
+        self.contend_read_no_mreq(self.HL(), 1);
+        self.biti(5, byte_temp, self.data.memptr);
     }
 
     /* BIT 5,A */
@@ -8750,9 +8702,9 @@ impl Z80 {
 
     /* BIT 6,(HL) */
     pub(crate) fn instrCB__BIT_6_iHL(&mut self) {
-        let byte_temp = self.memory.read_byte(self.HL());
-        self.memory.contend_read_no_mreq(self.HL(), 1);
-        self.bit(6, byte_temp);
+        let byte_temp = self.read_byte(self.HL());
+        self.contend_read_no_mreq(self.HL(), 1);
+        self.biti(6, byte_temp, self.data.memptr);
     }
 
     /* BIT 6,A */
@@ -8792,9 +8744,9 @@ impl Z80 {
 
     /* BIT 7,(HL) */
     pub(crate) fn instrCB__BIT_7_iHL(&mut self) {
-        let byte_temp = self.memory.read_byte(self.HL());
-        self.memory.contend_read_no_mreq(self.HL(), 1);
-        self.bit(7, byte_temp);
+        let byte_temp = self.read_byte(self.HL());
+        self.contend_read_no_mreq(self.HL(), 1);
+        self.biti(7, byte_temp, self.data.memptr);
     }
 
     /* BIT 7,A */
@@ -8834,9 +8786,9 @@ impl Z80 {
 
     /* RES 0,(HL) */
     pub(crate) fn instrCB__RES_0_iHL(&mut self) {
-        let byte_temp: u8 = self.memory.read_byte(self.HL());
-        self.memory.contend_read_no_mreq(self.HL(), 1);
-        self.memory.write_byte(self.HL(), byte_temp & 0xfe);
+        let byte_temp: u8 = self.read_byte(self.HL());
+        self.contend_read_no_mreq(self.HL(), 1);
+        self.write_byte(self.HL(), byte_temp & 0xfe);
     }
 
     /* RES 0,A */
@@ -8876,9 +8828,9 @@ impl Z80 {
 
     /* RES 1,(HL) */
     pub(crate) fn instrCB__RES_1_iHL(&mut self) {
-        let byte_temp: u8 = self.memory.read_byte(self.HL());
-        self.memory.contend_read_no_mreq(self.HL(), 1);
-        self.memory.write_byte(self.HL(), byte_temp & 0xfd);
+        let byte_temp: u8 = self.read_byte(self.HL());
+        self.contend_read_no_mreq(self.HL(), 1);
+        self.write_byte(self.HL(), byte_temp & 0xfd);
     }
 
     /* RES 1,A */
@@ -8918,9 +8870,9 @@ impl Z80 {
 
     /* RES 2,(HL) */
     pub(crate) fn instrCB__RES_2_iHL(&mut self) {
-        let byte_temp: u8 = self.memory.read_byte(self.HL());
-        self.memory.contend_read_no_mreq(self.HL(), 1);
-        self.memory.write_byte(self.HL(), byte_temp & 0xfb)
+        let byte_temp: u8 = self.read_byte(self.HL());
+        self.contend_read_no_mreq(self.HL(), 1);
+        self.write_byte(self.HL(), byte_temp & 0xfb)
     }
 
     /* RES 2,A */
@@ -8960,9 +8912,9 @@ impl Z80 {
 
     /* RES 3,(HL) */
     pub(crate) fn instrCB__RES_3_iHL(&mut self) {
-        let byte_temp: u8 = self.memory.read_byte(self.HL());
-        self.memory.contend_read_no_mreq(self.HL(), 1);
-        self.memory.write_byte(self.HL(), byte_temp & 0xf7);
+        let byte_temp: u8 = self.read_byte(self.HL());
+        self.contend_read_no_mreq(self.HL(), 1);
+        self.write_byte(self.HL(), byte_temp & 0xf7);
     }
 
     /* RES 3,A */
@@ -9002,9 +8954,9 @@ impl Z80 {
 
     /* RES 4,(HL) */
     pub(crate) fn instrCB__RES_4_iHL(&mut self) {
-        let byte_temp: u8 = self.memory.read_byte(self.HL());
-        self.memory.contend_read_no_mreq(self.HL(), 1);
-        self.memory.write_byte(self.HL(), byte_temp & 0xef);
+        let byte_temp: u8 = self.read_byte(self.HL());
+        self.contend_read_no_mreq(self.HL(), 1);
+        self.write_byte(self.HL(), byte_temp & 0xef);
     }
 
     /* RES 4,A */
@@ -9044,9 +8996,9 @@ impl Z80 {
 
     /* RES 5,(HL) */
     pub(crate) fn instrCB__RES_5_iHL(&mut self) {
-        let byte_temp: u8 = self.memory.read_byte(self.HL());
-        self.memory.contend_read_no_mreq(self.HL(), 1);
-        self.memory.write_byte(self.HL(), byte_temp & 0xdf);
+        let byte_temp: u8 = self.read_byte(self.HL());
+        self.contend_read_no_mreq(self.HL(), 1);
+        self.write_byte(self.HL(), byte_temp & 0xdf);
     }
 
     /* RES 5,A */
@@ -9086,9 +9038,9 @@ impl Z80 {
 
     /* RES 6,(HL) */
     pub(crate) fn instrCB__RES_6_iHL(&mut self) {
-        let byte_temp: u8 = self.memory.read_byte(self.HL());
-        self.memory.contend_read_no_mreq(self.HL(), 1);
-        self.memory.write_byte(self.HL(), byte_temp & 0xbf);
+        let byte_temp: u8 = self.read_byte(self.HL());
+        self.contend_read_no_mreq(self.HL(), 1);
+        self.write_byte(self.HL(), byte_temp & 0xbf);
     }
 
     /* RES 6,A */
@@ -9128,9 +9080,9 @@ impl Z80 {
 
     /* RES 7,(HL) */
     pub(crate) fn instrCB__RES_7_iHL(&mut self) {
-        let byte_temp: u8 = self.memory.read_byte(self.HL());
-        self.memory.contend_read_no_mreq(self.HL(), 1);
-        self.memory.write_byte(self.HL(), byte_temp & 0x7f);
+        let byte_temp: u8 = self.read_byte(self.HL());
+        self.contend_read_no_mreq(self.HL(), 1);
+        self.write_byte(self.HL(), byte_temp & 0x7f);
     }
 
     /* RES 7,A */
@@ -9170,9 +9122,9 @@ impl Z80 {
 
     /* SET 0,(HL) */
     pub(crate) fn instrCB__SET_0_iHL(&mut self) {
-        let byte_temp: u8 = self.memory.read_byte(self.HL());
-        self.memory.contend_read_no_mreq(self.HL(), 1);
-        self.memory.write_byte(self.HL(), byte_temp | 0x01);
+        let byte_temp: u8 = self.read_byte(self.HL());
+        self.contend_read_no_mreq(self.HL(), 1);
+        self.write_byte(self.HL(), byte_temp | 0x01);
     }
 
     /* SET 0,A */
@@ -9212,9 +9164,9 @@ impl Z80 {
 
     /* SET 1,(HL) */
     pub(crate) fn instrCB__SET_1_iHL(&mut self) {
-        let byte_temp: u8 = self.memory.read_byte(self.HL());
-        self.memory.contend_read_no_mreq(self.HL(), 1);
-        self.memory.write_byte(self.HL(), byte_temp | 0x02)
+        let byte_temp: u8 = self.read_byte(self.HL());
+        self.contend_read_no_mreq(self.HL(), 1);
+        self.write_byte(self.HL(), byte_temp | 0x02)
     }
 
     /* SET 1,A */
@@ -9254,9 +9206,9 @@ impl Z80 {
 
     /* SET 2,(HL) */
     pub(crate) fn instrCB__SET_2_iHL(&mut self) {
-        let byte_temp: u8 = self.memory.read_byte(self.HL());
-        self.memory.contend_read_no_mreq(self.HL(), 1);
-        self.memory.write_byte(self.HL(), byte_temp | 0x04)
+        let byte_temp: u8 = self.read_byte(self.HL());
+        self.contend_read_no_mreq(self.HL(), 1);
+        self.write_byte(self.HL(), byte_temp | 0x04)
     }
 
     /* SET 2,A */
@@ -9296,9 +9248,9 @@ impl Z80 {
 
     /* SET 3,(HL) */
     pub(crate) fn instrCB__SET_3_iHL(&mut self) {
-        let byte_temp: u8 = self.memory.read_byte(self.HL());
-        self.memory.contend_read_no_mreq(self.HL(), 1);
-        self.memory.write_byte(self.HL(), byte_temp | 0x08)
+        let byte_temp: u8 = self.read_byte(self.HL());
+        self.contend_read_no_mreq(self.HL(), 1);
+        self.write_byte(self.HL(), byte_temp | 0x08)
     }
 
     /* SET 3,A */
@@ -9338,9 +9290,9 @@ impl Z80 {
 
     /* SET 4,(HL) */
     pub(crate) fn instrCB__SET_4_iHL(&mut self) {
-        let byte_temp: u8 = self.memory.read_byte(self.HL());
-        self.memory.contend_read_no_mreq(self.HL(), 1);
-        self.memory.write_byte(self.HL(), byte_temp | 0x10)
+        let byte_temp: u8 = self.read_byte(self.HL());
+        self.contend_read_no_mreq(self.HL(), 1);
+        self.write_byte(self.HL(), byte_temp | 0x10)
     }
 
     /* SET 4,A */
@@ -9380,9 +9332,9 @@ impl Z80 {
 
     /* SET 5,(HL) */
     pub(crate) fn instrCB__SET_5_iHL(&mut self) {
-        let byte_temp: u8 = self.memory.read_byte(self.HL());
-        self.memory.contend_read_no_mreq(self.HL(), 1);
-        self.memory.write_byte(self.HL(), byte_temp | 0x20)
+        let byte_temp: u8 = self.read_byte(self.HL());
+        self.contend_read_no_mreq(self.HL(), 1);
+        self.write_byte(self.HL(), byte_temp | 0x20)
     }
 
     /* SET 5,A */
@@ -9422,9 +9374,9 @@ impl Z80 {
 
     /* SET 6,(HL) */
     pub(crate) fn instrCB__SET_6_iHL(&mut self) {
-        let byte_temp: u8 = self.memory.read_byte(self.HL());
-        self.memory.contend_read_no_mreq(self.HL(), 1);
-        self.memory.write_byte(self.HL(), byte_temp | 0x40)
+        let byte_temp: u8 = self.read_byte(self.HL());
+        self.contend_read_no_mreq(self.HL(), 1);
+        self.write_byte(self.HL(), byte_temp | 0x40)
     }
 
     /* SET 6,A */
@@ -9464,9 +9416,9 @@ impl Z80 {
 
     /* SET 7,(HL) */
     pub(crate) fn instrCB__SET_7_iHL(&mut self) {
-        let byte_temp: u8 = self.memory.read_byte(self.HL());
-        self.memory.contend_read_no_mreq(self.HL(), 1);
-        self.memory.write_byte(self.HL(), byte_temp | 0x80)
+        let byte_temp: u8 = self.read_byte(self.HL());
+        self.contend_read_no_mreq(self.HL(), 1);
+        self.write_byte(self.HL(), byte_temp | 0x80)
     }
 
     /* SET 7,A */
@@ -9484,12 +9436,13 @@ impl Z80 {
     /* OUT (C),B */
     pub(crate) fn instrED__OUT_iC_B(&mut self) {
         self.write_port(self.BC(), self.data.B);
+        self.data.memptr = self.BC().wrapping_add(1);
     }
 
     /* SBC HL,BC */
     pub(crate) fn instrED__SBC_HL_BC(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq_loop(_address, 1, 7);
+        self.contend_read_no_mreq_loop(_address, 1, 7);
         self.sbc16(self.BC());
     }
 
@@ -9517,7 +9470,7 @@ impl Z80 {
     /* LD I,A */
     pub(crate) fn instrED__LD_I_A(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq(_address, 1);
+        self.contend_read_no_mreq(_address, 1);
         self.data.I = self.data.A;
     }
 
@@ -9529,12 +9482,13 @@ impl Z80 {
     /* OUT (C),C */
     pub(crate) fn instrED__OUT_iC_C(&mut self) {
         self.write_port(self.BC(), self.data.C);
+        self.data.memptr = self.BC().wrapping_add(1);
     }
 
     /* ADC HL,BC */
     pub(crate) fn instrED__ADC_HL_BC(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq_loop(_address, 1, 7);
+        self.contend_read_no_mreq_loop(_address, 1, 7);
         self.adc16(self.BC());
     }
 
@@ -9544,7 +9498,7 @@ impl Z80 {
     /* LD R,A */
     pub(crate) fn instrED__LD_R_A(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq(_address, 1);
+        self.contend_read_no_mreq(_address, 1);
         /* Keep the RZX instruction counter right */
         self.data.rzx_instructions_offset += (self.data.R as isize) - (self.data.A as isize);
         (self.data.R, self.data.R7) = ((self.data.A as u16), self.data.A);
@@ -9558,12 +9512,13 @@ impl Z80 {
     /* OUT (C),D */
     pub(crate) fn instrED__OUT_iC_D(&mut self) {
         self.write_port(self.BC(), self.data.D);
+        self.data.memptr = self.BC().wrapping_add(1);
     }
 
     /* SBC HL,DE */
     pub(crate) fn instrED__SBC_HL_DE(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq_loop(_address, 1, 7);
+        self.contend_read_no_mreq_loop(_address, 1, 7);
         self.sbc16(self.DE());
     }
 
@@ -9578,7 +9533,7 @@ impl Z80 {
     /* LD A,I */
     pub(crate) fn instrED__LD_A_I(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq(_address, 1);
+        self.contend_read_no_mreq(_address, 1);
         self.data.A = self.data.I;
         self.data.F = self.data.F & FLAG_C
             | self.tables.sz53_table[self.data.A as usize]
@@ -9594,12 +9549,13 @@ impl Z80 {
     /* OUT (C),E */
     pub(crate) fn instrED__OUT_iC_E(&mut self) {
         self.write_port(self.BC(), self.data.E);
+        self.data.memptr = self.BC().wrapping_add(1);
     }
 
     /* ADC HL,DE */
     pub(crate) fn instrED__ADC_HL_DE(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq_loop(_address, 1, 7);
+        self.contend_read_no_mreq_loop(_address, 1, 7);
         self.adc16(self.DE());
     }
 
@@ -9614,7 +9570,7 @@ impl Z80 {
     /* LD A,R */
     pub(crate) fn instrED__LD_A_R(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq(_address, 1);
+        self.contend_read_no_mreq(_address, 1);
         self.data.A = (self.data.R & 0x7f) as u8 | (self.data.R7 & 0x80);
         self.data.F = self.data.F & FLAG_C
             | self.tables.sz53_table[self.data.A as usize]
@@ -9629,12 +9585,13 @@ impl Z80 {
     /* OUT (C),H */
     pub(crate) fn instrED__OUT_iC_H(&mut self) {
         self.write_port(self.BC(), self.data.H);
+        self.data.memptr = self.BC().wrapping_add(1);
     }
 
     /* SBC HL,HL */
     pub(crate) fn instrED__SBC_HL_HL(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq_loop(_address, 1, 7);
+        self.contend_read_no_mreq_loop(_address, 1, 7);
         self.sbc16(self.HL())
     }
 
@@ -9643,12 +9600,12 @@ impl Z80 {
 
     /* RRD */
     pub(crate) fn instrED__RRD(&mut self) {
-        let byte_temp: u8 = self.memory.read_byte(self.HL());
-        self.memory.contend_read_no_mreq_loop(self.HL(), 1, 4);
-        self.memory
-            .write_byte(self.HL(), (self.data.A << 4) | (byte_temp >> 4));
+        let byte_temp: u8 = self.read_byte(self.HL());
+        self.contend_read_no_mreq_loop(self.HL(), 1, 4);
+        self.write_byte(self.HL(), (self.data.A << 4) | (byte_temp >> 4));
         self.data.A = self.data.A & 0xf0 | byte_temp & 0x0f;
         self.data.F = self.data.F & FLAG_C | self.tables.sz53p_table[self.data.A as usize];
+        self.data.memptr = self.HL().wrapping_add(1);
     }
 
     /* IN L,(C) */
@@ -9659,12 +9616,13 @@ impl Z80 {
     /* OUT (C),L */
     pub(crate) fn instrED__OUT_iC_L(&mut self) {
         self.write_port(self.BC(), self.data.L);
+        self.data.memptr = self.BC().wrapping_add(1);
     }
 
     /* ADC HL,HL */
     pub(crate) fn instrED__ADC_HL_HL(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq_loop(_address, 1, 7);
+        self.contend_read_no_mreq_loop(_address, 1, 7);
         self.adc16(self.HL());
     }
 
@@ -9673,12 +9631,12 @@ impl Z80 {
 
     /* RLD */
     pub(crate) fn instrED__RLD(&mut self) {
-        let byte_temp: u8 = self.memory.read_byte(self.HL());
-        self.memory.contend_read_no_mreq_loop(self.HL(), 1, 4);
-        self.memory
-            .write_byte(self.HL(), (byte_temp << 4) | (self.data.A & 0x0f));
+        let byte_temp: u8 = self.read_byte(self.HL());
+        self.contend_read_no_mreq_loop(self.HL(), 1, 4);
+        self.write_byte(self.HL(), (byte_temp << 4) | (self.data.A & 0x0f));
         self.data.A = (self.data.A & 0xf0) | (byte_temp >> 4);
         self.data.F = self.data.F & FLAG_C | self.tables.sz53p_table[self.data.A as usize];
+        self.data.memptr = self.HL().wrapping_add(1);
     }
 
     /* IN F,(C) */
@@ -9689,12 +9647,13 @@ impl Z80 {
     /* OUT (C),0 */
     pub(crate) fn instrED__OUT_iC_0(&mut self) {
         self.write_port(self.BC(), 0);
+        self.data.memptr = self.BC().wrapping_add(1);
     }
 
     /* SBC HL,SP */
     pub(crate) fn instrED__SBC_HL_SP(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq_loop(_address, 1, 7);
+        self.contend_read_no_mreq_loop(_address, 1, 7);
         let value = self.SP();
         self.sbc16(value);
     }
@@ -9714,12 +9673,13 @@ impl Z80 {
     /* OUT (C),A */
     pub(crate) fn instrED__OUT_iC_A(&mut self) {
         self.write_port(self.BC(), self.data.A);
+        self.data.memptr = self.BC().wrapping_add(1);
     }
 
     /* ADC HL,SP */
     pub(crate) fn instrED__ADC_HL_SP(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq_loop(_address, 1, 7);
+        self.contend_read_no_mreq_loop(_address, 1, 7);
         let value = self.SP();
         self.adc16(value);
     }
@@ -9734,13 +9694,13 @@ impl Z80 {
 
     /* LDI */
     pub(crate) fn instrED__LDI(&mut self) {
-        let mut byte_temp: u8 = self.memory.read_byte(self.HL());
+        let mut byte_temp: u8 = self.read_byte(self.HL());
         self.DecBC();
-        self.memory.write_byte(self.DE(), byte_temp);
-        self.memory.contend_write_no_mreq_loop(self.DE(), 1, 2);
+        self.write_byte(self.DE(), byte_temp);
+        self.contend_write_no_mreq_loop(self.DE(), 1, 2);
         self.IncDE();
         self.IncHL();
-        byte_temp += self.data.A;
+        byte_temp = byte_temp.wrapping_add(self.data.A);
         self.data.F = self.data.F & (FLAG_C | FLAG_Z | FLAG_S)
             | tern_op_b(self.BC() != 0, FLAG_V, 0)
             | byte_temp & FLAG_3
@@ -9749,12 +9709,13 @@ impl Z80 {
 
     /* CPI */
     pub(crate) fn instrED__CPI(&mut self) {
-        let value: u8 = self.memory.read_byte(self.HL());
-        let mut byte_temp: u8 = self.data.A - value;
+        let value: u8 = self.read_byte(self.HL());
+        let mut byte_temp: u8 = self.data.A.wrapping_sub(value);
         let lookup: u8 =
             ((self.data.A & 0x08) >> 3) | ((value & 0x08) >> 2) | ((byte_temp & 0x08) >> 1);
-        self.memory.contend_read_no_mreq_loop(self.HL(), 1, 5);
+        self.contend_read_no_mreq_loop(self.HL(), 1, 5);
         self.IncHL();
+        self.data.memptr = self.data.memptr.wrapping_add(1);
         self.DecBC();
         self.data.F = self.data.F & FLAG_C
             | tern_op_b(self.BC() != 0, FLAG_V | FLAG_N, FLAG_N)
@@ -9762,7 +9723,7 @@ impl Z80 {
             | tern_op_b(byte_temp != 0, 0, FLAG_Z)
             | byte_temp & FLAG_S;
         if (self.data.F & FLAG_H) != 0 {
-            byte_temp -= 1;
+            byte_temp = byte_temp.wrapping_sub(1);
         }
         self.data.F |= (byte_temp & FLAG_3) | tern_op_b((byte_temp & 0x02) != 0, FLAG_5, 0);
     }
@@ -9770,9 +9731,10 @@ impl Z80 {
     /* INI */
     pub(crate) fn instrED__INI(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq(_address, 1);
+        self.contend_read_no_mreq(_address, 1);
         let in_i_temp: u8 = self.read_port(self.BC());
-        self.memory.write_byte(self.HL(), in_i_temp);
+        self.write_byte(self.HL(), in_i_temp);
+        self.data.memptr = self.BC().wrapping_add(1);
 
         self.data.B = self.data.B.wrapping_sub(1);
         self.IncHL();
@@ -9790,10 +9752,11 @@ impl Z80 {
     /* OUTI */
     pub(crate) fn instrED__OUTI(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq(_address, 1);
-        let out_i_temp: u8 = self.memory.read_byte(self.HL());
+        self.contend_read_no_mreq(_address, 1);
+        let out_i_temp: u8 = self.read_byte(self.HL());
         self.data.B = self.data.B.wrapping_sub(1); /* This does happen first, despite what the specs say */
         self.write_port(self.BC(), out_i_temp);
+        self.data.memptr = self.BC().wrapping_add(1);
 
         self.IncHL();
         let out_i_temp2: u8 = out_i_temp.wrapping_add(self.data.L); // + self.data.L;
@@ -9809,13 +9772,13 @@ impl Z80 {
 
     /* LDD */
     pub(crate) fn instrED__LDD(&mut self) {
-        let mut byte_temp: u8 = self.memory.read_byte(self.HL());
+        let mut byte_temp: u8 = self.read_byte(self.HL());
         self.DecBC();
-        self.memory.write_byte(self.DE(), byte_temp);
-        self.memory.contend_write_no_mreq_loop(self.DE(), 1, 2);
+        self.write_byte(self.DE(), byte_temp);
+        self.contend_write_no_mreq_loop(self.DE(), 1, 2);
         self.DecDE();
         self.DecHL();
-        byte_temp += self.data.A;
+        byte_temp = byte_temp.wrapping_add(self.data.A);
         self.data.F = self.data.F & (FLAG_C | FLAG_Z | FLAG_S)
             | tern_op_b(self.BC() != 0, FLAG_V, 0)
             | byte_temp & FLAG_3
@@ -9824,12 +9787,13 @@ impl Z80 {
 
     /* CPD */
     pub(crate) fn instrED__CPD(&mut self) {
-        let value: u8 = self.memory.read_byte(self.HL());
-        let mut byte_temp: u8 = self.data.A - value;
+        let value: u8 = self.read_byte(self.HL());
+        let mut byte_temp: u8 = self.data.A.wrapping_sub(value);
         let lookup: u8 =
             ((self.data.A & 0x08) >> 3) | ((value & 0x08) >> 2) | ((byte_temp & 0x08) >> 1);
-        self.memory.contend_read_no_mreq_loop(self.HL(), 1, 5);
+        self.contend_read_no_mreq_loop(self.HL(), 1, 5);
         self.DecHL();
+        self.data.memptr = self.data.memptr.wrapping_sub(1);
         self.DecBC();
         self.data.F = self.data.F & FLAG_C
             | tern_op_b(self.BC() != 0, FLAG_V | FLAG_N, FLAG_N)
@@ -9837,7 +9801,7 @@ impl Z80 {
             | tern_op_b(byte_temp != 0, 0, FLAG_Z)
             | byte_temp & FLAG_S;
         if (self.data.F & FLAG_H) != 0 {
-            byte_temp -= 1;
+            byte_temp = byte_temp.wrapping_sub(1);
         }
         self.data.F |= (byte_temp & FLAG_3) | tern_op_b((byte_temp & 0x02) != 0, FLAG_5, 0)
     }
@@ -9845,13 +9809,14 @@ impl Z80 {
     /* IND */
     pub(crate) fn instrED__IND(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq(_address, 1);
+        self.contend_read_no_mreq(_address, 1);
         let in_i_temp: u8 = self.read_port(self.BC());
-        self.memory.write_byte(self.HL(), in_i_temp);
+        self.write_byte(self.HL(), in_i_temp);
+        self.data.memptr = self.BC().wrapping_sub(1);
 
         self.data.B = self.data.B.wrapping_sub(1);
         self.DecHL();
-        let in_i_temp2: u8 = in_i_temp + self.data.C - 1;
+        let in_i_temp2: u8 = in_i_temp.wrapping_add(self.data.C).wrapping_sub(1);
         self.data.F = tern_op_b((in_i_temp & 0x80) != 0, FLAG_N, 0)
             | tern_op_b(in_i_temp2 < in_i_temp, FLAG_H | FLAG_C, 0)
             | tern_op_b(
@@ -9865,13 +9830,14 @@ impl Z80 {
     /* OUTD */
     pub(crate) fn instrED__OUTD(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq(_address, 1);
-        let out_i_temp: u8 = self.memory.read_byte(self.HL());
+        self.contend_read_no_mreq(_address, 1);
+        let out_i_temp: u8 = self.read_byte(self.HL());
         self.data.B = self.data.B.wrapping_sub(1); /* This does happen first, despite what the specs say */
         self.write_port(self.BC(), out_i_temp);
+        self.data.memptr = self.BC().wrapping_sub(1);
 
         self.DecHL();
-        let out_i_temp2: u8 = out_i_temp + self.data.L;
+        let out_i_temp2: u8 = out_i_temp.wrapping_add(self.data.L);
         self.data.F = tern_op_b((out_i_temp & 0x80) != 0, FLAG_N, 0)
             | tern_op_b(out_i_temp2 < out_i_temp, FLAG_H | FLAG_C, 0)
             | tern_op_b(
@@ -9884,9 +9850,9 @@ impl Z80 {
 
     /* LDIR */
     pub(crate) fn instrED__LDIR(&mut self) {
-        let mut byte_temp: u8 = self.memory.read_byte(self.HL());
-        self.memory.write_byte(self.DE(), byte_temp);
-        self.memory.contend_write_no_mreq_loop(self.DE(), 1, 2);
+        let mut byte_temp: u8 = self.read_byte(self.HL());
+        self.write_byte(self.DE(), byte_temp);
+        self.contend_write_no_mreq_loop(self.DE(), 1, 2);
         self.DecBC();
         byte_temp = byte_temp.wrapping_add(self.data.A);
         self.data.F = self.data.F & (FLAG_C | FLAG_Z | FLAG_S)
@@ -9894,11 +9860,9 @@ impl Z80 {
             | byte_temp & FLAG_3
             | tern_op_b(byte_temp & 0x02 != 0, FLAG_5, 0);
         if self.BC() != 0 {
-            self.memory.contend_write_no_mreq_loop(self.DE(), 1, 5);
+            self.contend_write_no_mreq_loop(self.DE(), 1, 5);
             self.DecPC(2);
-            self.data.cycles += 23;
-        } else {
-            self.data.cycles += 18;
+            self.data.memptr = self.PC().wrapping_add(1);
         }
         self.IncHL();
         self.IncDE();
@@ -9906,11 +9870,11 @@ impl Z80 {
 
     /* CPIR */
     pub(crate) fn instrED__CPIR(&mut self) {
-        let value: u8 = self.memory.read_byte(self.HL());
-        let mut byte_temp: u8 = self.data.A - value;
+        let value: u8 = self.read_byte(self.HL());
+        let mut byte_temp: u8 = self.data.A.wrapping_sub(value);
         let lookup: u8 =
             ((self.data.A & 0x08) >> 3) | ((value & 0x08) >> 2) | ((byte_temp & 0x08) >> 1);
-        self.memory.contend_read_no_mreq_loop(self.HL(), 1, 5);
+        self.contend_read_no_mreq_loop(self.HL(), 1, 5);
         self.DecBC();
         self.data.F = self.data.F & FLAG_C
             | tern_op_b(self.BC() != 0, FLAG_V | FLAG_N, FLAG_N)
@@ -9918,15 +9882,15 @@ impl Z80 {
             | tern_op_b(byte_temp != 0, 0, FLAG_Z)
             | byte_temp & FLAG_S;
         if self.data.F & FLAG_H != 0 {
-            byte_temp -= 1;
+            byte_temp = byte_temp.wrapping_sub(1);
         }
         self.data.F |= (byte_temp & FLAG_3) | tern_op_b((byte_temp & 0x02) != 0, FLAG_5, 0);
         if (self.data.F & (FLAG_V | FLAG_Z)) == FLAG_V {
-            self.memory.contend_read_no_mreq_loop(self.HL(), 1, 5);
+            self.contend_read_no_mreq_loop(self.HL(), 1, 5);
             self.DecPC(2);
-            self.data.cycles += 18;
+            self.data.memptr = self.PC().wrapping_add(1);
         } else {
-            self.data.cycles += 23;
+            self.data.memptr = self.data.memptr.wrapping_add(1);
         }
         self.IncHL();
     }
@@ -9934,12 +9898,13 @@ impl Z80 {
     /* INIR */
     pub(crate) fn instrED__INIR(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq(_address, 1);
+        self.contend_read_no_mreq(_address, 1);
         let in_i_temp: u8 = self.read_port(self.BC());
-        self.memory.write_byte(self.HL(), in_i_temp);
+        self.write_byte(self.HL(), in_i_temp);
+        self.data.memptr = self.BC().wrapping_add(1);
 
         self.data.B = self.data.B.wrapping_sub(1);
-        let in_i_temp2: u8 = in_i_temp + self.data.C + 1;
+        let in_i_temp2: u8 = in_i_temp.wrapping_add(self.data.C).wrapping_add(1);
         self.data.F = tern_op_b(in_i_temp & 0x80 != 0, FLAG_N, 0)
             | tern_op_b(in_i_temp2 < in_i_temp, FLAG_H | FLAG_C, 0)
             | tern_op_b(
@@ -9950,11 +9915,8 @@ impl Z80 {
             | self.tables.sz53_table[self.data.B as usize];
 
         if self.data.B != 0 {
-            self.memory.contend_write_no_mreq_loop(self.HL(), 1, 5);
+            self.contend_write_no_mreq_loop(self.HL(), 1, 5);
             self.DecPC(2);
-            self.data.cycles += 23;
-        } else {
-            self.data.cycles += 18;
         }
         self.IncHL();
     }
@@ -9962,10 +9924,11 @@ impl Z80 {
     /* OTIR */
     pub(crate) fn instrED__OTIR(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq(_address, 1);
-        let out_i_temp: u8 = self.memory.read_byte(self.HL());
+        self.contend_read_no_mreq(_address, 1);
+        let out_i_temp: u8 = self.read_byte(self.HL());
         self.data.B = self.data.B.wrapping_sub(1); /* This does happen first, despite what the specs say */
         self.write_port(self.BC(), out_i_temp);
+        self.data.memptr = self.BC().wrapping_add(1);
 
         self.IncHL();
         let out_i_temp2: u8 = out_i_temp.wrapping_add(self.data.L);
@@ -9980,19 +9943,16 @@ impl Z80 {
             | self.tables.sz53_table[self.data.B as usize];
 
         if self.data.B != 0 {
-            self.memory.contend_read_no_mreq_loop(self.BC(), 1, 5);
+            self.contend_read_no_mreq_loop(self.BC(), 1, 5);
             self.DecPC(2);
-            self.data.cycles += 23;
-        } else {
-            self.data.cycles += 18;
         }
     }
 
     /* LDDR */
     pub(crate) fn instrED__LDDR(&mut self) {
-        let mut byte_temp: u8 = self.memory.read_byte(self.HL());
-        self.memory.write_byte(self.DE(), byte_temp);
-        self.memory.contend_write_no_mreq_loop(self.DE(), 1, 2);
+        let mut byte_temp: u8 = self.read_byte(self.HL());
+        self.write_byte(self.DE(), byte_temp);
+        self.contend_write_no_mreq_loop(self.DE(), 1, 2);
         self.DecBC();
         byte_temp = byte_temp.wrapping_add(self.data.A);
         self.data.F = self.data.F & (FLAG_C | FLAG_Z | FLAG_S)
@@ -10000,11 +9960,9 @@ impl Z80 {
             | byte_temp & FLAG_3
             | tern_op_b(byte_temp & 0x02 != 0, FLAG_5, 0);
         if self.BC() != 0 {
-            self.memory.contend_write_no_mreq_loop(self.DE(), 1, 5);
+            self.contend_write_no_mreq_loop(self.DE(), 1, 5);
             self.DecPC(2);
-            self.data.cycles += 23;
-        } else {
-            self.data.cycles += 18;
+            self.data.memptr = self.PC().wrapping_add(1);
         }
         self.DecHL();
         self.DecDE();
@@ -10012,11 +9970,11 @@ impl Z80 {
 
     /* CPDR */
     pub(crate) fn instrED__CPDR(&mut self) {
-        let value: u8 = self.memory.read_byte(self.HL());
-        let mut byte_temp: u8 = self.data.A - value;
+        let value: u8 = self.read_byte(self.HL());
+        let mut byte_temp: u8 = self.data.A.wrapping_sub(value);
         let lookup: u8 =
             ((self.data.A & 0x08) >> 3) | ((value & 0x08) >> 2) | ((byte_temp & 0x08) >> 1);
-        self.memory.contend_read_no_mreq_loop(self.HL(), 1, 5);
+        self.contend_read_no_mreq_loop(self.HL(), 1, 5);
         self.DecBC();
         self.data.F = self.data.F & FLAG_C
             | tern_op_b(self.BC() != 0, FLAG_V | FLAG_N, FLAG_N)
@@ -10024,15 +9982,15 @@ impl Z80 {
             | tern_op_b(byte_temp != 0, 0, FLAG_Z)
             | byte_temp & FLAG_S;
         if self.data.F & FLAG_H != 0 {
-            byte_temp -= 1;
+            byte_temp = byte_temp.wrapping_sub(1);
         }
         self.data.F |= byte_temp & FLAG_3 | tern_op_b((byte_temp & 0x02) != 0, FLAG_5, 0);
         if self.data.F & (FLAG_V | FLAG_Z) == FLAG_V {
-            self.memory.contend_read_no_mreq_loop(self.HL(), 1, 5);
+            self.contend_read_no_mreq_loop(self.HL(), 1, 5);
             self.DecPC(2);
-            self.data.cycles += 18;
+            self.data.memptr = self.PC().wrapping_add(1);
         } else {
-            self.data.cycles += 23;
+            self.data.memptr = self.data.memptr.wrapping_sub(1);
         }
         self.DecHL();
     }
@@ -10040,12 +9998,13 @@ impl Z80 {
     /* INDR */
     pub(crate) fn instrED__INDR(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq(_address, 1);
+        self.contend_read_no_mreq(_address, 1);
         let in_i_temp: u8 = self.read_port(self.BC());
-        self.memory.write_byte(self.HL(), in_i_temp);
+        self.write_byte(self.HL(), in_i_temp);
+        self.data.memptr = self.BC().wrapping_sub(1);
 
         self.data.B = self.data.B.wrapping_sub(1);
-        let in_i_temp2: u8 = in_i_temp + self.data.C - 1;
+        let in_i_temp2: u8 = in_i_temp.wrapping_add(self.data.C).wrapping_sub(1);
         self.data.F = tern_op_b(in_i_temp & 0x80 != 0, FLAG_N, 0)
             | tern_op_b(in_i_temp2 < in_i_temp, FLAG_H | FLAG_C, 0)
             | tern_op_b(
@@ -10056,11 +10015,8 @@ impl Z80 {
             | self.tables.sz53_table[self.data.B as usize];
 
         if self.data.B != 0 {
-            self.memory.contend_write_no_mreq_loop(self.HL(), 1, 5);
+            self.contend_write_no_mreq_loop(self.HL(), 1, 5);
             self.DecPC(2);
-            self.data.cycles += 23;
-        } else {
-            self.data.cycles += 18;
         }
         self.DecHL();
     }
@@ -10068,14 +10024,15 @@ impl Z80 {
     /* OTDR */
     pub(crate) fn instrED__OTDR(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq(_address, 1);
+        self.contend_read_no_mreq(_address, 1);
         let address = self.HL();
-        let out_i_temp: u8 = self.memory.read_byte(address);
+        let out_i_temp: u8 = self.read_byte(address);
         self.data.B = self.data.B.wrapping_sub(1); /* This does happen first, despite what the specs say */
         self.write_port(self.BC(), out_i_temp);
+        self.data.memptr = self.BC().wrapping_sub(1);
 
         self.DecHL();
-        let out_i_temp2: u8 = out_i_temp + self.data.L;
+        let out_i_temp2: u8 = out_i_temp.wrapping_add(self.data.L);
         self.data.F = tern_op_b((out_i_temp & 0x80) != 0, FLAG_N, 0)
             | tern_op_b(out_i_temp2 < out_i_temp, FLAG_H | FLAG_C, 0)
             | tern_op_b(
@@ -10086,11 +10043,8 @@ impl Z80 {
             | self.tables.sz53_table[self.data.B as usize];
 
         if self.data.B != 0 {
-            self.memory.contend_read_no_mreq_loop(self.BC(), 1, 5);
+            self.contend_read_no_mreq_loop(self.BC(), 1, 5);
             self.DecPC(2);
-            self.data.cycles += 23;
-        } else {
-            self.data.cycles += 18;
         }
     }
 
@@ -10102,7 +10056,7 @@ impl Z80 {
     /* ADD ix,BC */
     pub(crate) fn instrDD__ADD_REG_BC(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq_loop(_address, 1, 7);
+        self.contend_read_no_mreq_loop(_address, 1, 7);
         // self.add16(self.ix, self.BC())
         let mut ix = Register16::new(self.data.IXH, self.data.IXL);
         let value2 = self.BC();
@@ -10113,7 +10067,7 @@ impl Z80 {
     /* ADD ix,DE */
     pub(crate) fn instrDD__ADD_REG_DE(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq_loop(_address, 1, 7);
+        self.contend_read_no_mreq_loop(_address, 1, 7);
         // self.add16(self.ix, self.DE())
         let mut ix = Register16::new(self.data.IXH, self.data.IXL);
         let value2 = self.DE();
@@ -10124,10 +10078,10 @@ impl Z80 {
     /* LD ix,nnnn */
     pub(crate) fn instrDD__LD_REG_NNNN(&mut self) {
         let address = self.PC();
-        let b1 = self.memory.read_byte(address);
+        let b1 = self.read_byte(address);
         self.IncPC(1);
         let address = self.PC();
-        let b2 = self.memory.read_byte(address);
+        let b2 = self.read_byte(address);
         self.IncPC(1);
         self.SetIX(join_bytes(b2, b1));
     }
@@ -10138,7 +10092,7 @@ impl Z80 {
     /* INC ix */
     pub(crate) fn instrDD__INC_REG(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq_loop(_address, 1, 2);
+        self.contend_read_no_mreq_loop(_address, 1, 2);
         self.IncIX()
     }
 
@@ -10155,14 +10109,14 @@ impl Z80 {
     /* LD IXH,nn */
     pub(crate) fn instrDD__LD_REGH_NN(&mut self) {
         let address = self.PC();
-        self.data.IXH = self.memory.read_byte(address);
+        self.data.IXH = self.read_byte(address);
         self.IncPC(1);
     }
 
     /* ADD ix,ix */
     pub(crate) fn instrDD__ADD_REG_REG(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq_loop(_address, 1, 7);
+        self.contend_read_no_mreq_loop(_address, 1, 7);
         // self.add16(self.ix, self.IX());
         let mut ix = Register16::new(self.data.IXH, self.data.IXL);
         let value2 = self.IX();
@@ -10176,7 +10130,7 @@ impl Z80 {
     /* DEC ix */
     pub(crate) fn instrDD__DEC_REG(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq_loop(_address, 1, 2);
+        self.contend_read_no_mreq_loop(_address, 1, 2);
         self.DecIX()
     }
 
@@ -10193,7 +10147,7 @@ impl Z80 {
     /* LD IXL,nn */
     pub(crate) fn instrDD__LD_REGL_NN(&mut self) {
         let address = self.PC();
-        self.data.IXL = self.memory.read_byte(address);
+        self.data.IXL = self.read_byte(address);
         self.IncPC(1);
     }
 
@@ -10206,21 +10160,21 @@ impl Z80 {
     /* LD (ix+dd),nn */
     pub(crate) fn instrDD__LD_iREGpDD_NN(&mut self) {
         let address = self.PC();
-        let offset = self.memory.read_byte(address);
+        let offset = self.read_byte(address);
         self.IncPC(1);
         let address = self.PC();
-        let value = self.memory.read_byte(address);
+        let value = self.read_byte(address);
         let _address = self.PC();
-        self.memory.contend_read_no_mreq_loop(_address, 1, 2);
+        self.contend_read_no_mreq_loop(_address, 1, 2);
         self.IncPC(1);
-        self.memory
-            .write_byte(self.IX() + (sign_extend(offset) as u16), value);
+        self.data.memptr = self.IX().wrapping_add(sign_extend(offset) as u16);
+        self.write_byte(self.data.memptr, value);
     }
 
     /* ADD ix,SP */
     pub(crate) fn instrDD__ADD_REG_SP(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq_loop(_address, 1, 7);
+        self.contend_read_no_mreq_loop(_address, 1, 7);
         // self.add16(self.ix, self.SP());
         let mut ix = Register16::new(self.data.IXH, self.data.IXL);
         let value2 = self.SP();
@@ -10463,19 +10417,20 @@ impl Z80 {
     /* EX (SP),ix */
     pub(crate) fn instrDD__EX_iSP_REG(&mut self) {
         let address = self.SP();
-        let byte_temp_l = self.memory.read_byte(address);
+        let byte_temp_l = self.read_byte(address);
         let sp = self.SP();
-        let byte_temp_h = self.memory.read_byte(sp + 1);
+        let byte_temp_h = self.read_byte(sp + 1);
         let sp = self.SP();
-        self.memory.contend_read_no_mreq(sp + 1, 1);
+        self.contend_read_no_mreq(sp + 1, 1);
         let sp = self.SP();
-        self.memory.write_byte(sp + 1, self.data.IXH);
+        self.write_byte(sp + 1, self.data.IXH);
         let address = self.SP();
-        self.memory.write_byte(address, self.data.IXL);
+        self.write_byte(address, self.data.IXL);
         let _address = self.SP();
-        self.memory.contend_write_no_mreq_loop(_address, 1, 2);
+        self.contend_write_no_mreq_loop(_address, 1, 2);
         self.data.IXL = byte_temp_l;
         self.data.IXH = byte_temp_h;
+        self.data.memptr = join_bytes(byte_temp_h, byte_temp_l);
     }
 
     /* PUSH ix */
@@ -10489,14 +10444,14 @@ impl Z80 {
     /* LD SP,ix */
     pub(crate) fn instrDD__LD_SP_REG(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq_loop(_address, 1, 2);
+        self.contend_read_no_mreq_loop(_address, 1, 2);
         self.SetSP(self.IX());
     }
 
     /* ADD iy,BC */
     pub(crate) fn instrFD__ADD_REG_BC(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq_loop(_address, 1, 7);
+        self.contend_read_no_mreq_loop(_address, 1, 7);
         // self.add16(self.iy, self.BC());
         let mut iy = Register16::new(self.data.IYH, self.data.IYL);
         let value2 = self.BC();
@@ -10507,7 +10462,7 @@ impl Z80 {
     /* ADD iy,DE */
     pub(crate) fn instrFD__ADD_REG_DE(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq_loop(_address, 1, 7);
+        self.contend_read_no_mreq_loop(_address, 1, 7);
         // self.add16(self.iy, self.DE());
         let mut iy = Register16::new(self.data.IYH, self.data.IYL);
         let value2 = self.DE();
@@ -10518,10 +10473,10 @@ impl Z80 {
     /* LD iy,nnnn */
     pub(crate) fn instrFD__LD_REG_NNNN(&mut self) {
         let address = self.PC();
-        let b1 = self.memory.read_byte(address);
+        let b1 = self.read_byte(address);
         self.IncPC(1);
         let address = self.PC();
-        let b2 = self.memory.read_byte(address);
+        let b2 = self.read_byte(address);
         self.IncPC(1);
         self.SetIY(join_bytes(b2, b1));
     }
@@ -10532,7 +10487,7 @@ impl Z80 {
     /* INC iy */
     pub(crate) fn instrFD__INC_REG(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq_loop(_address, 1, 2);
+        self.contend_read_no_mreq_loop(_address, 1, 2);
         self.IncIY()
     }
 
@@ -10549,14 +10504,14 @@ impl Z80 {
     /* LD IYH,nn */
     pub(crate) fn instrFD__LD_REGH_NN(&mut self) {
         let address = self.PC();
-        self.data.IYH = self.memory.read_byte(address);
+        self.data.IYH = self.read_byte(address);
         self.IncPC(1);
     }
 
     /* ADD iy,iy */
     pub(crate) fn instrFD__ADD_REG_REG(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq_loop(_address, 1, 7);
+        self.contend_read_no_mreq_loop(_address, 1, 7);
         // self.add16(self.iy, self.IY())
         let mut iy = Register16::new(self.data.IYH, self.data.IYL);
         let value2 = self.IY();
@@ -10570,7 +10525,7 @@ impl Z80 {
     /* DEC iy */
     pub(crate) fn instrFD__DEC_REG(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq_loop(_address, 1, 2);
+        self.contend_read_no_mreq_loop(_address, 1, 2);
         self.DecIY()
     }
 
@@ -10587,7 +10542,7 @@ impl Z80 {
     /* LD IYL,nn */
     pub(crate) fn instrFD__LD_REGL_NN(&mut self) {
         let address = self.PC();
-        self.data.IYL = self.memory.read_byte(address);
+        self.data.IYL = self.read_byte(address);
         self.IncPC(1);
     }
 
@@ -10600,21 +10555,21 @@ impl Z80 {
     /* LD (iy+dd),nn */
     pub(crate) fn instrFD__LD_iREGpDD_NN(&mut self) {
         let address = self.PC();
-        let offset = self.memory.read_byte(address);
+        let offset = self.read_byte(address);
         self.IncPC(1);
         let address = self.PC();
-        let value = self.memory.read_byte(address);
+        let value = self.read_byte(address);
         let _address = self.PC();
-        self.memory.contend_read_no_mreq_loop(_address, 1, 2);
+        self.contend_read_no_mreq_loop(_address, 1, 2);
         self.IncPC(1);
-        self.memory
-            .write_byte(self.IY() + (sign_extend(offset) as u16), value)
+        self.data.memptr = self.IY().wrapping_add(sign_extend(offset) as u16);
+        self.write_byte(self.data.memptr, value)
     }
 
     /* ADD iy,SP */
     pub(crate) fn instrFD__ADD_REG_SP(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq_loop(_address, 1, 7);
+        self.contend_read_no_mreq_loop(_address, 1, 7);
         // self.add16(self.iy, self.SP())
         let mut iy = Register16::new(self.data.IYH, self.data.IYL);
         let value2 = self.SP();
@@ -10857,19 +10812,20 @@ impl Z80 {
     /* EX (SP),iy */
     pub(crate) fn instrFD__EX_iSP_REG(&mut self) {
         let address = self.SP();
-        let byte_temp_l = self.memory.read_byte(address);
+        let byte_temp_l = self.read_byte(address);
         let address = self.SP() + 1;
-        let byte_temp_h = self.memory.read_byte(address);
+        let byte_temp_h = self.read_byte(address);
         let _address = self.SP() + 1;
-        self.memory.contend_read_no_mreq(_address, 1);
+        self.contend_read_no_mreq(_address, 1);
         let address = self.SP() + 1;
-        self.memory.write_byte(address, self.data.IYH);
+        self.write_byte(address, self.data.IYH);
         let address = self.SP();
-        self.memory.write_byte(address, self.data.IYL);
+        self.write_byte(address, self.data.IYL);
         let _address = self.SP();
-        self.memory.contend_write_no_mreq_loop(_address, 1, 2);
+        self.contend_write_no_mreq_loop(_address, 1, 2);
         self.data.IYL = byte_temp_l;
         self.data.IYH = byte_temp_h;
+        self.data.memptr = join_bytes(byte_temp_h, byte_temp_l);
     }
 
     /* PUSH iy */
@@ -10883,7 +10839,7 @@ impl Z80 {
     /* LD SP,iy */
     pub(crate) fn instrFD__LD_SP_REG(&mut self) {
         let _address = self.IR();
-        self.memory.contend_read_no_mreq_loop(_address, 1, 2);
+        self.contend_read_no_mreq_loop(_address, 1, 2);
         self.SetSP(self.IY());
     }
 
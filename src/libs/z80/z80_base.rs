@@ -28,12 +28,7 @@ The z80 package implements a Zilog Z80 emulator.
 */
 use serde::{Deserialize, Serialize};
 
-use crate::libs::{
-    memory::{Memory, MemoryData},
-    ports::Ports,
-    ppi::PPIData,
-};
-
+use super::z80_bus::Bus;
 use super::z80_tables::{
     Z80Tables, HALF_CARRY_ADD_TABLE, HALF_CARRY_SUB_TABLE, OVERFLOW_ADD_TABLE, OVERFLOW_SUB_TABLE,
 };
@@ -76,6 +71,34 @@ pub(crate) fn sign_extend(v: u8) -> i16 {
     i16::from(v as i8)
 }
 
+// Whether the instruction of an opcode, shifted by its prefix, computes the
+// flags. Q follows F after these only: POP AF and EX AF,AF' load F but
+// leave Q at 0.
+fn sets_flags(opcode: u16) -> bool {
+    let op = opcode as u8;
+    match opcode & 0xff00 {
+        // Unprefixed, DD and FD
+        0x000 | 0x300 | 0x500 => {
+            // Rotations of A, DAA, CPL, SCF, CCF and ALU A,r
+            matches!(op, 0x07 | 0x0f | 0x17 | 0x1f | 0x27 | 0x2f | 0x37 | 0x3f | 0x80..=0xbf)
+                // INC r, DEC r, ADD HL,rr and ALU A,n
+                || op & 0xc6 == 0x04
+                || op & 0xcf == 0x09
+                || op & 0xc7 == 0xc6
+        }
+        // Rotations, shifts and BIT, with or without an index
+        0x100 | 0x400 => op < 0x80,
+        // IN r,(C), ADC/SBC HL,rr, NEG, LD A,I/R, RRD, RLD and the block
+        // instructions
+        0x200 => {
+            matches!(op & 0xc7, 0x40 | 0x42 | 0x44)
+                || matches!(op, 0x57 | 0x5f | 0x67 | 0x6f)
+                || (op & 0xe4 == 0xa0)
+        }
+        _ => false,
+    }
+}
+
 pub struct Register16 {
     high: u8,
     low: u8,
@@ -112,7 +135,9 @@ impl Register16 {
     }
 }
 
+// Fields missing in older save files take their default value
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
 #[allow(non_snake_case)]
 pub struct Z80Data {
     pub(crate) A: u8,
@@ -168,10 +193,17 @@ pub struct Z80Data {
 
     pub(crate) halted: bool,
 
-    // Needed when executing opcodes prefixed by 0xCB
-    pub(crate) temp_addr: u16,
+    // Internal address register (WZ). Jumps, indexed and 16 bit accesses
+    // leave their address in it, and BIT n,(HL) shows its high byte in
+    // flags 3 and 5.
+    pub(crate) memptr: u16,
+
+    // F if the last instruction changed the flags, 0 otherwise. SCF and CCF
+    // take flags 3 and 5 from it.
+    pub(crate) q: u8,
 
-    pub(crate) interrupts_enabled_at: isize,
+    // Set by EI: INT is not sampled at the end of the next instruction
+    pub(crate) ei_delay: bool,
 
     pub(crate) rzx_instructions_offset: isize,
 
@@ -220,9 +252,10 @@ impl Z80Data {
 
             halted: false,
 
-            temp_addr: 0,
+            memptr: 0,
+            q: 0,
 
-            interrupts_enabled_at: 0,
+            ei_delay: false,
 
             rzx_instructions_offset: 0,
 
@@ -247,7 +280,8 @@ impl Z80Data {
         self.t_states = 0;
 
         self.halted = false;
-        self.interrupts_enabled_at = 0;
+        self.ei_delay = false;
+        (self.memptr, self.q) = (0, 0);
     }
 }
 impl Default for Z80Data {
@@ -256,26 +290,60 @@ impl Default for Z80Data {
     }
 }
 
+// Register file of the Z80, to set up and check the CPU from outside
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Registers {
+    pub af: u16,
+    pub bc: u16,
+    pub de: u16,
+    pub hl: u16,
+    pub af_: u16,
+    pub bc_: u16,
+    pub de_: u16,
+    pub hl_: u16,
+    pub ix: u16,
+    pub iy: u16,
+    pub sp: u16,
+    pub pc: u16,
+    pub memptr: u16,
+    pub i: u8,
+    pub r: u8,
+    pub iff1: u8,
+    pub iff2: u8,
+    pub im: u8,
+    pub halted: bool,
+}
+
 #[allow(non_snake_case)]
-pub struct Z80 {
+pub struct Z80<B: Bus> {
     pub(crate) data: Z80Data,
 
     pub(crate) tables: Z80Tables,
-    pub(crate) memory: Memory,
-    ports: Ports,
+    pub(crate) bus: B,
+
+    // Cycles counted by the hooks for the code they replace
+    pub(crate) hook_cycles: u64,
 }
 
 #[allow(non_snake_case)]
-impl Z80 {
-    pub fn new(memory: Memory, ports: Ports) -> Self {
+impl<B: Bus> Z80<B> {
+    pub fn new(bus: B) -> Self {
         Self {
             data: Z80Data::default(),
-            memory,
-            ports,
+            bus,
             tables: Z80Tables::new(),
+            hook_cycles: 0,
         }
     }
 
+    pub fn bus(&self) -> &B {
+        &self.bus
+    }
+
+    pub fn bus_mut(&mut self) -> &mut B {
+        &mut self.bus
+    }
+
     // Reset resets the Z80.
     pub fn reset(&mut self) {
         self.data.reset();
@@ -289,76 +357,118 @@ impl Z80 {
     pub fn is_halted(&self) -> bool {
         self.data.halted
     }
-    // Interrupt process a Z80 maskable interrupt
-    pub fn interrupt(&mut self) {
-        if self.data.IFF1 != 0 {
-            if self.data.halted {
-                self.data.pc += 1;
-                self.data.halted = false;
-            }
-
-            self.data.t_states += 7;
-
-            self.data.R = (self.data.R + 1) & 0x7f;
-            (self.data.IFF1, self.data.IFF2) = (0, 0);
-
-            // push PC
-            {
-                let (pch, pcl) = split_word(self.data.pc);
-
-                self.data.sp -= 1;
-                self.memory.write_byte(self.data.sp, pch);
-                self.data.sp -= 1;
-                self.memory.write_byte(self.data.sp, pcl);
-            }
-
-            match self.data.IM {
-                0 | 1 => {
-                    self.data.pc = 0x0038;
-                }
-                2 => {
-                    let mut int_temp: u16 = ((self.data.I as u16) << 8) | 0xff;
-                    let pcl = self.memory.read_byte(int_temp);
-                    int_temp += 1;
-                    let pch = self.memory.read_byte(int_temp);
-                    self.data.pc = join_bytes(pch, pcl);
-                }
-                _ => {
-                    panic!("Unknown interrupt mode");
-                }
-            }
-        }
+    // INT is a level: devices keep it asserted until they are serviced,
+    // and the CPU samples it between instructions while IFF1 is set
+    fn interrupt_pending(&mut self) -> bool {
+        let delayed = std::mem::take(&mut self.data.ei_delay);
+        !delayed && self.data.IFF1 != 0 && self.bus.int_line()
     }
-    // Process a Z80 non-maskable interrupt.
-    pub fn non_maskable_interrupt(&mut self) {
+
+    fn leave_halt(&mut self) {
         if self.data.halted {
-            self.data.pc += 1;
+            self.data.pc = self.data.pc.wrapping_add(1);
             self.data.halted = false;
         }
+    }
 
-        self.data.t_states += 7;
+    fn push_pc(&mut self) {
+        let (pch, pcl) = split_word(self.data.pc);
+        self.push16(pcl, pch);
+    }
 
+    // Process a Z80 maskable interrupt. The acknowledge cycle is a M1 cycle
+    // two states longer, which reads the data bus instead of the memory.
+    fn interrupt(&mut self) {
+        self.leave_halt();
+        self.data.cycles += 6 + self.bus.m1_wait();
         self.data.R = (self.data.R + 1) & 0x7f;
         (self.data.IFF1, self.data.IFF2) = (0, 0);
+        let value = self.bus.acknowledge_interrupt();
+
+        match self.data.IM {
+            0 => self.interrupt_mode0(value),
+            1 => {
+                let _address = self.IR();
+                self.contend_read_no_mreq(_address, 1);
+                self.push_pc();
+                self.data.pc = 0x0038;
+                self.data.memptr = 0x0038;
+            }
+            2 => {
+                // The bus has the low byte of the address of the vector
+                let _address = self.IR();
+                self.contend_read_no_mreq(_address, 1);
+                self.push_pc();
+                let address = join_bytes(self.data.I, value);
+                let pcl = self.read_byte(address);
+                let pch = self.read_byte(address.wrapping_add(1));
+                self.data.pc = join_bytes(pch, pcl);
+                self.data.memptr = self.data.pc;
+            }
+            _ => {
+                panic!("Unknown interrupt mode");
+            }
+        }
+    }
 
-        // push PC
-        {
-            let (pch, pcl) = split_word(self.data.pc);
-
-            self.data.sp -= 1;
-            self.memory.write_byte(self.data.sp, pch);
-            self.data.sp -= 1;
-            self.memory.write_byte(self.data.sp, pcl);
+    // IM 0 executes the instruction on the bus, usually a RST. CALL takes
+    // its address from two more reads of the bus; other instructions of
+    // more than one byte would read their operands from memory.
+    fn interrupt_mode0(&mut self, opcode: u8) {
+        match opcode {
+            0xcd => {
+                self.data.cycles += 6;
+                let pcl = self.bus.acknowledge_interrupt();
+                let pch = self.bus.acknowledge_interrupt();
+                let _address = self.IR();
+                self.contend_read_no_mreq(_address, 1);
+                self.push_pc();
+                self.data.pc = join_bytes(pch, pcl);
+                self.data.memptr = self.data.pc;
+            }
+            op if op & 0xc7 == 0xc7 => {
+                let _address = self.IR();
+                self.contend_read_no_mreq(_address, 1);
+                self.rst(op & 0x38);
+            }
+            op => {
+                self.execute_opcode(op as u16);
+            }
         }
+    }
 
+    // Process a Z80 non-maskable interrupt.
+    pub fn non_maskable_interrupt(&mut self) {
+        self.leave_halt();
+        self.data.cycles += 5 + self.bus.m1_wait();
+        self.data.R = (self.data.R + 1) & 0x7f;
+        // IFF2 keeps the state of the interrupts for RETN
+        self.data.IFF1 = 0;
+        self.push_pc();
         self.data.pc = 0x0066;
+        self.data.memptr = 0x0066;
     }
     pub fn jp(&mut self) {
         let mut jp_temp: u16 = self.data.pc;
-        let pcl = self.memory.read_byte(jp_temp);
-        jp_temp += 1;
-        let pch = self.memory.read_byte(jp_temp);
+        let pcl = self.read_byte(jp_temp);
+        jp_temp = jp_temp.wrapping_add(1);
+        let pch = self.read_byte(jp_temp);
         self.data.pc = join_bytes(pch, pcl);
+        self.data.memptr = self.data.pc;
+    }
+
+    // JP cc and CALL cc read their address even when the condition is false
+    pub fn skip_address(&mut self) {
+        let pcl = self.read_byte(self.data.pc);
+        let pch = self.read_byte(self.data.pc.wrapping_add(1));
+        self.data.memptr = join_bytes(pch, pcl);
+        self.data.pc = self.data.pc.wrapping_add(2);
+    }
+
+    // Flags 3 and 5 of SCF and CCF: from A, or from A|F when the
+    // instruction before changed the flags
+    pub fn scf_ccf_xy(&self) -> u8 {
+        ((self.data.q ^ self.data.F) | self.data.A) & (FLAG_3 | FLAG_5)
     }
 
     pub fn dec(&mut self, value: &mut u8) {
@@ -376,46 +486,50 @@ impl Z80 {
             | self.tables.sz53_table[*value as usize];
     }
     pub fn jr(&mut self) {
-        let jr_temp: i16 = sign_extend(self.memory.read_byte(self.data.pc));
-        self.memory.contend_read_no_mreq_loop(self.data.pc, 1, 5);
+        let jr_temp: i16 = sign_extend(self.read_byte(self.data.pc));
+        self.contend_read_no_mreq_loop(self.data.pc, 1, 5);
         self.data.pc = self.data.pc.wrapping_add(jr_temp as u16);
+        // The caller steps over the offset
+        self.data.memptr = self.data.pc.wrapping_add(1);
     }
 
     pub fn ld16nnrr(&mut self, reg_l: u8, reg_h: u8) {
         let mut ld_temp: u16;
 
-        ld_temp = self.memory.read_byte(self.data.pc) as u16;
-        self.data.pc += 1;
-        ld_temp |= (self.memory.read_byte(self.data.pc) as u16) << 8;
-        self.data.pc += 1;
-        self.memory.write_byte(ld_temp, reg_l);
-        ld_temp += 1;
-        self.memory.write_byte(ld_temp, reg_h);
+        ld_temp = self.read_byte(self.data.pc) as u16;
+        self.data.pc = self.data.pc.wrapping_add(1);
+        ld_temp |= (self.read_byte(self.data.pc) as u16) << 8;
+        self.data.pc = self.data.pc.wrapping_add(1);
+        self.write_byte(ld_temp, reg_l);
+        ld_temp = ld_temp.wrapping_add(1);
+        self.data.memptr = ld_temp;
+        self.write_byte(ld_temp, reg_h);
     }
 
     // pub fn ld16rrnn(&mut self, reg_l: &mut u8, reg_h: &mut u8) {
     //     let mut ld_temp: u16;
 
-    //     ld_temp = self.memory.ReadByte(self.data.pc) as u16;
+    //     ld_temp = self.bus.ReadByte(self.data.pc) as u16;
     //     self.data.pc += 1;
-    //     ld_temp |= (self.memory.ReadByte(self.data.pc) as u16) << 8;
+    //     ld_temp |= (self.bus.ReadByte(self.data.pc) as u16) << 8;
     //     self.data.pc += 1;
-    //     *reg_l = self.memory.ReadByte(ld_temp);
+    //     *reg_l = self.bus.ReadByte(ld_temp);
     //     ld_temp += 1;
-    //     *reg_h = self.memory.ReadByte(ld_temp);
+    //     *reg_h = self.bus.ReadByte(ld_temp);
     // }
 
     /// return low, high
     pub fn ld16rrnn_ex(&mut self) -> (u8, u8) {
         let mut ld_temp: u16;
 
-        ld_temp = self.memory.read_byte(self.data.pc) as u16;
-        self.data.pc += 1;
-        ld_temp |= (self.memory.read_byte(self.data.pc) as u16) << 8;
-        self.data.pc += 1;
-        let reg_l = self.memory.read_byte(ld_temp);
-        ld_temp += 1;
-        let reg_h = self.memory.read_byte(ld_temp);
+        ld_temp = self.read_byte(self.data.pc) as u16;
+        self.data.pc = self.data.pc.wrapping_add(1);
+        ld_temp |= (self.read_byte(self.data.pc) as u16) << 8;
+        self.data.pc = self.data.pc.wrapping_add(1);
+        let reg_l = self.read_byte(ld_temp);
+        ld_temp = ld_temp.wrapping_add(1);
+        self.data.memptr = ld_temp;
+        let reg_h = self.read_byte(ld_temp);
         (reg_l, reg_h)
     }
 
@@ -452,6 +566,7 @@ impl Z80 {
     }
 
     pub fn adc16(&mut self, value: u16) {
+        self.data.memptr = self.HL().wrapping_add(1);
         let add16_temp: usize =
             (self.HL() as usize) + (value as usize) + ((self.data.F & FLAG_C) as usize);
         let lookup: u8 = ((((self.HL()) & 0x8800) >> 11) as usize
@@ -468,6 +583,7 @@ impl Z80 {
     }
 
     pub fn add16(&mut self, value1: &mut Register16, value2: u16) {
+        self.data.memptr = value1.get().wrapping_add(1);
         let add16_temp: usize = (value1.get() as usize) + (value2 as usize);
         let lookup: u8 = ((value1.get() & 0x0800) >> 11
             | (value2 & 0x0800) >> 10
@@ -499,26 +615,27 @@ impl Z80 {
 
     /// return reg_l, reg_h
     pub fn pop16(&mut self) -> (u8, u8) {
-        let reg_l = self.memory.read_byte(self.data.sp);
-        self.data.sp += 1;
-        let reg_h = self.memory.read_byte(self.data.sp);
-        self.data.sp += 1;
+        let reg_l = self.read_byte(self.data.sp);
+        self.data.sp = self.data.sp.wrapping_add(1);
+        let reg_h = self.read_byte(self.data.sp);
+        self.data.sp = self.data.sp.wrapping_add(1);
         (reg_l, reg_h)
     }
 
     pub fn push16(&mut self, reg_l: u8, reg_h: u8) {
         // self.data.sp -= 1;
         self.data.sp = self.data.sp.wrapping_sub(1);
-        self.memory.write_byte(self.data.sp, reg_h);
+        self.write_byte(self.data.sp, reg_h);
         // self.data.sp -= 1;
         self.data.sp = self.data.sp.wrapping_sub(1);
-        self.memory.write_byte(self.data.sp, reg_l);
+        self.write_byte(self.data.sp, reg_l);
     }
 
     pub fn ret(&mut self) {
         let (pcl, pch) = self.pop16();
         // let old_pc = self.data.pc;
         self.data.pc = join_bytes(pch, pcl);
+        self.data.memptr = self.data.pc;
         // println!("z80:ret 0x{:04x} -> 0x{:04x}", old_pc, self.data.pc);
     }
 
@@ -553,6 +670,7 @@ impl Z80 {
         let (pch, pcl) = split_word(self.data.pc);
         self.push16(pcl, pch);
         self.data.pc = value as u16;
+        self.data.memptr = self.data.pc;
     }
 
     pub fn sbc(&mut self, value: u8) {
@@ -570,6 +688,7 @@ impl Z80 {
     }
 
     pub fn sbc16(&mut self, value: u16) {
+        self.data.memptr = self.HL().wrapping_add(1);
         let sub16_temp: usize = (self.HL() as usize)
             .wrapping_sub(value as usize)
             .wrapping_sub((self.data.F & FLAG_C) as usize);
@@ -644,7 +763,7 @@ impl Z80 {
 
     pub fn jp_hl(&mut self) {
         let jmp_tgt = self.HL();
-        if self.has_hook(jmp_tgt) {
+        if self.bus.has_hooks() && self.has_hook(jmp_tgt) {
             // println!("jp_iHL:0x{:04x}", jmp_tgt);
             let skip = self.call_hook(jmp_tgt);
             assert!(skip);
@@ -655,19 +774,20 @@ impl Z80 {
     }
 
     pub fn call(&mut self) {
-        let call_temp_l: u8 = self.memory.read_byte(self.data.pc);
-        self.data.pc += 1;
-        let call_temp_h: u8 = self.memory.read_byte(self.data.pc);
+        let call_temp_l: u8 = self.read_byte(self.data.pc);
+        self.data.pc = self.data.pc.wrapping_add(1);
+        let call_temp_h: u8 = self.read_byte(self.data.pc);
         let new_pc = join_bytes(call_temp_h, call_temp_l);
-        self.memory.contend_read_no_mreq(self.data.pc, 1);
-        self.data.pc += 1;
+        self.data.memptr = new_pc;
+        self.contend_read_no_mreq(self.data.pc, 1);
+        self.data.pc = self.data.pc.wrapping_add(1);
         let (pch, pcl) = split_word(self.data.pc);
         self.push16(pcl, pch);
         let old_pc = self.data.pc;
         // if new_pc >= 0x4000 && new_pc <= 0xc000 {
         //     log::info!("z80:call 0x{:04x}=>0x{:04x}", old_pc, new_pc);
         // }
-        if self.has_hook(new_pc) {
+        if self.bus.has_hooks() && self.has_hook(new_pc) {
             let die_after_unknown_caller = true;
             let skip = self.call_hook(new_pc);
             if !self.is_known_caller(old_pc) {
@@ -714,17 +834,61 @@ impl Z80 {
     // }
 
     pub fn in_u8_ex(&mut self, port: u16) -> u8 {
+        self.data.memptr = port.wrapping_add(1);
         let reg = self.read_port(port);
         self.data.F = self.data.F & FLAG_C | self.tables.sz53p_table[reg as usize];
         reg
     }
 
     pub fn read_port(&mut self, address: u16) -> u8 {
-        self.ports.read_port(address)
+        self.io_cycle(address);
+        self.bus.read_port(address)
     }
 
     pub fn write_port(&mut self, address: u16, b: u8) {
-        self.ports.write_port(address, b);
+        self.io_cycle(address);
+        self.bus.write_port(address, b);
+    }
+
+    // Cycles of the accesses to the bus. The contend_* calls count the
+    // cycles an instruction spends on an address without a read or a
+    // write, like the internal operations of the CPU.
+    fn io_cycle(&mut self, port: u16) {
+        let wait = self.bus.io_wait(port, self.data.cycles);
+        self.data.cycles += 4 + wait;
+    }
+
+    fn fetch_opcode(&mut self) -> u8 {
+        self.data.cycles += 4 + self.bus.m1_wait();
+        let opcode = self.bus.read_byte(self.data.pc);
+        self.data.pc = self.data.pc.wrapping_add(1);
+        opcode
+    }
+
+    pub(crate) fn read_byte(&mut self, address: u16) -> u8 {
+        self.data.cycles += 3;
+        self.bus.read_byte(address)
+    }
+
+    pub(crate) fn write_byte(&mut self, address: u16, value: u8) {
+        self.data.cycles += 3;
+        self.bus.write_byte(address, value);
+    }
+
+    pub(crate) fn contend_read(&mut self, _address: u16, time: u64) {
+        self.data.cycles += time;
+    }
+
+    pub(crate) fn contend_read_no_mreq(&mut self, _address: u16, time: u64) {
+        self.data.cycles += time;
+    }
+
+    pub(crate) fn contend_read_no_mreq_loop(&mut self, _address: u16, time: u64, count: u64) {
+        self.data.cycles += time * count;
+    }
+
+    pub(crate) fn contend_write_no_mreq_loop(&mut self, _address: u16, time: u64, count: u64) {
+        self.data.cycles += time * count;
     }
 
     // The following functions can not be generated as they need special treatments
@@ -741,12 +905,12 @@ impl Z80 {
 
     // IncPC increments the program counter.
     pub fn IncPC(&mut self, value: u16) {
-        self.data.pc += value;
+        self.data.pc = self.data.pc.wrapping_add(value);
     }
 
     // IncPC decrements the program counter.
     pub fn DecPC(&mut self, value: u16) {
-        self.data.pc -= value;
+        self.data.pc = self.data.pc.wrapping_sub(value);
     }
 
     // SP returns the SP register.
@@ -761,12 +925,12 @@ impl Z80 {
 
     // IncSP increments the SP register.
     pub fn IncSP(&mut self) {
-        self.data.sp += 1;
+        self.data.sp = self.data.sp.wrapping_add(1);
     }
 
     // DecSP decrements the SP register.
     pub fn DecSP(&mut self) {
-        self.data.sp -= 1;
+        self.data.sp = self.data.sp.wrapping_sub(1);
     }
 
     // IR returns the IR register.
@@ -777,124 +941,119 @@ impl Z80 {
         ir
     }
 
+    // Memory as the CPU sees it, for debuggers and test harnesses
+    pub fn peek(&self, address: u16) -> u8 {
+        self.bus.read_byte(address)
+    }
+
+    pub fn poke(&mut self, address: u16, value: u8) {
+        self.bus.write_byte(address, value);
+    }
+
     pub fn slt_trap(&mut self, _address: i16, _level: u8) -> isize {
         // Dummy implementation
         0
     }
 
-    // Execute a single instruction at the program counter.
+    // Execute a single instruction at the program counter, or accept a
+    // pending interrupt.
     pub fn do_opcode(&mut self) {
-        self.memory.contend_read(self.data.pc, 4);
-        let opcode = self.memory.read_byte_internal(self.data.pc);
+        if self.interrupt_pending() {
+            self.interrupt();
+            self.data.q = 0;
+            return;
+        }
+        let opcode = self.fetch_opcode();
         self.data.R = (self.data.R + 1) & 0x7f;
-        self.data.pc += 1;
-        // self.data.cycles += get_timings[opcode](z80);
-        self.data.cycles += self.get_timings(opcode as u16);
 
         // OpcodesMap[opcode](z80)
         if self.data.debug {
             self.disassemble_map(opcode as u16);
         }
-        match opcode {
-            0xcb => {
-                self.opcode_cb();
-            }
-            0xdd => {
-                self.opcode_dd();
-            }
-            0xed => {
-                self.opcode_ed();
-            }
-            0xfd => {
-                self.opcode_fd();
-            }
+        let executed = match opcode {
+            0xcb => self.opcode_cb(),
+            0xdd => self.opcode_dd(),
+            0xed => self.opcode_ed(),
+            0xfd => self.opcode_fd(),
             _ => {
                 self.execute_opcode(opcode as u16);
+                opcode as u16
             }
-        }
+        };
+        self.data.q = if sets_flags(executed) { self.data.F } else { 0 };
     }
 
-    fn opcode_cb(&mut self) {
-        self.memory.contend_read(self.data.pc, 4);
-        let opcode2: u8 = self.memory.read_byte_internal(self.data.pc);
-        self.data.pc += 1;
+    // The prefixed opcodes return the opcode they executed
+
+    fn opcode_cb(&mut self) -> u16 {
+        let opcode2: u8 = self.fetch_opcode();
         self.data.R += 1;
         self.execute_opcode(SHIFT_0X_CB + opcode2 as u16);
-        self.data.cycles += self.get_timings(SHIFT_0X_CB + opcode2 as u16);
+        SHIFT_0X_CB + opcode2 as u16
     }
 
-    fn opcode_dd(&mut self) {
-        self.memory.contend_read(self.data.pc, 4);
-        let opcode2: u8 = self.memory.read_byte_internal(self.data.pc);
-        self.data.pc += 1;
+    fn opcode_dd(&mut self) -> u16 {
+        let opcode2: u8 = self.fetch_opcode();
         self.data.R += 1;
 
         match opcode2 {
             0xcb => {
-                self.memory.contend_read(self.data.pc, 3);
-                self.data.temp_addr =
-                    self.IX() + sign_extend(self.memory.read_byte_internal(self.data.pc)) as u16;
-                self.data.pc += 1;
-                self.memory.contend_read(self.data.pc, 3);
-                let opcode3: u8 = self.memory.read_byte_internal(self.data.pc);
-                self.memory.contend_read_no_mreq_loop(self.data.pc, 1, 2);
-                self.data.pc += 1;
+                let offset = sign_extend(self.read_byte(self.data.pc));
+                self.data.memptr = self.IX().wrapping_add(offset as u16);
+                self.data.pc = self.data.pc.wrapping_add(1);
+                // The opcode after the offset is read without a M1 cycle
+                let opcode3: u8 = self.read_byte(self.data.pc);
+                self.contend_read_no_mreq_loop(self.data.pc, 1, 2);
+                self.data.pc = self.data.pc.wrapping_add(1);
                 self.execute_opcode(SHIFT_0X_DDCB + (opcode3 as u16));
-                self.data.cycles += self.get_timings(SHIFT_0X_DDCB + opcode3 as u16);
+                SHIFT_0X_DDCB + (opcode3 as u16)
             }
             _ => {
                 if self.execute_opcode(SHIFT_0X_DD + (opcode2 as u16)) {
-                    self.data.cycles += self.get_timings(SHIFT_0X_DD + (opcode2 as u16));
+                    SHIFT_0X_DD + (opcode2 as u16)
                 } else {
                     /* Instruction did not involve H or L */
                     self.execute_opcode(opcode2 as u16);
-                    self.data.cycles += self.get_timings(opcode2 as u16);
+                    opcode2 as u16
                 }
             }
         }
     }
 
-    fn opcode_ed(&mut self) {
-        self.memory.contend_read(self.data.pc, 4);
-        let opcode2: u8 = self.memory.read_byte_internal(self.data.pc);
-        self.data.pc += 1;
+    fn opcode_ed(&mut self) -> u16 {
+        let opcode2: u8 = self.fetch_opcode();
         self.data.R += 1;
-
-        self.data.cycles += self.get_timings(SHIFT_0X_ED + opcode2 as u16);
         if !self.execute_opcode(SHIFT_0X_ED + opcode2 as u16) {
             self.invalid_opcode();
         }
+        SHIFT_0X_ED + opcode2 as u16
     }
 
-    fn opcode_fd(&mut self) {
-        self.memory.contend_read(self.data.pc, 4);
-        let opcode2: u8 = self.memory.read_byte_internal(self.data.pc);
-        self.data.pc += 1;
+    fn opcode_fd(&mut self) -> u16 {
+        let opcode2: u8 = self.fetch_opcode();
         self.data.R += 1;
 
         match opcode2 {
             0xcb => {
-                self.memory.contend_read(self.data.pc, 3);
-                self.data.temp_addr =
-                    self.IY() + sign_extend(self.memory.read_byte_internal(self.data.pc)) as u16;
-                self.data.pc += 1;
-                self.memory.contend_read(self.data.pc, 3);
-                let opcode3: u8 = self.memory.read_byte_internal(self.data.pc);
-                self.memory.contend_read_no_mreq_loop(self.data.pc, 1, 2);
-                self.data.pc += 1;
+                let offset = sign_extend(self.read_byte(self.data.pc));
+                self.data.memptr = self.IY().wrapping_add(offset as u16);
+                self.data.pc = self.data.pc.wrapping_add(1);
+                // The opcode after the offset is read without a M1 cycle
+                let opcode3: u8 = self.read_byte(self.data.pc);
+                self.contend_read_no_mreq_loop(self.data.pc, 1, 2);
+                self.data.pc = self.data.pc.wrapping_add(1);
 
                 self.execute_opcode(SHIFT_0X_FDCB + (opcode3 as u16));
-                self.data.cycles += self.get_timings(SHIFT_0X_FDCB + (opcode3 as u16));
+                SHIFT_0X_FDCB + (opcode3 as u16)
             }
 
             _ => {
                 if self.execute_opcode(SHIFT_0X_FD + opcode2 as u16) {
-                    // f(z80)
-                    self.data.cycles += self.get_timings(SHIFT_0X_FD + opcode2 as u16);
+                    SHIFT_0X_FD + opcode2 as u16
                 } else {
                     /* Instruction did not involve H or L */
                     self.execute_opcode(opcode2 as u16);
-                    self.data.cycles += self.get_timings(opcode2 as u16);
+                    opcode2 as u16
                 }
             }
         }
@@ -910,16 +1069,45 @@ impl Z80 {
     pub fn set_data(&mut self, data: Z80Data) {
         self.data = data;
     }
-    pub fn get_memory_data(&self) -> MemoryData {
-        self.memory.get_data()
-    }
-    pub fn set_memory_data(&mut self, data: MemoryData) {
-        self.memory.set_data(data);
-    }
-    pub fn get_ppi_data(&self) -> PPIData {
-        self.memory.get_ppi_data()
+    pub fn registers(&self) -> Registers {
+        let d = &self.data;
+        Registers {
+            af: join_bytes(d.A, d.F),
+            bc: join_bytes(d.B, d.C),
+            de: join_bytes(d.D, d.E),
+            hl: join_bytes(d.H, d.L),
+            af_: join_bytes(d.A_, d.F_),
+            bc_: join_bytes(d.B_, d.C_),
+            de_: join_bytes(d.D_, d.E_),
+            hl_: join_bytes(d.H_, d.L_),
+            ix: join_bytes(d.IXH, d.IXL),
+            iy: join_bytes(d.IYH, d.IYL),
+            sp: d.sp,
+            pc: d.pc,
+            memptr: d.memptr,
+            i: d.I,
+            r: d.R7 & 0x80 | (d.R & 0x7f) as u8,
+            iff1: d.IFF1,
+            iff2: d.IFF2,
+            im: d.IM,
+            halted: d.halted,
+        }
     }
-    pub fn set_ppi_data(&mut self, data: PPIData) {
-        self.memory.set_ppi_data(data);
+    pub fn set_registers(&mut self, regs: &Registers) {
+        let d = &mut self.data;
+        (d.A, d.F) = split_word(regs.af);
+        (d.B, d.C) = split_word(regs.bc);
+        (d.D, d.E) = split_word(regs.de);
+        (d.H, d.L) = split_word(regs.hl);
+        (d.A_, d.F_) = split_word(regs.af_);
+        (d.B_, d.C_) = split_word(regs.bc_);
+        (d.D_, d.E_) = split_word(regs.de_);
+        (d.H_, d.L_) = split_word(regs.hl_);
+        (d.IXH, d.IXL) = split_word(regs.ix);
+        (d.IYH, d.IYL) = split_word(regs.iy);
+        (d.sp, d.pc, d.memptr) = (regs.sp, regs.pc, regs.memptr);
+        (d.I, d.R7, d.R) = (regs.i, regs.r & 0x80, (regs.r & 0x7f) as u16);
+        (d.IFF1, d.IFF2, d.IM) = (regs.iff1, regs.iff2, regs.im);
+        d.halted = regs.halted;
     }
 }
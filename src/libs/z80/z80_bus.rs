@@ -0,0 +1,46 @@
+/*
+
+    What the Z80 sees of the machine: memory, I/O ports and the interrupt
+    acknowledge cycle.
+
+    The CPU counts the cycles of every access it makes: 4 for an opcode
+    fetch (M1), 3 for a memory read or write and 4 for an I/O access, which
+    includes the wait state the Z80 inserts itself. The machine can add
+    wait states to the M1 and I/O cycles; there are none by default.
+
+*/
+
+pub trait Bus {
+    fn read_byte(&self, address: u16) -> u8;
+    fn write_byte(&mut self, address: u16, value: u8);
+    fn read_port(&mut self, port: u16) -> u8;
+    fn write_port(&mut self, port: u16, value: u8);
+
+    // Wait states added to every opcode fetch, prefixes included
+    fn m1_wait(&mut self) -> u64 {
+        0
+    }
+
+    // Wait states added to an I/O access starting at the given cycle of
+    // the CPU. Devices timed with the CPU can catch up with it here.
+    fn io_wait(&mut self, _port: u16, _time: u64) -> u64 {
+        0
+    }
+
+    // Level of the INT line, asserted while any device wants the CPU
+    fn int_line(&self) -> bool {
+        false
+    }
+
+    // Value the interrupting device puts on the data bus. With nothing
+    // driving it the bus floats to 0xff (RST 38h in IM 0).
+    fn acknowledge_interrupt(&mut self) -> u8 {
+        0xff
+    }
+
+    // Whether calls to the BASIC routines of z80_hook_controls run the
+    // native replacements
+    fn has_hooks(&self) -> bool {
+        false
+    }
+}
@@ -24,6 +24,7 @@ WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 */
 
 use super::z80_base::{join_bytes, split_word, tern_op_b, FLAG_C, FLAG_H, FLAG_N, FLAG_V, Z80};
+use super::z80_bus::Bus;
 
 macro_rules! fn_inc_reg {
     ($fn:tt, $r:ident) => {
@@ -88,7 +89,7 @@ macro_rules! fn_inc_reg16 {
 }
 
 #[allow(non_snake_case, dead_code)]
-impl Z80 {
+impl<B: Bus> Z80<B> {
     fn_inc_reg!(incA, A);
     fn_dec_reg!(decA, A);
 
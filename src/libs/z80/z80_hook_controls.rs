@@ -1,8 +1,10 @@
 use super::z80_base::{split_word, Z80};
+use super::z80_bus::Bus;
 
 #[allow(non_snake_case, dead_code)]
-impl Z80 {
+impl<B: Bus> Z80<B> {
     // 0x8a21 |
+    #[allow(clippy::match_like_matches_macro)]
     pub(crate) fn has_hook(&self, addr: u16) -> bool {
         match addr {
             0x0093 | 0x0096 | 0x42ba | 0x4453 | 0x44b6 | 0x46ac | 0x46da | 0x46ea | 0x46fa
@@ -225,6 +227,8 @@ impl Z80 {
         //     0xb6cd | 0xb6f1 | 0xb74f | 0xb79b | 0xb79f | 0xb825 | 0xb8b4 | 0xb8c3
         // );
         let need_guard = true;
+        let cycles = self.data.cycles;
+        let outer_hook_cycles = std::mem::take(&mut self.hook_cycles);
         let old_pc = self.PC() + 3; // cd xx xx
         self.SetPC(addr);
         let mut l = 0xad;
@@ -239,6 +243,8 @@ impl Z80 {
             assert!(l == ll);
             assert!(h == hh);
         }
+        self.data.cycles = cycles + self.hook_cycles;
+        self.hook_cycles += outer_hook_cycles;
         self.SetPC(old_pc);
         r
     }
@@ -1,6 +1,7 @@
 use crate::libs::z80::z80_base::{FLAG_C, FLAG_S, FLAG_Z};
 
 use super::z80_base::{join_bytes, Z80};
+use super::z80_bus::Bus;
 
 const SCREEN2_PATTERN_GENERATOR_TABLE_SIZE: u16 = 0x1800;
 const SCREEN_2_VRAM_SPRITE_TABLE_BEGIN: u16 = 0x1b00;
@@ -8,7 +9,7 @@ const DAT_IO_0098: u8 = 0x98;
 const DAT_IO_0099: u8 = 0x99;
 
 #[allow(non_snake_case, dead_code)]
-impl Z80 {
+impl<B: Bus> Z80<B> {
     fn assert_pc(&self, addr: u16) {
         assert!(
             self.PC() == addr,
@@ -18,8 +19,8 @@ impl Z80 {
         );
     }
     pub(crate) fn read_word(&self, addr: u16) -> u16 {
-        let l = self.memory.read_byte(addr);
-        let h = self.memory.read_byte(addr + 1);
+        let l = self.bus.read_byte(addr);
+        let h = self.bus.read_byte(addr + 1);
         join_bytes(h, l)
     }
     pub(crate) fn peek_stack(&self, depth: usize) {
@@ -4289,7 +4290,7 @@ impl Z80 {
         self.instr_hk__LD_iHL_A();
         //         ram:6bcc c9              RET
         self.assert_pc(0x6bcc);
-        return true;
+        true
     }
     pub(crate) fn hook_6bd1(&mut self) -> bool {
         println!("hook_6bd1");
@@ -4513,7 +4514,7 @@ impl Z80 {
         self.IncPC(3);
         self.increase_cycles(10);
         if (self.data.F & FLAG_Z) != 0 {
-            JP(LAB_ram_723e);
+            return true; // JP(LAB_ram_723e);
         }
 
         //         ram:6f5d fe  11           CP         0x11
@@ -5004,7 +5005,7 @@ impl Z80 {
         self.IncPC(3);
         self.increase_cycles(10);
         if (self.data.F & FLAG_Z) == 0 {
-            JP(LAB_ram_6f76);
+            return true; // JP(LAB_ram_6f76);
         }
 
         //         ram:714b 06  02           LD         B,0x2
@@ -5160,7 +5161,7 @@ impl Z80 {
                 //         ram:7209 c3  76  6f       JP         LAB_ram_6f76
                 self.IncPC(3);
                 self.increase_cycles(10);
-                JP(LAB_ram_6f76);
+                return true; // JP(LAB_ram_6f76);
             }
             // JR(LAB_ram_71ab);
         } else {
@@ -5180,7 +5181,7 @@ impl Z80 {
                 //         ram:7209 c3  76  6f       JP         LAB_ram_6f76
                 self.IncPC(3);
                 self.increase_cycles(10);
-                JP(LAB_ram_6f76);
+                return true; // JP(LAB_ram_6f76);
             } else {
                 //         ram:71a4 18  05           JR         LAB_ram_71ab
                 self.IncPC(2);
@@ -5399,9 +5400,7 @@ impl Z80 {
         //         ram:7204 c3  76  6f       JP         LAB_ram_6f76
         self.IncPC(3);
         self.increase_cycles(10);
-        JP(LAB_ram_6f76);
-
-        //
+        // JP(LAB_ram_6f76)
         true
     }
     pub(crate) fn hook_7335(&mut self) -> bool {
@@ -5946,8 +5945,7 @@ impl Z80 {
         }
 
         //         ram:7536 c9              RET
-        return true;
-        // true
+        true
     }
     pub(crate) fn hook_7537(&mut self) -> bool {
         //         ram:7537 7e              LD         A,(HL)
@@ -1,11 +1,14 @@
 use super::z80_base::{
     sign_extend, tern_op_b, FLAG_3, FLAG_5, FLAG_C, FLAG_S, FLAG_V, FLAG_Z, Z80,
 };
+use super::z80_bus::Bus;
 
 #[allow(non_snake_case, dead_code)]
-impl Z80 {
+impl<B: Bus> Z80<B> {
+    // Cycles of the replaced code, which call_hook counts instead of the
+    // accesses of the hook
     pub(crate) fn increase_cycles(&mut self, n: u64) {
-        self.data.cycles += n;
+        self.hook_cycles += n;
     }
     // adc
     pub(crate) fn instr_hk__ADC_A_B(&mut self) {
@@ -404,7 +407,7 @@ impl Z80 {
     }
     pub(crate) fn instr_hk__LD_A_iNNNN(&mut self, nnnn: u16) {
         self.IncPC(3);
-        self.data.A = self.memory.read_byte(nnnn);
+        self.data.A = self.bus.read_byte(nnnn);
         self.increase_cycles(13);
     }
     pub(crate) fn instr_hk__LD_A_B(&mut self) {
@@ -614,23 +617,23 @@ impl Z80 {
     }
     pub(crate) fn instr_hk__LD_BC_iNNNN(&mut self, mut nnnn: u16) {
         self.IncPC(4);
-        self.data.C = self.memory.read_byte(nnnn);
+        self.data.C = self.bus.read_byte(nnnn);
         nnnn += 1;
-        self.data.B = self.memory.read_byte(nnnn);
+        self.data.B = self.bus.read_byte(nnnn);
         self.increase_cycles(20);
     }
     pub(crate) fn instr_hk__LD_DE_iNNNN(&mut self, mut nnnn: u16) {
         self.IncPC(4);
-        self.data.E = self.memory.read_byte(nnnn);
+        self.data.E = self.bus.read_byte(nnnn);
         nnnn += 1;
-        self.data.D = self.memory.read_byte(nnnn);
+        self.data.D = self.bus.read_byte(nnnn);
         self.increase_cycles(20);
     }
     pub(crate) fn instr_hk__LD_HL_iNNNN(&mut self, mut nnnn: u16) {
         self.IncPC(3);
-        self.data.L = self.memory.read_byte(nnnn);
+        self.data.L = self.bus.read_byte(nnnn);
         nnnn += 1;
-        self.data.H = self.memory.read_byte(nnnn);
+        self.data.H = self.bus.read_byte(nnnn);
         self.increase_cycles(16);
     }
     pub(crate) fn instr_hk__LD_iDE_A(&mut self) {
@@ -667,33 +670,33 @@ impl Z80 {
         self.IncPC(2);
         // self.instr__LD_iHL_NN();
         let address = self.HL();
-        self.memory.write_byte(address, nn);
+        self.bus.write_byte(address, nn);
         self.increase_cycles(10);
     }
     pub(crate) fn instr_hk__LD_iNNNN_BC(&mut self, mut nnnn: u16) {
         self.IncPC(4);
-        self.memory.write_byte(nnnn, self.data.C);
+        self.bus.write_byte(nnnn, self.data.C);
         nnnn += 1;
-        self.memory.write_byte(nnnn, self.data.B);
+        self.bus.write_byte(nnnn, self.data.B);
         self.increase_cycles(20);
     }
     pub(crate) fn instr_hk__LD_iNNNN_DE(&mut self, mut nnnn: u16) {
         self.IncPC(4);
-        self.memory.write_byte(nnnn, self.data.E);
+        self.bus.write_byte(nnnn, self.data.E);
         nnnn += 1;
-        self.memory.write_byte(nnnn, self.data.D);
+        self.bus.write_byte(nnnn, self.data.D);
         self.increase_cycles(20);
     }
     pub(crate) fn instr_hk__LD_iNNNN_HL(&mut self, mut nnnn: u16) {
         self.IncPC(3);
-        self.memory.write_byte(nnnn, self.data.L);
+        self.bus.write_byte(nnnn, self.data.L);
         nnnn += 1;
-        self.memory.write_byte(nnnn, self.data.H);
+        self.bus.write_byte(nnnn, self.data.H);
         self.increase_cycles(16);
     }
     pub(crate) fn instr_hk__LD_iNNNN_A(&mut self, nnnn: u16) {
         self.IncPC(3);
-        self.memory.write_byte(nnnn, self.data.A);
+        self.bus.write_byte(nnnn, self.data.A);
         self.increase_cycles(13);
     }
     pub(crate) fn instr_hk__LD_IX_NNNN(&mut self, nnnn: u16) {
@@ -712,63 +715,49 @@ impl Z80 {
     pub(crate) fn instr_hk__LD_A_iIXpDD(&mut self, nn: u8) {
         self.IncPC(3);
         let offset: u8 = nn;
-        self.data.A = self
-            .memory
-            .read_byte(self.IX() + (sign_extend(offset) as u16));
+        self.data.A = self.bus.read_byte(self.IX() + (sign_extend(offset) as u16));
         self.increase_cycles(19);
     }
     /* LD B,(ix+dd) */
     pub(crate) fn instr_hk__LD_B_iIXpDD(&mut self, nn: u8) {
         self.IncPC(3);
         let offset: u8 = nn;
-        self.data.B = self
-            .memory
-            .read_byte(self.IX() + (sign_extend(offset) as u16));
+        self.data.B = self.bus.read_byte(self.IX() + (sign_extend(offset) as u16));
         self.increase_cycles(19);
     }
     /* LD C,(ix+dd) */
     pub(crate) fn instr_hk__LD_C_iIXpDD(&mut self, nn: u8) {
         self.IncPC(3);
         let offset: u8 = nn;
-        self.data.C = self
-            .memory
-            .read_byte(self.IX() + (sign_extend(offset) as u16));
+        self.data.C = self.bus.read_byte(self.IX() + (sign_extend(offset) as u16));
         self.increase_cycles(19);
     }
     /* LD D,(ix+dd) */
     pub(crate) fn instr_hk__LD_D_iIXpDD(&mut self, nn: u8) {
         self.IncPC(3);
         let offset: u8 = nn;
-        self.data.D = self
-            .memory
-            .read_byte(self.IX() + (sign_extend(offset) as u16));
+        self.data.D = self.bus.read_byte(self.IX() + (sign_extend(offset) as u16));
         self.increase_cycles(19);
     }
     /* LD E,(ix+dd) */
     pub(crate) fn instr_hk__LD_E_iIXpDD(&mut self, nn: u8) {
         self.IncPC(3);
         let offset: u8 = nn;
-        self.data.E = self
-            .memory
-            .read_byte(self.IX() + (sign_extend(offset) as u16));
+        self.data.E = self.bus.read_byte(self.IX() + (sign_extend(offset) as u16));
         self.increase_cycles(19);
     }
     /* LD H,(ix+dd) */
     pub(crate) fn instr_hk__LD_H_iIXpDD(&mut self, nn: u8) {
         self.IncPC(3);
         let offset: u8 = nn;
-        self.data.H = self
-            .memory
-            .read_byte(self.IX() + (sign_extend(offset) as u16));
+        self.data.H = self.bus.read_byte(self.IX() + (sign_extend(offset) as u16));
         self.increase_cycles(19);
     }
     /* LD L,(ix+dd) */
     pub(crate) fn instr_hk__LD_L_iIXpDD(&mut self, nn: u8) {
         self.IncPC(3);
         let offset: u8 = nn;
-        self.data.L = self
-            .memory
-            .read_byte(self.IX() + (sign_extend(offset) as u16));
+        self.data.L = self.bus.read_byte(self.IX() + (sign_extend(offset) as u16));
         self.increase_cycles(19);
     }
     /* LD (ix+dd),A */
@@ -776,7 +765,7 @@ impl Z80 {
     pub(crate) fn instr_hk__LD_iIXpDD_A(&mut self, nn: u8) {
         self.IncPC(3);
         let offset: u8 = nn;
-        self.memory
+        self.bus
             .write_byte(self.IX() + (sign_extend(offset) as u16), self.data.A);
         self.increase_cycles(19);
     }
@@ -784,7 +773,7 @@ impl Z80 {
     pub(crate) fn instr_hk__LD_iIXpDD_B(&mut self, nn: u8) {
         self.IncPC(3);
         let offset: u8 = nn;
-        self.memory
+        self.bus
             .write_byte(self.IX() + (sign_extend(offset) as u16), self.data.B);
         self.increase_cycles(19);
     }
@@ -792,7 +781,7 @@ impl Z80 {
     pub(crate) fn instr_hk__LD_iIXpDD_C(&mut self, nn: u8) {
         self.IncPC(3);
         let offset: u8 = nn;
-        self.memory
+        self.bus
             .write_byte(self.IX() + (sign_extend(offset) as u16), self.data.C);
         self.increase_cycles(19);
     }
@@ -800,7 +789,7 @@ impl Z80 {
     pub(crate) fn instr_hk__LD_iIXpDD_D(&mut self, nn: u8) {
         self.IncPC(3);
         let offset: u8 = nn;
-        self.memory
+        self.bus
             .write_byte(self.IX() + (sign_extend(offset) as u16), self.data.D);
         self.increase_cycles(19);
     }
@@ -808,7 +797,7 @@ impl Z80 {
     pub(crate) fn instr_hk__LD_iIXpDD_E(&mut self, nn: u8) {
         self.IncPC(3);
         let offset: u8 = nn;
-        self.memory
+        self.bus
             .write_byte(self.IX() + (sign_extend(offset) as u16), self.data.E);
         self.increase_cycles(19);
     }
@@ -816,7 +805,7 @@ impl Z80 {
     pub(crate) fn instr_hk__LD_iIXpDD_H(&mut self, nn: u8) {
         self.IncPC(3);
         let offset: u8 = nn;
-        self.memory
+        self.bus
             .write_byte(self.IX() + (sign_extend(offset) as u16), self.data.H);
         self.increase_cycles(19);
     }
@@ -824,7 +813,7 @@ impl Z80 {
     pub(crate) fn instr_hk__LD_iIXpDD_L(&mut self, nn: u8) {
         self.IncPC(3);
         let offset: u8 = nn;
-        self.memory
+        self.bus
             .write_byte(self.IX() + (sign_extend(offset) as u16), self.data.L);
         self.increase_cycles(19);
     }
@@ -835,9 +824,9 @@ impl Z80 {
         // pub(crate) fn instrED__LDDR(&mut self) {
         let mut running = true;
         while running {
-            let mut byte_temp: u8 = self.memory.read_byte(self.HL());
-            self.memory.write_byte(self.DE(), byte_temp);
-            self.memory.contend_write_no_mreq_loop(self.DE(), 1, 2);
+            let mut byte_temp: u8 = self.bus.read_byte(self.HL());
+            self.bus.write_byte(self.DE(), byte_temp);
+            self.contend_write_no_mreq_loop(self.DE(), 1, 2);
             self.DecBC();
             byte_temp = byte_temp.wrapping_add(self.data.A);
             self.data.F = self.data.F & (FLAG_C | FLAG_Z | FLAG_S)
@@ -845,11 +834,11 @@ impl Z80 {
                 | byte_temp & FLAG_3
                 | tern_op_b(byte_temp & 0x02 != 0, FLAG_5, 0);
             if self.BC() != 0 {
-                self.memory.contend_write_no_mreq_loop(self.DE(), 1, 5);
+                self.contend_write_no_mreq_loop(self.DE(), 1, 5);
                 // self.DecPC(2); // do it again
-                self.data.cycles += 23;
+                self.increase_cycles(23);
             } else {
-                self.data.cycles += 18;
+                self.increase_cycles(18);
                 running = false;
             }
             self.DecHL();
@@ -864,9 +853,9 @@ impl Z80 {
         // from instrED__LDIR;
         let mut running = true;
         while running {
-            let mut byte_temp: u8 = self.memory.read_byte(self.HL());
-            self.memory.write_byte(self.DE(), byte_temp);
-            self.memory.contend_write_no_mreq_loop(self.DE(), 1, 2);
+            let mut byte_temp: u8 = self.bus.read_byte(self.HL());
+            self.bus.write_byte(self.DE(), byte_temp);
+            self.contend_write_no_mreq_loop(self.DE(), 1, 2);
             self.DecBC();
             byte_temp = byte_temp.wrapping_add(self.data.A);
             self.data.F = self.data.F & (FLAG_C | FLAG_Z | FLAG_S)
@@ -874,11 +863,11 @@ impl Z80 {
                 | byte_temp & FLAG_3
                 | tern_op_b(byte_temp & 0x02 != 0, FLAG_5, 0);
             if self.BC() != 0 {
-                self.memory.contend_write_no_mreq_loop(self.DE(), 1, 5);
+                self.contend_write_no_mreq_loop(self.DE(), 1, 5);
                 // self.DecPC(2); // do it again
-                self.data.cycles += 23;
+                self.increase_cycles(23);
             } else {
-                self.data.cycles += 18;
+                self.increase_cycles(18);
                 running = false;
             }
             self.IncHL();
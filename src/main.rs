@@ -9,14 +9,20 @@ use macroquad::prelude::*;
 use rsmsx::libs::graphics::GraphicsType;
 use rsmsx::libs::memory::Memory;
 use rsmsx::libs::msx::MSX;
+use rsmsx::libs::msx_bus::MsxBus;
+use rsmsx::libs::palette::Palette;
 use rsmsx::libs::ports::Ports;
 use rsmsx::libs::ppi::PPI;
 use rsmsx::libs::psg::PSG;
+use rsmsx::libs::rom_database::{RomDatabase, XML_DATABASE};
 use rsmsx::libs::sound::SoundType;
-use rsmsx::libs::vdp::Vdp;
+use rsmsx::libs::timing::Timing;
+use rsmsx::libs::vdp::{Vdp, VdpModel};
 use rsmsx::libs::z80::z80_base::Z80;
 
 const SYSTEM_ROM_FILE: &str = "cbios_main_msx1.rom";
+const SYSTEM_ROM_FILE_MSX2: &str = "cbios_main_msx2.rom";
+const SUB_ROM_FILE: &str = "cbios_sub.rom";
 
 #[derive(Args, Debug)]
 ///rsmsx 0.1.0
@@ -26,16 +32,40 @@ struct MyArgs {
     ///ROM in SLOT 1
     cart: String,
 
+    #[arg(long)]
+    ///ROM in SLOT 2
+    cart2: String,
+
     ///System file
     #[arg(long = "sys")]
     system_rom: String,
 
+    #[arg(long)]
+    ///MSX2 machine with a V9938 VDP
+    msx2: bool,
+
+    #[arg(long = "subrom")]
+    ///MSX2 sub ROM, in SLOT 3-1
+    sub_rom: String,
+
     #[arg(long, default_value = "true")]
     ///Best quality rendering
     quality: bool,
 
-    #[arg(long = "fint", default_value = "16")]
-    ///Frame interval in milliseconds
+    #[arg(long)]
+    ///Show the whole border around the screen
+    overscan: bool,
+
+    #[arg(long)]
+    ///Colours of the TMS9918: ntsc (default), pal, v9938, grey or a palette file
+    palette: String,
+
+    #[arg(long)]
+    ///Video timing: ntsc (60Hz, default) or pal (50Hz)
+    video: String,
+
+    #[arg(long = "fint")]
+    ///Frame interval in milliseconds, by default the frame rate of the video timing
     /// The `frame_interval` variable in the code is used to specify the interval in milliseconds
     /// between frames in the main game loop. This interval determines how often the game logic and
     /// rendering are updated. In this case, the `frame_interval` value is provided as a command-line
@@ -43,8 +73,17 @@ struct MyArgs {
     frame_interval: u32,
 
     #[arg(long)]
-    ///Mapper type (KONAMI4...)
+    ///Mapper type (NORMAL, KONAMI4, KONAMI5, ASCII8KB, ASCII16KB, RTYPE...)
+    /// Overrides the softwaredb.xml lookup and the mapper guess
     mtype: String,
+
+    #[arg(long)]
+    ///Mapper type of the ROM in SLOT 2
+    mtype2: String,
+
+    #[arg(long)]
+    ///ROM database (softwaredb.xml)
+    romdb: String,
 }
 
 static MY_LOGGER: MyLogger = MyLogger;
@@ -77,19 +116,70 @@ async fn main() {
     match MyArgs::from_args(args.iter().map(|x| x.as_str())) {
         Ok(mut args) => {
             if args.system_rom.is_empty() {
-                args.system_rom = SYSTEM_ROM_FILE.to_string();
+                args.system_rom = if args.msx2 {
+                    SYSTEM_ROM_FILE_MSX2.to_string()
+                } else {
+                    SYSTEM_ROM_FILE.to_string()
+                };
+            }
+            if args.msx2 && args.sub_rom.is_empty() {
+                args.sub_rom = SUB_ROM_FILE.to_string();
             }
             let ppi = Rc::new(RefCell::new(PPI::new()));
             let mut memory = Memory::new(ppi.clone());
-            memory.load_bios_basic(&args.system_rom);
+            if args.romdb.is_empty() {
+                args.romdb = XML_DATABASE.to_string();
+            }
+            match RomDatabase::load(&args.romdb) {
+                Ok(db) => memory.set_rom_database(Rc::new(db)),
+                Err(err) => log::warn!("Cannot load ROM database: {}", err),
+            }
+            if let Err(err) = memory.load_bios_basic(&args.system_rom) {
+                log::warn!("Cannot load system ROM: {}", err);
+                return;
+            }
+            if args.msx2 {
+                // The RAM stays in slot 3-0
+                memory.set_slot_expanded(3, true);
+                if let Err(err) = memory.load_sub_rom(&args.sub_rom, 3, 1) {
+                    log::warn!("Cannot load sub ROM: {}", err);
+                    return;
+                }
+            }
             if !args.cart.is_empty() {
-                memory.load_rom(&args.cart, 1, &args.mtype);
+                if let Err(err) = memory.load_rom(&args.cart, 1, &args.mtype) {
+                    log::warn!("Cannot load cartridge: {}", err);
+                }
+            }
+            if !args.cart2.is_empty() {
+                if let Err(err) = memory.load_rom(&args.cart2, 2, &args.mtype2) {
+                    log::warn!("Cannot load cartridge: {}", err);
+                }
+            }
+            let mut psg = PSG::new(SoundType::Normal);
+            if let Some(scc) = memory.get_scc() {
+                psg.attach_scc(scc);
             }
-            let psg = PSG::new(SoundType::Normal);
             let vdp = Rc::new(RefCell::new(Vdp::new(GraphicsType::Normal, args.quality)));
+            if args.msx2 {
+                vdp.borrow_mut().set_model(VdpModel::V9938);
+            }
+            if !args.video.is_empty() {
+                match Timing::parse(&args.video) {
+                    Ok(timing) => vdp.borrow_mut().set_timing(timing),
+                    Err(err) => log::warn!("Cannot set video timing: {}", err),
+                }
+            }
             vdp.borrow_mut().init_graphics();
+            vdp.borrow_mut().set_overscan(args.overscan);
+            if !args.palette.is_empty() {
+                match Palette::find(&args.palette) {
+                    Ok(palette) => vdp.borrow_mut().set_palette(&palette),
+                    Err(err) => log::warn!("Cannot load palette: {}", err),
+                }
+            }
             let ports = Ports::new(vdp.clone(), ppi.clone(), psg);
-            let mut cpu_z80 = Z80::new(memory, ports);
+            let mut cpu_z80 = Z80::new(MsxBus::new(memory, ports));
             cpu_z80.reset();
             cpu_z80.SetPC(0);
             let mut msx = MSX::new(
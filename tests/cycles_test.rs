@@ -1,8 +1,8 @@
 use std::{cell::RefCell, rc::Rc};
 
 use rsmsx::libs::{
-    graphics::GraphicsType, memory::Memory, ports::Ports, ppi::PPI, psg::PSG, sound::SoundType,
-    vdp::Vdp, z80::z80_base::Z80,
+    graphics::GraphicsType, memory::Memory, msx_bus::MsxBus, ports::Ports, ppi::PPI, psg::PSG,
+    sound::SoundType, vdp::Vdp, z80::z80_base::Z80,
 };
 
 #[test]
@@ -35,6 +35,24 @@ fn test3() {
     assert_eq!(nc, 103);
 }
 
+#[test]
+fn test4() {
+    // LD IX, 0x100       (16 cycles)
+    // LD (IX+2), 0x55    (21 cycles)
+    // BIT 7, (IX+2)      (22 cycles)
+    // NEG                (10 cycles)
+    // RLC B              (10 cycles)
+    // OUT (C), A         (14 cycles)
+    // OUT (0x98), A      (12 cycles)
+    // HALT               (5 cycles)
+    let ar = [
+        0xdd, 0x21, 0x00, 0x01, 0xdd, 0x36, 0x02, 0x55, 0xdd, 0xcb, 0x02, 0x7e, 0xed, 0x44, 0xcb,
+        0x00, 0xed, 0x79, 0xd3, 0x98, 0x76,
+    ];
+    let nc = check_cycles(&ar);
+    assert_eq!(nc, 110);
+}
+
 fn check_cycles(ar: &[u8]) -> isize {
     let ppi = Rc::new(RefCell::new(PPI::new()));
     let mut memory = Memory::new(ppi.clone());
@@ -44,7 +62,7 @@ fn check_cycles(ar: &[u8]) -> isize {
     let psg = PSG::new(SoundType::None);
     let vdp = Rc::new(RefCell::new(Vdp::new(GraphicsType::None, false)));
     let ports = Ports::new(vdp.clone(), ppi.clone(), psg);
-    let mut cpu_z80 = Z80::new(memory, ports);
+    let mut cpu_z80 = Z80::new(MsxBus::new(memory, ports));
     cpu_z80.reset();
     cpu_z80.SetPC(0);
     cpu_z80.reset_cycles();
@@ -0,0 +1,211 @@
+use rsmsx::libs::{
+    cartridges::{
+        get_cart_type, get_rom_hash, guess_cart_type, CartType, MapperASCII16, MapperASCII8,
+        MapperASCII8SRAM, MapperGameMaster2, MapperKonami4, MapperKonami5, MapperRType,
+    },
+    memory::Mapper,
+    rom_database::RomDatabase,
+};
+
+// ROM where every byte holds the number of its 16KB bank
+fn banked_rom(banks: usize) -> Vec<u8> {
+    (0..banks * 0x4000).map(|i| (i / 0x4000) as u8).collect()
+}
+
+// ROM where every byte holds the number of its 8KB bank
+fn banked_rom_8k(banks: usize) -> Vec<u8> {
+    (0..banks * 0x2000).map(|i| (i / 0x2000) as u8).collect()
+}
+
+#[test]
+fn ascii16_switches_both_pages() {
+    let mut mapper = MapperASCII16::new();
+    mapper.init(&banked_rom(8)).unwrap();
+    assert_eq!(mapper.read_byte(0x4000), 0);
+    assert_eq!(mapper.read_byte(0x8000), 0);
+
+    mapper.write_byte(0x6000, 3);
+    mapper.write_byte(0x77ff, 5);
+    assert_eq!(mapper.read_byte(0x7fff), 3);
+    assert_eq!(mapper.read_byte(0x8000), 5);
+
+    // Out of range banks wrap around
+    mapper.write_byte(0x6000, 9);
+    assert_eq!(mapper.read_byte(0x4000), 1);
+}
+
+#[test]
+fn roms_smaller_than_a_bank_are_rejected() {
+    assert!(MapperASCII16::new().init(&[0; 0x2000]).is_err());
+    assert!(MapperRType::new().init(&[0; 0x3fff]).is_err());
+    assert!(MapperASCII8SRAM::new(0x2000).init(&[0; 0x1000]).is_err());
+}
+
+#[test]
+fn ascii8_reaches_all_256_banks() {
+    let rom: Vec<u8> = (0..256 * 0x2000).map(|i| (i / 0x2000) as u8).collect();
+    let mut mapper = MapperASCII8::new();
+    mapper.init(&rom).unwrap();
+    mapper.write_byte(0x6000, 0xff);
+    assert_eq!(mapper.read_byte(0x4000), 0xff);
+    mapper.write_byte(0x7800, 0x80);
+    assert_eq!(mapper.read_byte(0xa000), 0x80);
+}
+
+#[test]
+fn rtype_has_fixed_first_page() {
+    let mut mapper = MapperRType::new();
+    mapper.init(&banked_rom(24)).unwrap();
+    assert_eq!(mapper.read_byte(0x4000), 0x17);
+    assert_eq!(mapper.read_byte(0x8000), 0);
+
+    mapper.write_byte(0x7000, 0x0a);
+    assert_eq!(mapper.read_byte(0xbfff), 0x0a);
+    assert_eq!(mapper.read_byte(0x4000), 0x17);
+
+    // Bit 4 set: only banks 0x10-0x17 are reachable
+    mapper.write_byte(0x7fff, 0x1f);
+    assert_eq!(mapper.read_byte(0x8000), 0x17);
+
+    // Writes outside the bank register are ignored
+    mapper.write_byte(0x6000, 0x03);
+    assert_eq!(mapper.read_byte(0x8000), 0x17);
+}
+
+#[test]
+fn ascii8_sram_is_mapped_and_writable_in_upper_pages() {
+    // 16 banks of 8KB: SRAM enable bit is 0x10
+    let mut mapper = MapperASCII8SRAM::new(0x2000);
+    mapper.init(&vec![0; 16 * 0x2000]).unwrap();
+
+    mapper.write_byte(0x7000, 0x10);
+    mapper.write_byte(0x8123, 0x42);
+    assert_eq!(mapper.read_byte(0x8123), 0x42);
+    assert_eq!(mapper.get_sram().unwrap()[0x123], 0x42);
+
+    // Not writable at 0x4000-0x5FFF on non-Koei boards
+    mapper.write_byte(0x6000, 0x10);
+    mapper.write_byte(0x4123, 0x99);
+    assert_eq!(mapper.read_byte(0x4123), 0x42);
+
+    // Back to ROM
+    mapper.write_byte(0x7000, 0x01);
+    assert_eq!(mapper.read_byte(0x8123), 0x00);
+}
+
+#[test]
+fn game_master2_sram_halves() {
+    let mut mapper = MapperGameMaster2::new();
+    mapper.init(&vec![0; 16 * 0x2000]).unwrap();
+    mapper.set_sram(&[0; 0x2000]);
+
+    mapper.write_byte(0xa000, 0x30);
+    mapper.write_byte(0xb010, 0x77);
+    assert_eq!(mapper.read_byte(0xa010), 0x77);
+    assert_eq!(mapper.get_sram().unwrap()[0x1010], 0x77);
+
+    mapper.write_byte(0xa000, 0x10);
+    assert_eq!(mapper.read_byte(0xa010), 0x00);
+}
+
+#[test]
+fn konami5_scc_window() {
+    let mut mapper = MapperKonami5::new();
+    mapper.init(&vec![0; 64 * 0x2000]).unwrap();
+
+    // SCC is only visible with 0x3F in the third bank register
+    mapper.write_byte(0x9000, 0x3f);
+    mapper.write_byte(0x9800, 0x80);
+    mapper.write_byte(0x987f, 0x7f);
+    assert_eq!(mapper.read_byte(0x9800), 0x80);
+    // Registers are mirrored every 0x100 bytes
+    assert_eq!(mapper.read_byte(0x9900), 0x80);
+    // Channel 5 reads back the waveform of channel 4
+    assert_eq!(mapper.read_byte(0x98bf), 0x7f);
+
+    let scc = mapper.get_scc().unwrap();
+    mapper.write_byte(0x9880, 0xfe);
+    mapper.write_byte(0x9881, 0x01);
+    let freq = scc.borrow().get_frequency(0);
+    assert!((freq - 3579545.0 / (32.0 * 511.0)).abs() < 0.01);
+}
+
+#[test]
+fn konami4_banks_wrap() {
+    let mut mapper = MapperKonami4::new();
+    mapper.init(&banked_rom_8k(8)).unwrap();
+
+    mapper.write_byte(0x6000, 0x0b);
+    assert_eq!(mapper.read_byte(0x6000), 3);
+    mapper.write_byte(0xa000, 0xff);
+    assert_eq!(mapper.read_byte(0xa000), 7);
+    // The first page is fixed
+    mapper.write_byte(0x4000, 0x05);
+    assert_eq!(mapper.read_byte(0x4000), 0);
+}
+
+#[test]
+fn konami5_banks_wrap_and_keep_the_scc() {
+    let mut mapper = MapperKonami5::new();
+    mapper.init(&banked_rom_8k(8)).unwrap();
+
+    mapper.write_byte(0x5000, 0x0a);
+    assert_eq!(mapper.read_byte(0x4000), 2);
+    // 0x3F selects bank 7 and still enables the SCC
+    mapper.write_byte(0x9000, 0x3f);
+    assert_eq!(mapper.read_byte(0x8000), 7);
+    mapper.write_byte(0x9800, 0x55);
+    assert_eq!(mapper.read_byte(0x9800), 0x55);
+
+    // Only the bank register windows switch banks
+    mapper.write_byte(0x6000, 0x03);
+    mapper.write_byte(0xa000, 0x03);
+    assert_eq!(mapper.read_byte(0x6000), 1);
+    assert_eq!(mapper.read_byte(0xa000), 3);
+    mapper.write_byte(0xb000, 0x05);
+    assert_eq!(mapper.read_byte(0xa000), 5);
+}
+
+// 128KB ROM with an "AB" header and LD (nnnn),A to the given addresses
+fn rom_writing_to(addresses: &[u16]) -> Vec<u8> {
+    let mut rom = vec![0; 0x20000];
+    rom[0] = b'A';
+    rom[1] = b'B';
+    for (i, address) in addresses.iter().enumerate() {
+        let [l, h] = address.to_le_bytes();
+        rom[0x100 + i * 3..0x100 + i * 3 + 3].copy_from_slice(&[0x32, l, h]);
+    }
+    rom
+}
+
+#[test]
+fn guess_mapper_from_bank_writes() {
+    let (cart_type, confidence) = guess_cart_type(&rom_writing_to(&[0x5000, 0x7000, 0x9000]));
+    assert_eq!(cart_type, CartType::KONAMI5);
+    assert!(confidence > 0.5);
+
+    let (cart_type, _) = guess_cart_type(&rom_writing_to(&[0x6000, 0x6800, 0x7000, 0x7800]));
+    assert_eq!(cart_type, CartType::ASCII8KB);
+
+    let (cart_type, _) = guess_cart_type(&rom_writing_to(&[0x6000, 0x77ff, 0x7000]));
+    assert_eq!(cart_type, CartType::ASCII16KB);
+
+    let (cart_type, _) = guess_cart_type(&rom_writing_to(&[0x8000, 0xa000, 0x6000]));
+    assert_eq!(cart_type, CartType::KONAMI4);
+
+    let (cart_type, confidence) = guess_cart_type(&[0; 0x8000]);
+    assert_eq!(cart_type, CartType::NORMAL);
+    assert_eq!(confidence, 1.0);
+}
+
+#[test]
+fn unsupported_database_mappers_are_guessed() {
+    let rom = rom_writing_to(&[0x5000, 0x7000, 0x9000]);
+    let db = RomDatabase::parse(&format!(
+        "<softwaredb><software><title>T</title><dump><megarom>\
+         <type>GenericKonami</type><hash>{}</hash></megarom></dump></software></softwaredb>",
+        get_rom_hash(&rom)
+    ))
+    .unwrap();
+    assert_eq!(get_cart_type(&rom, &db), CartType::KONAMI5);
+}
@@ -0,0 +1,45 @@
+use rsmsx::libs::palette::Palette;
+
+#[test]
+fn builtin_palettes_are_found_by_name() {
+    let names: Vec<String> = Palette::builtin().into_iter().map(|p| p.name).collect();
+    assert_eq!(names, ["ntsc", "pal", "v9938", "grey"]);
+    assert_eq!(Palette::find("pal").unwrap(), Palette::pal());
+    assert_eq!(Palette::ntsc().colors[2], (0x3e, 0xb8, 0x49));
+    assert_eq!(Palette::v9938().colors[15], (255, 255, 255));
+    assert_eq!(Palette::v9938().colors[4], (36, 36, 255));
+}
+
+#[test]
+fn greyscale_keeps_the_luminance() {
+    let grey = Palette::greyscale();
+    assert!(grey.colors.iter().all(|(r, g, b)| r == g && g == b));
+    assert_eq!(grey.colors[15], (255, 255, 255));
+    // Light green is brighter than dark blue
+    assert!(grey.colors[3].0 > grey.colors[4].0);
+}
+
+#[test]
+fn palette_files_are_parsed() {
+    let mut content = String::from("; my palette\n\n");
+    for i in 0..16 {
+        content.push_str(&format!("#{:02x}{:02x}{:02x}\n", i * 16, i, 255 - i));
+    }
+    let palette = Palette::parse("mine", &content).unwrap();
+    assert_eq!(palette.name, "mine");
+    assert_eq!(palette.colors[1], (16, 1, 254));
+
+    let path = std::env::temp_dir().join("rsmsx_palette_test.txt");
+    std::fs::write(&path, content.replace('#', "")).unwrap();
+    let loaded = Palette::find(path.to_str().unwrap()).unwrap();
+    assert_eq!(loaded.colors, palette.colors);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn bad_palette_files_are_rejected() {
+    assert!(Palette::parse("short", "000000\nffffff\n").is_err());
+    let err = Palette::parse("bad", "00zz00\n").unwrap_err();
+    assert_eq!(err, "bad:1: bad colour 00zz00");
+    assert!(Palette::find("no such palette").is_err());
+}
@@ -0,0 +1,154 @@
+use std::{cell::RefCell, rc::Rc};
+
+use rsmsx::libs::{
+    audio_mixer::{AudioMixer, SoundChip, Z80_CLOCK},
+    ay8910::{AY8910, CYCLES_PER_TICK},
+    psg::PSG,
+    sound::{SampleRing, SoundDriver, SoundType, FREQUENCY},
+};
+
+// Output of the next chip tick
+fn tick(ay: &mut AY8910) -> f32 {
+    ay.advance(CYCLES_PER_TICK);
+    ay.take_sample()
+}
+
+// Envelope volume after every step, with the shortest period
+fn envelope(shape: u8, steps: usize) -> Vec<u8> {
+    let mut ay = AY8910::new();
+    ay.write_register(11, 1);
+    ay.write_register(13, shape);
+    let mut volumes = vec![ay.get_envelope_volume()];
+    for _ in 0..steps {
+        tick(&mut ay);
+        tick(&mut ay);
+        volumes.push(ay.get_envelope_volume());
+    }
+    volumes
+}
+
+#[test]
+fn envelope_shapes() {
+    let decay: Vec<u8> = (0..16).rev().collect();
+    let attack: Vec<u8> = (0..16).collect();
+
+    // \___
+    let v = envelope(0x00, 31);
+    assert_eq!(v[..16], decay[..]);
+    assert!(v[16..].iter().all(|x| *x == 0));
+    // /___
+    let v = envelope(0x04, 31);
+    assert_eq!(v[..16], attack[..]);
+    assert!(v[16..].iter().all(|x| *x == 0));
+    // \\\\
+    let v = envelope(0x08, 31);
+    assert_eq!(v[16..], decay[..]);
+    // \/\/
+    let v = envelope(0x0a, 31);
+    assert_eq!(v[16..], attack[..]);
+    // \```
+    let v = envelope(0x0b, 31);
+    assert!(v[16..].iter().all(|x| *x == 15));
+    // /```
+    let v = envelope(0x0d, 31);
+    assert_eq!(v[..16], attack[..]);
+    assert!(v[16..].iter().all(|x| *x == 15));
+    // /\/\
+    let v = envelope(0x0e, 31);
+    assert_eq!(v[16..], decay[..]);
+}
+
+#[test]
+fn mixer_selects_tone_and_noise() {
+    // Everything disabled: constant level
+    let mut ay = AY8910::new();
+    ay.write_register(8, 15);
+    let samples: Vec<f32> = (0..100).map(|_| tick(&mut ay)).collect();
+    assert!(samples[0] > 0.0);
+    assert!(samples.windows(2).all(|w| w[0] == w[1]));
+
+    // Noise only on channel A
+    ay.write_register(7, 0b11110111);
+    let samples: Vec<f32> = (0..1000).map(|_| tick(&mut ay)).collect();
+    let changes = samples.windows(2).filter(|w| w[0] != w[1]).count();
+    assert!(changes > 100);
+
+    // Tone only: a period of 4 ticks toggles every 4 ticks
+    let mut ay = AY8910::new();
+    ay.write_register(0, 4);
+    ay.write_register(8, 15);
+    ay.write_register(7, 0b11111110);
+    let samples: Vec<f32> = (0..12).map(|_| tick(&mut ay)).collect();
+    assert_eq!(samples[0], samples[3]);
+    assert_ne!(samples[3], samples[4]);
+    assert_eq!(samples[4], samples[7]);
+    assert_ne!(samples[7], samples[8]);
+}
+
+// Keeps what the mixer queues
+struct Recorder {
+    samples: Vec<f32>,
+}
+
+impl SoundDriver for Recorder {
+    fn queue_samples(&mut self, samples: &[f32]) -> Result<String, String> {
+        self.samples.extend_from_slice(samples);
+        Ok("recorded".to_string())
+    }
+    fn queued(&self) -> usize {
+        self.samples.len()
+    }
+    fn pause(&mut self) -> Result<String, String> {
+        Ok("recorded".to_string())
+    }
+    fn play(&mut self) -> Result<String, String> {
+        Ok("recorded".to_string())
+    }
+}
+
+#[test]
+fn mixer_follows_cpu_cycles() {
+    let recorder = Rc::new(RefCell::new(Recorder {
+        samples: Vec::new(),
+    }));
+    let mut mixer = AudioMixer::new(recorder.clone());
+    let mut ay = AY8910::new();
+    ay.write_register(0, 100);
+    ay.write_register(8, 15);
+    ay.write_register(7, 0b11111110);
+
+    // One second of 11 cycle instructions
+    for _ in 0..(Z80_CLOCK as u32 / 11) {
+        mixer.run(11, &mut [&mut ay]);
+    }
+    let samples = &recorder.borrow().samples;
+    // The queue grows past its target, so the rate slows down a bit
+    let expected = FREQUENCY as usize;
+    assert!(samples.len() < expected);
+    assert!(samples.len() > expected * 99 / 100);
+    assert!(samples.iter().any(|x| *x > 0.1));
+    assert!(samples.iter().any(|x| *x < -0.1));
+}
+
+#[test]
+fn ring_drops_what_does_not_fit() {
+    let mut ring = SampleRing::new(4);
+    ring.push(&[0.1, 0.2, 0.3]);
+    ring.push(&[0.4, 0.5]);
+    assert_eq!(ring.len(), 4);
+    assert_eq!(ring.pop(), Some(0.1));
+    ring.push(&[0.6]);
+    let rest: Vec<f32> = std::iter::from_fn(|| ring.pop()).collect();
+    assert_eq!(rest, vec![0.2, 0.3, 0.4, 0.6]);
+    assert!(ring.is_empty());
+}
+
+#[test]
+fn registers_read_back_masked() {
+    let mut psg = PSG::new(SoundType::None);
+    for (reg, mask) in [(1, 0x0f), (6, 0x1f), (8, 0x1f), (13, 0x0f), (0, 0xff)] {
+        psg.write_port(0xa0, reg);
+        psg.write_port(0xa1, 0xff);
+        assert_eq!(psg.read_port(0xa2), mask);
+    }
+}
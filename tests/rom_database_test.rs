@@ -0,0 +1,35 @@
+use rsmsx::libs::rom_database::{RomDatabase, XML_DATABASE};
+
+#[test]
+fn loads_softwaredb_entries() {
+    let db = RomDatabase::load(XML_DATABASE).unwrap();
+    assert!(db.len() > 3000);
+
+    let entry = db.find("fe74b4df9698a61dffd3ac88f47619675514ba1c").unwrap();
+    assert_eq!(entry.title, "Konami's Game Master 2");
+    assert_eq!(entry.company, "Konami");
+    assert_eq!(entry.year, "1987");
+    assert_eq!(entry.kind, "megarom");
+    assert_eq!(entry.mapper, "GameMaster2");
+    assert_eq!(entry.original, None);
+
+    let entry = db.find("9a63d48c4fc05e76c9de25e5c5f2a0c99b5d1be4").unwrap();
+    assert_eq!(entry.start, Some(0x8000));
+    assert_eq!(entry.original, Some(true));
+    assert_eq!(entry.dump_status, "GoodMSX");
+
+    assert!(!db.search_title("game master").is_empty());
+    assert!(db.find("0000").is_none());
+}
+
+#[test]
+fn plain_roms_default_to_normal() {
+    let db = RomDatabase::parse(
+        "<softwaredb><software><title>T</title>\
+         <dump><rom><hash>abcd</hash></rom></dump></software></softwaredb>",
+    )
+    .unwrap();
+    let entry = db.find("abcd").unwrap();
+    assert_eq!(entry.title, "T");
+    assert_eq!(entry.mapper, "NORMAL");
+}
@@ -0,0 +1,65 @@
+use rsmsx::libs::cartridges::{get_rom_image, get_rom_start, RomHeader};
+
+// ROM of `size` bytes with an "AB" header at `offset`
+fn rom(size: usize, offset: usize, init: u16, text: u16) -> Vec<u8> {
+    let mut data: Vec<u8> = (0..size).map(|i| (i / 0x2000) as u8).collect();
+    data[offset..offset + 2].copy_from_slice(b"AB");
+    data[offset + 2..offset + 4].copy_from_slice(&init.to_le_bytes());
+    data[offset + 4..offset + 8].fill(0);
+    data[offset + 8..offset + 10].copy_from_slice(&text.to_le_bytes());
+    data
+}
+
+#[test]
+fn header_is_parsed() {
+    let data = rom(0x8000, 0, 0x4010, 0);
+    let header = RomHeader::parse(&data).unwrap();
+    assert_eq!(header.offset, 0);
+    assert_eq!(header.init, 0x4010);
+    assert!(!header.is_basic());
+    assert_eq!(RomHeader::parse(&[0; 0x4000]), None);
+}
+
+#[test]
+fn start_follows_init_address() {
+    assert_eq!(get_rom_start(&rom(0x2000, 0, 0x4010, 0), None), 0x4000);
+    assert_eq!(get_rom_start(&rom(0x4000, 0, 0x8010, 0), None), 0x8000);
+    assert_eq!(get_rom_start(&rom(0x8000, 0, 0x7ff0, 0), None), 0x4000);
+    // Header in the second 16KB
+    assert_eq!(get_rom_start(&rom(0xc000, 0x4000, 0x4010, 0), None), 0x0000);
+    // BASIC ROM
+    assert_eq!(get_rom_start(&rom(0x4000, 0, 0, 0x8010), None), 0x8000);
+    // The database wins
+    assert_eq!(
+        get_rom_start(&rom(0x4000, 0, 0x4010, 0), Some(0x8000)),
+        0x8000
+    );
+}
+
+#[test]
+fn small_roms_are_mirrored() {
+    let (image, pages) = get_rom_image(&rom(0x2000, 0, 0x4010, 0), 0x4000);
+    assert_eq!(pages, [true; 4]);
+    assert_eq!(&image[0x4000..0x4002], b"AB");
+    assert_eq!(&image[0x6000..0x6002], b"AB");
+    assert_eq!(&image[0x0000..0x0002], b"AB");
+
+    let (image, _) = get_rom_image(&rom(0x8000, 0, 0x4010, 0), 0x4000);
+    assert_eq!(&image[0x4000..0x4002], b"AB");
+    assert_eq!(image[0xa000], 3);
+    assert_eq!(image[0x0000], 2);
+    assert_eq!(&image[0xc000..0xc002], b"AB");
+
+    // 24KB: the missing 8KB reads 0xFF
+    let (image, _) = get_rom_image(&rom(0x6000, 0, 0x4010, 0), 0x4000);
+    assert_eq!(image[0x8000], 2);
+    assert_eq!(image[0xa000], 0xff);
+}
+
+#[test]
+fn large_roms_are_not_mirrored() {
+    let (image, pages) = get_rom_image(&rom(0xc000, 0x4000, 0x4010, 0), 0x0000);
+    assert_eq!(pages, [true, true, true, false]);
+    assert_eq!(&image[0x4000..0x4002], b"AB");
+    assert_eq!(image[0xa000], 5);
+}
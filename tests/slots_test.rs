@@ -0,0 +1,143 @@
+use std::{cell::RefCell, rc::Rc};
+
+use rsmsx::libs::{
+    cartridges::{MapperKonami4, MapperKonami5},
+    memory::{Memory, MemoryData},
+    ppi::PPI,
+};
+
+fn new_memory() -> (Memory, Rc<RefCell<PPI>>) {
+    let ppi = Rc::new(RefCell::new(PPI::new()));
+    (Memory::new(ppi.clone()), ppi)
+}
+
+#[test]
+fn subslot_register_reads_back_inverted() {
+    let (mut memory, ppi) = new_memory();
+    memory.set_slot_expanded(3, true);
+    // All pages in primary slot 3
+    ppi.borrow_mut().write_port(0xa8, 0xff);
+
+    memory.write_byte(0xffff, 0b1110_0100);
+    assert_eq!(memory.read_byte(0xffff), 0b0001_1011);
+    assert_eq!(memory.get_slot(0), (3, 0));
+    assert_eq!(memory.get_slot(1), (3, 1));
+    assert_eq!(memory.get_slot(2), (3, 2));
+    assert_eq!(memory.get_slot(3), (3, 3));
+}
+
+#[test]
+fn subslots_have_separate_contents() {
+    let (mut memory, ppi) = new_memory();
+    memory.set_slot_expanded(3, true);
+    ppi.borrow_mut().write_port(0xa8, 0xff);
+
+    let rom = vec![0xaa; 0x4000];
+    memory.load_subslot(&rom, 1, 3, 2);
+
+    // Page 1 -> subslot 0 (RAM)
+    memory.write_byte(0xffff, 0x00);
+    memory.write_byte(0x4000, 0x12);
+    assert_eq!(memory.read_byte(0x4000), 0x12);
+
+    // Page 1 -> subslot 2 (ROM)
+    memory.write_byte(0xffff, 0x08);
+    memory.write_byte(0x4000, 0x34);
+    assert_eq!(memory.read_byte(0x4000), 0xaa);
+
+    memory.write_byte(0xffff, 0x00);
+    assert_eq!(memory.read_byte(0x4000), 0x12);
+}
+
+#[test]
+fn unexpanded_slot_has_plain_ram_at_ffff() {
+    let (mut memory, ppi) = new_memory();
+    ppi.borrow_mut().write_port(0xa8, 0xc0);
+    memory.write_byte(0xffff, 0x5a);
+    assert_eq!(memory.read_byte(0xffff), 0x5a);
+}
+
+#[test]
+fn each_slot_routes_to_its_own_mapper() {
+    let (mut memory, ppi) = new_memory();
+    let mut rom1 = vec![0x11; 0x20000];
+    let mut rom2 = vec![0x22; 0x20000];
+    rom1[0x2000 * 5] = 0x15;
+    rom2[0x2000 * 6] = 0x26;
+
+    let mut mapper1 = MapperKonami4::new();
+    mapper1.init(&rom1).unwrap();
+    memory.set_mapper(Rc::new(RefCell::new(mapper1)), 1);
+    let mut mapper2 = MapperKonami4::new();
+    mapper2.init(&rom2).unwrap();
+    memory.set_mapper(Rc::new(RefCell::new(mapper2)), 2);
+
+    // Page 2 in slot 1
+    ppi.borrow_mut().write_port(0xa8, 0x10);
+    memory.write_byte(0x8000, 5);
+    assert_eq!(memory.read_byte(0x8000), 0x15);
+
+    // Page 2 in slot 2, bank registers are independent
+    ppi.borrow_mut().write_port(0xa8, 0x20);
+    assert_eq!(memory.read_byte(0x8000), 0x22);
+    memory.write_byte(0x8000, 6);
+    assert_eq!(memory.read_byte(0x8000), 0x26);
+
+    ppi.borrow_mut().write_port(0xa8, 0x10);
+    assert_eq!(memory.read_byte(0x8000), 0x15);
+}
+
+#[test]
+fn cartridges_load_or_report_an_error() {
+    let (mut memory, _) = new_memory();
+    assert!(memory.load_rom("no/such/cartridge.rom", 1, "").is_err());
+
+    let path = std::env::temp_dir().join("rsmsx_forced_type.rom");
+    std::fs::write(&path, vec![0; 0x20000]).unwrap();
+    let result = memory.load_rom(path.to_str().unwrap(), 1, "Konami");
+    std::fs::remove_file(&path).unwrap();
+    assert!(result.is_ok());
+}
+
+#[test]
+fn missing_system_roms_report_an_error() {
+    let (mut memory, _) = new_memory();
+    assert!(memory.load_bios_basic("no/such/bios.rom").is_err());
+    assert!(memory.load_sub_rom("no/such/sub.rom", 3, 1).is_err());
+}
+
+#[test]
+fn save_data_without_subslots_still_loads() {
+    let (mut memory, _) = new_memory();
+    // One page per slot and no secondary slot registers
+    let mut contents = vec![0_u8; 4 * 4 * 0x4000];
+    // Page 1 of slot 0
+    contents[4 * 0x4000] = 0x42;
+    let json = serde_json::json!({
+        "contents": contents,
+        "can_write": vec![true; 16],
+        "slot_mapper": 0,
+    });
+    let data: MemoryData = serde_json::from_value(json).unwrap();
+    memory.set_data(data);
+    assert_eq!(memory.read_byte(0x4000), 0x42);
+}
+
+#[test]
+fn scc_writes_are_seen_through_the_slots() {
+    let (mut memory, ppi) = new_memory();
+    let mut mapper = MapperKonami5::new();
+    mapper.init(&vec![0; 0x20000]).unwrap();
+    memory.set_mapper(Rc::new(RefCell::new(mapper)), 1);
+
+    // Page 2 in slot 1
+    ppi.borrow_mut().write_port(0xa8, 0x10);
+    assert!(!memory.writes_sound(0x9800));
+    memory.write_byte(0x9000, 0x3f);
+    assert!(memory.writes_sound(0x9800));
+    assert!(!memory.writes_sound(0xa000));
+
+    // Page 2 back in slot 0
+    ppi.borrow_mut().write_port(0xa8, 0x00);
+    assert!(!memory.writes_sound(0x9800));
+}
@@ -0,0 +1,30 @@
+use rsmsx::libs::timing::{Timing, CYCLES_PER_LINE};
+
+#[test]
+fn frames_have_the_lines_of_the_system() {
+    assert_eq!(Timing::NTSC.lines_per_frame(), 262);
+    assert_eq!(Timing::PAL.lines_per_frame(), 313);
+    assert_eq!(Timing::NTSC.cycles_per_frame(), 262 * CYCLES_PER_LINE);
+    assert_eq!(Timing::PAL.cycles_per_frame(), 71364);
+}
+
+#[test]
+fn frame_rates_follow_the_z80_clock() {
+    assert!((Timing::NTSC.frame_rate() - 59.92).abs() < 0.01);
+    assert!((Timing::PAL.frame_rate() - 50.16).abs() < 0.01);
+}
+
+#[test]
+fn pal_frames_have_a_longer_blanking() {
+    assert_eq!(Timing::NTSC.first_active_line(192), 70);
+    assert_eq!(Timing::PAL.first_active_line(192), 121);
+    assert_eq!(Timing::PAL.first_active_line(212), 101);
+}
+
+#[test]
+fn timings_are_chosen_by_name() {
+    assert_eq!(Timing::parse("pal"), Ok(Timing::PAL));
+    assert_eq!(Timing::parse("NTSC"), Ok(Timing::NTSC));
+    assert_eq!(Timing::parse("50"), Ok(Timing::PAL));
+    assert!(Timing::parse("secam").is_err());
+}
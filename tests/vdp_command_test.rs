@@ -0,0 +1,273 @@
+use rsmsx::libs::vdp_command::{CommandEngine, CommandMode, STATUS_BD, STATUS_CE, STATUS_TR};
+
+const SX: usize = 0;
+const SY: usize = 2;
+const DX: usize = 4;
+const DY: usize = 6;
+const NX: usize = 8;
+const NY: usize = 10;
+const CLR: usize = 12;
+const ARG: usize = 13;
+const CMD: usize = 14;
+
+struct Harness {
+    engine: CommandEngine,
+    vram: Vec<u8>,
+    mode: CommandMode,
+}
+
+impl Harness {
+    fn new(mode: CommandMode) -> Self {
+        Self {
+            engine: CommandEngine::new(),
+            vram: vec![0; 0x20000],
+            mode,
+        }
+    }
+
+    fn write(&mut self, reg: usize, value: u8) {
+        self.engine.write_register(reg, value, self.mode);
+    }
+
+    fn write_word(&mut self, reg: usize, value: u16) {
+        self.write(reg, value as u8);
+        self.write(reg + 1, (value >> 8) as u8);
+    }
+
+    fn block(&mut self, sx: u16, sy: u16, dx: u16, dy: u16, nx: u16, ny: u16) {
+        for (reg, value) in [(SX, sx), (SY, sy), (DX, dx), (DY, dy), (NX, nx), (NY, ny)] {
+            self.write_word(reg, value);
+        }
+    }
+
+    // Runs until the command ends, returns the Z80 cycles it took
+    fn finish(&mut self) -> u32 {
+        let mut cycles = 0;
+        while self.engine.is_busy() {
+            self.engine.run(4, &mut self.vram);
+            cycles += 4;
+            assert!(cycles < 10_000_000, "command never ends");
+        }
+        cycles
+    }
+
+    fn pset(&mut self, x: u16, y: u16, color: u8) {
+        self.block(0, 0, x, y, 0, 0);
+        self.write(CLR, color);
+        self.write(ARG, 0);
+        self.write(CMD, 0x50);
+        self.finish();
+    }
+
+    // Pixels of an area as hex digits, '.' for colour 0
+    fn image(&self, w: i32, h: i32) -> Vec<String> {
+        (0..h)
+            .map(|y| {
+                (0..w)
+                    .map(|x| match self.mode.point(&self.vram, x, y) {
+                        0 => '.',
+                        c => char::from_digit((c & 0x0f) as u32, 16).unwrap(),
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+fn assert_image(harness: &Harness, reference: &[&str]) {
+    let image = harness.image(reference[0].len() as i32, reference.len() as i32);
+    assert_eq!(image, reference);
+}
+
+#[test]
+fn line_follows_the_long_side() {
+    let mut h = Harness::new(CommandMode::Graphic4);
+    h.block(0, 0, 1, 1, 6, 3);
+    h.write(CLR, 0x0f);
+    h.write(ARG, 0);
+    h.write(CMD, 0x70);
+    h.finish();
+    assert_image(
+        &h,
+        &[
+            ".........", //
+            ".f.......",
+            "..ff.....",
+            "....ff...",
+            "......ff.",
+            ".........",
+        ],
+    );
+
+    // MAJ: long side in Y, drawing to the left
+    let mut h = Harness::new(CommandMode::Graphic4);
+    h.block(0, 0, 4, 0, 4, 2);
+    h.write(CLR, 0x03);
+    h.write(ARG, 0x05);
+    h.write(CMD, 0x70);
+    h.finish();
+    assert_image(&h, &["....3", "...3.", "...3.", "..3..", "..3..", "....."]);
+}
+
+#[test]
+fn lmmv_applies_the_logical_operation() {
+    let mut h = Harness::new(CommandMode::Graphic4);
+    h.pset(1, 0, 0x0c);
+    h.pset(2, 1, 0x0c);
+    h.block(0, 0, 0, 0, 4, 2);
+    h.write(CLR, 0x03);
+    h.write(ARG, 0);
+    // XOR
+    h.write(CMD, 0x83);
+    h.finish();
+    assert_image(&h, &["3f33.", "33f3.", "....."]);
+}
+
+#[test]
+fn lmmm_with_timp_skips_colour_0() {
+    let mut h = Harness::new(CommandMode::Graphic6);
+    h.pset(0, 0, 0x05);
+    h.pset(2, 0, 0x06);
+    h.pset(3, 1, 0x07);
+    for x in 0..4 {
+        h.pset(x, 3, 0x01);
+    }
+    h.block(0, 0, 0, 3, 4, 2);
+    h.write(ARG, 0);
+    h.write(CMD, 0x98);
+    h.finish();
+    assert_image(&h, &["5.6..", "...7.", ".....", "5161.", "...7."]);
+}
+
+#[test]
+fn hmmm_moves_bytes_and_ymmm_moves_to_the_edge() {
+    let mut h = Harness::new(CommandMode::Graphic7);
+    h.pset(0, 0, 0x0a);
+    h.pset(1, 0, 0x0b);
+    h.pset(255, 0, 0x0c);
+    // HMMM, copy to the left from (1, 0) to (4, 1)
+    h.block(1, 0, 4, 1, 2, 1);
+    h.write(ARG, 0x04);
+    h.write(CMD, 0xd0);
+    h.finish();
+    assert_image(&h, &["ab...", "...ab"]);
+
+    // YMMM from X 254 to the right edge, one line down
+    h.block(0, 0, 254, 2, 0, 1);
+    h.write(ARG, 0);
+    h.write(CMD, 0xe0);
+    h.finish();
+    assert_eq!(h.mode.point(&h.vram, 255, 2), 0x0c);
+    assert_eq!(h.mode.point(&h.vram, 253, 2), 0x00);
+}
+
+#[test]
+fn hmmc_waits_for_the_cpu() {
+    let mut h = Harness::new(CommandMode::Graphic5);
+    h.block(0, 0, 0, 1, 8, 2);
+    // The first byte goes in CLR before the command
+    h.write(CLR, 0x1b);
+    h.write(ARG, 0);
+    h.write(CMD, 0xf0);
+    for data in [0xe4, 0xff, 0x00] {
+        h.engine.run(100, &mut h.vram);
+        assert_eq!(
+            h.engine.status() & (STATUS_TR | STATUS_CE),
+            STATUS_TR | STATUS_CE
+        );
+        // Nothing happens until the CPU sends a byte
+        h.engine.run(10_000, &mut h.vram);
+        h.write(CLR, data);
+        assert_eq!(h.engine.status() & STATUS_TR, 0);
+    }
+    h.finish();
+    assert_eq!(h.engine.status(), 0);
+    assert_image(&h, &["........", ".123321.", "3333....", "........"]);
+}
+
+#[test]
+fn lmcm_hands_pixels_to_the_cpu() {
+    let mut h = Harness::new(CommandMode::Graphic4);
+    h.pset(5, 2, 0x04);
+    h.pset(6, 2, 0x06);
+    h.pset(5, 3, 0x08);
+    h.block(5, 2, 0, 0, 2, 2);
+    h.write(ARG, 0);
+    h.write(CMD, 0xa0);
+    let mut pixels = Vec::new();
+    while h.engine.status() & (STATUS_CE | STATUS_TR) != 0 {
+        h.engine.run(50, &mut h.vram);
+        if h.engine.status() & STATUS_TR != 0 {
+            pixels.push(h.engine.read_color());
+        }
+    }
+    assert_eq!(pixels, [4, 6, 8, 0]);
+}
+
+#[test]
+fn srch_reports_the_border() {
+    let mut h = Harness::new(CommandMode::Graphic4);
+    h.pset(40, 7, 0x09);
+    h.block(10, 7, 0, 0, 0, 0);
+    h.write(CLR, 0x09);
+    h.write(ARG, 0);
+    h.write(CMD, 0x60);
+    h.finish();
+    assert_eq!(h.engine.status() & STATUS_BD, STATUS_BD);
+    assert_eq!(h.engine.border_x(), 40);
+
+    // To the left there is no border colour
+    h.write(ARG, 0x04);
+    h.write(CMD, 0x60);
+    h.finish();
+    assert_eq!(h.engine.status() & STATUS_BD, 0);
+
+    // EQ: look for any other colour
+    h.write_word(SX, 40);
+    h.write(ARG, 0x02);
+    h.write(CMD, 0x60);
+    h.finish();
+    assert_eq!(h.engine.border_x(), 41);
+}
+
+#[test]
+fn busy_time_follows_the_z80_cycles() {
+    let mut h = Harness::new(CommandMode::Graphic4);
+    // 1280 bytes of HMMV
+    h.block(0, 0, 0, 0, 256, 10);
+    h.write(CLR, 0x11);
+    h.write(ARG, 0);
+    h.write(CMD, 0xc0);
+    let hmmv = h.finish();
+    assert!((12_000..16_000).contains(&hmmv), "HMMV took {}", hmmv);
+
+    // The same area with LMMV, pixel by pixel, is much slower
+    h.write(CMD, 0x80);
+    let lmmv = h.finish();
+    assert!(lmmv > 4 * hmmv, "LMMV took {}", lmmv);
+
+    // STOP ends a command at once
+    h.write(CMD, 0xc0);
+    h.engine.run(100, &mut h.vram);
+    assert!(h.engine.is_busy());
+    h.write(CMD, 0x00);
+    assert!(!h.engine.is_busy());
+}
+
+#[test]
+fn block_leaves_dy_after_the_last_line() {
+    let mut h = Harness::new(CommandMode::Graphic7);
+    h.block(0, 0, 0, 4, 2, 3);
+    h.write(CLR, 0x21);
+    h.write(ARG, 0);
+    h.write(CMD, 0xc0);
+    h.finish();
+    // A second HMMV continues below the first one
+    h.write(CLR, 0x42);
+    h.write_word(NY, 1);
+    h.write(CMD, 0xc0);
+    h.finish();
+    assert_eq!(h.mode.point(&h.vram, 0, 6), 0x21);
+    assert_eq!(h.mode.point(&h.vram, 1, 7), 0x42);
+    assert_eq!(h.mode.point(&h.vram, 0, 8), 0x00);
+}
@@ -0,0 +1,666 @@
+use std::{cell::RefCell, rc::Rc};
+
+use rsmsx::libs::{
+    graphics::GraphicsDriver,
+    palette::Palette,
+    timing::Timing,
+    vdp::{Vdp, VdpModel, FIRST_ACTIVE_LINE, LINES_PER_FRAME},
+};
+
+// Default border around the active display
+const BORDER: usize = 8;
+
+// Keeps the pixels drawn by the VDP
+struct Screen {
+    width: usize,
+    pixels: Vec<usize>,
+    palette: Vec<(u8, u8, u8)>,
+}
+
+impl Screen {
+    // Pixel of the active display
+    fn get(&self, x: usize, y: usize) -> usize {
+        self.pixels[(y + BORDER) * self.width + x + BORDER]
+    }
+
+    fn line(&self, y: usize) -> &[usize] {
+        let start = (y + BORDER) * self.width + BORDER;
+        &self.pixels[start..start + 256]
+    }
+}
+
+impl GraphicsDriver for Screen {
+    fn init(&mut self) {}
+    fn render(&mut self) {}
+    fn set_logical_resolution(&mut self, _scr_mode: u8) {}
+    fn set_frame_size(&mut self, width: u32, height: u32) {
+        self.width = width as usize;
+        self.pixels = vec![99; (width * height) as usize];
+    }
+    fn draw_pixel(&mut self, x: u32, y: u32, color: usize) {
+        self.pixels[y as usize * self.width + x as usize] = color;
+    }
+    fn set_palette_color(&mut self, index: usize, r: u8, g: u8, b: u8) {
+        self.palette[index] = (r, g, b);
+    }
+    fn set_palette(&mut self, palette: &Palette) {
+        self.palette[..16].copy_from_slice(&palette.colors);
+    }
+    fn save_screenshot(&self, _path: &str) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+fn new_vdp() -> (Vdp, Rc<RefCell<Screen>>) {
+    let screen = Rc::new(RefCell::new(Screen {
+        width: 0,
+        pixels: Vec::new(),
+        palette: vec![(0, 0, 0); 16 + 256],
+    }));
+    let mut vdp = Vdp::with_driver(screen.clone());
+    vdp.init_graphics();
+    (vdp, screen)
+}
+
+fn write_register(vdp: &mut Vdp, reg: u8, value: u8) {
+    vdp.write_port(0x99, value);
+    vdp.write_port(0x99, 0x80 | reg);
+}
+
+fn write_vram(vdp: &mut Vdp, address: u16, data: &[u8]) {
+    vdp.write_port(0x99, address as u8);
+    vdp.write_port(0x99, 0x40 | (address >> 8) as u8);
+    for b in data {
+        vdp.write_port(0x98, *b);
+    }
+}
+
+// SCREEN1 with every character using pattern 0 (all pixels set)
+fn screen1(vdp: &mut Vdp) {
+    write_register(vdp, 0, 0x00);
+    write_register(vdp, 1, 0x40);
+    write_register(vdp, 2, 0x06); // names at 0x1800
+    write_register(vdp, 3, 0x80); // colours at 0x2000
+    write_register(vdp, 4, 0x00); // patterns at 0x0000
+    write_register(vdp, 5, 0x36); // sprite attributes at 0x1b00
+    write_register(vdp, 6, 0x07); // sprite patterns at 0x3800
+    write_vram(vdp, 0x0000, &[0xff; 8]);
+    write_vram(vdp, 0x1b00, &[0xd0]);
+    write_vram(vdp, 0x2000, &[0x40; 32]);
+}
+
+#[test]
+fn lines_use_the_registers_of_their_time() {
+    let (mut vdp, screen) = new_vdp();
+    screen1(&mut vdp);
+    for line in 0..FIRST_ACTIVE_LINE + 100 {
+        vdp.render_line(line);
+    }
+    // Raster effect: new colours for the rest of the frame
+    write_vram(&mut vdp, 0x2000, &[0xa0; 32]);
+    for line in FIRST_ACTIVE_LINE + 100..LINES_PER_FRAME {
+        vdp.render_line(line);
+    }
+
+    let screen = screen.borrow();
+    assert_eq!(screen.get(0, 99), 4);
+    assert_eq!(screen.get(255, 100), 10);
+    assert_eq!(screen.get(0, 191), 10);
+}
+
+#[test]
+fn pal_frames_start_the_display_later() {
+    let (mut vdp, screen) = new_vdp();
+    vdp.set_timing(Timing::PAL);
+    screen1(&mut vdp);
+    let first_active = Timing::PAL.first_active_line(192);
+    for line in 0..first_active + 100 {
+        vdp.render_line(line);
+    }
+    write_vram(&mut vdp, 0x2000, &[0xa0; 32]);
+    for line in first_active + 100..Timing::PAL.lines_per_frame() {
+        vdp.render_line(line);
+    }
+
+    let screen = screen.borrow();
+    assert_eq!(screen.get(0, 0), 4);
+    assert_eq!(screen.get(0, 99), 4);
+    assert_eq!(screen.get(0, 100), 10);
+    // Bottom border, drawn at the beginning of the frame
+    assert_eq!(screen.get(0, 192), 0);
+}
+
+#[test]
+fn sprites_are_drawn_per_line() {
+    let (mut vdp, screen) = new_vdp();
+    screen1(&mut vdp);
+    write_vram(&mut vdp, 0x3800, &[0x80; 8]);
+    write_vram(&mut vdp, 0x1b00, &[10, 20, 0, 0x0f, 0xd0]);
+    for line in 0..LINES_PER_FRAME {
+        vdp.render_line(line);
+    }
+
+    let screen = screen.borrow();
+    // Y is one line above the sprite
+    assert_eq!(screen.get(20, 10), 4);
+    assert_eq!(screen.get(20, 11), 15);
+    assert_eq!(screen.get(20, 18), 15);
+    assert_eq!(screen.get(20, 19), 4);
+    assert_eq!(screen.get(21, 10), 4);
+}
+
+#[test]
+fn screen3_draws_4x4_blocks() {
+    let (mut vdp, screen) = new_vdp();
+    screen1(&mut vdp);
+    write_register(&mut vdp, 1, 0x48); // M2
+                                       // Pattern 1: one colour pair per 4 lines
+    write_vram(
+        &mut vdp,
+        0x0008,
+        &[0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0xde, 0xf1],
+    );
+    write_vram(&mut vdp, 0x1800, &[1; 32 * 24]);
+    for line in 0..LINES_PER_FRAME {
+        vdp.render_line(line);
+    }
+
+    let screen = screen.borrow();
+    assert_eq!(screen.get(0, 0), 1);
+    assert_eq!(screen.get(3, 3), 1);
+    assert_eq!(screen.get(4, 0), 2);
+    assert_eq!(screen.get(0, 4), 3);
+    // Second character row uses bytes 2 and 3
+    assert_eq!(screen.get(7, 8), 6);
+    assert_eq!(screen.get(0, 15), 7);
+    // Fifth row starts again at byte 0
+    assert_eq!(screen.get(0, 32), 1);
+}
+
+fn status(vdp: &mut Vdp) -> u8 {
+    vdp.read_port(0x99)
+}
+
+#[test]
+fn fifth_sprite_is_flagged_and_hidden() {
+    let (mut vdp, screen) = new_vdp();
+    screen1(&mut vdp);
+    write_vram(&mut vdp, 0x3800, &[0xff; 8]);
+    // Five sprites on the same lines, far apart, colour 0 for the 2nd
+    let mut attrs = Vec::new();
+    for i in 0..5u8 {
+        let color = if i == 1 { 0 } else { 2 + i };
+        attrs.extend_from_slice(&[49, i * 40, 0, color]);
+    }
+    attrs.push(0xd0);
+    write_vram(&mut vdp, 0x1b00, &attrs);
+    for line in 0..LINES_PER_FRAME {
+        vdp.render_line(line);
+    }
+
+    let screen = screen.borrow();
+    assert_eq!(screen.get(0, 50), 2);
+    // Transparent sprite shows the background
+    assert_eq!(screen.get(40, 50), 4);
+    assert_eq!(screen.get(120, 50), 5);
+    // Sprite 4 is the 5th on the line
+    assert_eq!(screen.get(160, 50), 4);
+    assert_eq!(status(&mut vdp), 0x40 | 4);
+    // Reading the status clears the flag
+    assert_eq!(status(&mut vdp) & 0x40, 0);
+}
+
+#[test]
+fn overlapping_sprites_collide() {
+    let (mut vdp, screen) = new_vdp();
+    screen1(&mut vdp);
+    write_vram(&mut vdp, 0x3800, &[0x80; 8]);
+    // Sprite 0 on top of sprite 1, sprite 2 moved by the early clock
+    write_vram(
+        &mut vdp,
+        0x1b00,
+        &[9, 20, 0, 0x06, 9, 20, 0, 0x0a, 99, 40, 0, 0x8f, 0xd0],
+    );
+    for line in 0..LINES_PER_FRAME {
+        vdp.render_line(line);
+    }
+
+    {
+        let screen = screen.borrow();
+        assert_eq!(screen.get(20, 10), 6);
+        assert_eq!(screen.get(8, 100), 15);
+    }
+    assert_eq!(status(&mut vdp) & 0x60, 0x20);
+
+    // No collision when they do not overlap
+    write_vram(&mut vdp, 0x1b05, &[21]);
+    for line in 0..LINES_PER_FRAME {
+        vdp.render_line(line);
+    }
+    assert_eq!(status(&mut vdp) & 0x20, 0);
+}
+
+#[test]
+fn screen2_masks_mirror_the_tables() {
+    let (mut vdp, screen) = new_vdp();
+    screen1(&mut vdp);
+    write_register(&mut vdp, 0, 0x02); // M3
+    write_register(&mut vdp, 3, 0x9f); // colours at 0x2000, first third only
+    write_register(&mut vdp, 4, 0x00); // patterns at 0x0000, first third only
+    write_vram(&mut vdp, 0x0000, &[0xf0; 8]);
+    write_vram(&mut vdp, 0x2000, &[0x4a; 8]);
+    // The other thirds hold garbage that must not be used
+    write_vram(&mut vdp, 0x1000, &[0x0f; 8]);
+    write_vram(&mut vdp, 0x3000, &[0x23; 8]);
+    write_vram(&mut vdp, 0x1800, &[0; 32 * 24]);
+    for line in 0..LINES_PER_FRAME {
+        vdp.render_line(line);
+    }
+
+    let screen = screen.borrow();
+    for y in [0, 100, 150] {
+        assert_eq!(screen.get(0, y), 4);
+        assert_eq!(screen.get(4, y), 10);
+    }
+}
+
+#[test]
+fn text_modes_use_6_pixel_columns() {
+    let (mut vdp, screen) = new_vdp();
+    screen1(&mut vdp);
+    write_register(&mut vdp, 1, 0x50); // M1
+    write_register(&mut vdp, 7, 0xf4);
+    write_vram(&mut vdp, 0x0008, &[0xa8; 8]);
+    write_vram(&mut vdp, 0x1800, &[1; 40 * 24]);
+    for line in 0..LINES_PER_FRAME {
+        vdp.render_line(line);
+    }
+    {
+        let screen = screen.borrow();
+        assert_eq!(
+            screen.line(0)[..16],
+            [4, 4, 4, 4, 4, 4, 4, 4, 15, 4, 15, 4, 15, 4, 15, 4]
+        );
+        assert_eq!(screen.line(0)[248..256], [4; 8]);
+    }
+
+    // M1+M2: stripes without name table
+    write_register(&mut vdp, 1, 0x58);
+    for line in 0..LINES_PER_FRAME {
+        vdp.render_line(line);
+    }
+    let screen = screen.borrow();
+    assert_eq!(
+        screen.line(0)[8..20],
+        [15, 15, 15, 15, 4, 4, 15, 15, 15, 15, 4, 4]
+    );
+}
+
+#[test]
+fn border_and_colour_0_show_the_backdrop() {
+    let (mut vdp, screen) = new_vdp();
+    screen1(&mut vdp);
+    write_register(&mut vdp, 7, 0x05);
+    // Foreground 0 is transparent
+    write_vram(&mut vdp, 0x2000, &[0x0a; 32]);
+    for line in 0..LINES_PER_FRAME {
+        vdp.render_line(line);
+    }
+    {
+        let screen = screen.borrow();
+        assert_eq!(screen.get(0, 0), 5);
+        assert_eq!(screen.pixels[0], 5);
+        assert_eq!(screen.pixels.last(), Some(&5));
+        assert_eq!(screen.pixels.len(), 272 * 208);
+    }
+
+    // Blanked screen: only the backdrop
+    write_register(&mut vdp, 1, 0x00);
+    write_register(&mut vdp, 7, 0x0c);
+    for line in 0..LINES_PER_FRAME {
+        vdp.render_line(line);
+    }
+    assert!(screen.borrow().pixels.iter().all(|c| *c == 12));
+
+    vdp.set_overscan(true);
+    assert_eq!(vdp.frame_width(), 284);
+    assert_eq!(vdp.frame_height(), 243);
+    assert_eq!(screen.borrow().pixels.len(), 284 * 243);
+}
+
+fn set_read_address(vdp: &mut Vdp, address: u16) {
+    vdp.write_port(0x99, address as u8);
+    vdp.write_port(0x99, (address >> 8) as u8 & 0x3f);
+}
+
+#[test]
+fn vram_reads_go_through_the_read_ahead_buffer() {
+    let (mut vdp, _) = new_vdp();
+    write_vram(&mut vdp, 0x1000, &[1, 2, 3]);
+    set_read_address(&mut vdp, 0x1000);
+    // The first byte was fetched when the address was set
+    write_vram(&mut vdp, 0x1000, &[9]);
+    set_read_address(&mut vdp, 0x1000);
+    assert_eq!(vdp.read_port(0x98), 9);
+    assert_eq!(vdp.read_port(0x98), 2);
+    // A write in between updates the buffer and moves the pointer
+    vdp.write_port(0x98, 7);
+    assert_eq!(vdp.read_port(0x98), 7);
+    assert_eq!(vdp.read_port(0x98), 0);
+}
+
+#[test]
+fn vram_address_wraps_at_16kb() {
+    let (mut vdp, _) = new_vdp();
+    write_vram(&mut vdp, 0x3fff, &[1, 2]);
+    set_read_address(&mut vdp, 0x3fff);
+    assert_eq!(vdp.read_port(0x98), 1);
+    assert_eq!(vdp.read_port(0x98), 2);
+    set_read_address(&mut vdp, 0x0000);
+    assert_eq!(vdp.read_port(0x98), 2);
+}
+
+#[test]
+fn tms9918_ignores_the_v9938_ports() {
+    let (mut vdp, _) = new_vdp();
+    write_vram(&mut vdp, 0x1000, &[1, 2]);
+    set_read_address(&mut vdp, 0x1000);
+    // Neither a palette write nor an indirect register write
+    vdp.write_port(0x9a, 0x77);
+    vdp.write_port(0x9b, 0x07);
+    vdp.write_port(0x9b, 0x0f);
+    assert_eq!(vdp.read_port(0x9a), 0xff);
+    assert_eq!(vdp.read_port(0x9b), 0xff);
+    assert_eq!(vdp.read_port(0x98), 1);
+    assert_eq!(vdp.read_port(0x98), 2);
+}
+
+#[test]
+fn status_read_resets_the_latch() {
+    let (mut vdp, screen) = new_vdp();
+    screen1(&mut vdp);
+    write_register(&mut vdp, 7, 0x01);
+    // Half a command, then a status read
+    vdp.write_port(0x99, 0x0d);
+    vdp.read_port(0x99);
+    // Register numbers only decode 3 bits: 15 is R#7
+    write_register(&mut vdp, 15, 0x0d);
+    write_register(&mut vdp, 1, 0x00);
+    for line in 0..LINES_PER_FRAME {
+        vdp.render_line(line);
+    }
+    assert_eq!(screen.borrow().get(0, 0), 13);
+}
+
+fn new_v9938() -> (Vdp, Rc<RefCell<Screen>>) {
+    let screen = Rc::new(RefCell::new(Screen {
+        width: 0,
+        pixels: Vec::new(),
+        palette: vec![(0, 0, 0); 16 + 256],
+    }));
+    let mut vdp = Vdp::with_driver(screen.clone());
+    vdp.set_model(VdpModel::V9938);
+    vdp.init_graphics();
+    (vdp, screen)
+}
+
+// Pixel of the V9938 active display, in 512 pixels per line
+fn get_hires(screen: &Screen, x: usize, y: usize) -> usize {
+    screen.pixels[(y + BORDER) * screen.width + x + BORDER * 2]
+}
+
+// Writes VRAM past 16KB through R#14
+fn write_vram_128k(vdp: &mut Vdp, address: u32, data: &[u8]) {
+    write_register(vdp, 14, (address >> 14) as u8);
+    write_vram(vdp, address as u16 & 0x3fff, data);
+    write_register(vdp, 14, 0);
+}
+
+fn render_frame(vdp: &mut Vdp) {
+    for line in 0..LINES_PER_FRAME {
+        vdp.render_line(line);
+    }
+}
+
+// SCREEN5 with 212 lines, page 0 and sprites at 0x7600
+fn screen5(vdp: &mut Vdp) {
+    write_register(vdp, 0, 0x06);
+    write_register(vdp, 1, 0x40);
+    write_register(vdp, 2, 0x1f);
+    write_register(vdp, 5, 0xef);
+    write_register(vdp, 11, 0x00);
+    write_register(vdp, 6, 0x0f); // sprite patterns at 0x7800
+    write_register(vdp, 9, 0x80);
+    write_vram_128k(vdp, 0x7600, &[216]);
+}
+
+#[test]
+fn screen5_draws_nibbles_on_212_lines() {
+    let (mut vdp, screen) = new_v9938();
+    screen5(&mut vdp);
+    write_vram(&mut vdp, 0x0000, &[0x12, 0x30]);
+    write_vram_128k(&mut vdp, 211 * 128, &[0x45]);
+    render_frame(&mut vdp);
+
+    let screen = screen.borrow();
+    assert_eq!(screen.width, (8 + 256 + 8) * 2);
+    // Each pixel is drawn twice as wide
+    assert_eq!(get_hires(&screen, 0, 0), 1);
+    assert_eq!(get_hires(&screen, 1, 0), 1);
+    assert_eq!(get_hires(&screen, 2, 0), 2);
+    assert_eq!(get_hires(&screen, 4, 0), 3);
+    // Colour 0 shows the backdrop
+    assert_eq!(get_hires(&screen, 6, 0), 0);
+    assert_eq!(get_hires(&screen, 0, 211), 4);
+    assert_eq!(get_hires(&screen, 2, 211), 5);
+}
+
+#[test]
+fn screen7_and_screen8_pages_live_past_64kb() {
+    let (mut vdp, screen) = new_v9938();
+    screen5(&mut vdp);
+    // SCREEN7, page 1
+    write_register(&mut vdp, 0, 0x0a);
+    write_register(&mut vdp, 2, 0x3f);
+    write_vram_128k(&mut vdp, 0x10000, &[0x9a]);
+    render_frame(&mut vdp);
+    assert_eq!(get_hires(&screen.borrow(), 0, 0), 9);
+    assert_eq!(get_hires(&screen.borrow(), 1, 0), 10);
+
+    // SCREEN8 colours come after the palette
+    write_register(&mut vdp, 0, 0x0e);
+    render_frame(&mut vdp);
+    assert_eq!(get_hires(&screen.borrow(), 0, 0), 16 + 0x9a);
+    assert_eq!(screen.borrow().palette[16 + 0xff], (255, 255, 255));
+}
+
+#[test]
+fn vram_pointer_carries_into_r14_in_bitmap_modes() {
+    let (mut vdp, _) = new_v9938();
+    screen5(&mut vdp);
+    write_vram_128k(&mut vdp, 0x7fff, &[1, 2]);
+    write_register(&mut vdp, 14, 0x01);
+    set_read_address(&mut vdp, 0x3fff);
+    assert_eq!(vdp.read_port(0x98), 1);
+    assert_eq!(vdp.read_port(0x98), 2);
+}
+
+#[test]
+fn palette_port_sets_the_driver_colours() {
+    let (mut vdp, screen) = new_v9938();
+    assert_eq!(screen.borrow().palette[15], (255, 255, 255));
+    write_register(&mut vdp, 16, 4);
+    // 0RRR0BBB then 00000GGG, moving to the next colour
+    vdp.write_port(0x9a, 0x70);
+    vdp.write_port(0x9a, 0x00);
+    vdp.write_port(0x9a, 0x07);
+    vdp.write_port(0x9a, 0x07);
+    let screen = screen.borrow();
+    assert_eq!(screen.palette[4], (255, 0, 0));
+    assert_eq!(screen.palette[5], (0, 255, 255));
+}
+
+#[test]
+fn v9938_keeps_its_palette() {
+    let (mut vdp, screen) = new_v9938();
+    let before = screen.borrow().palette.clone();
+    vdp.set_palette(&Palette::greyscale());
+    assert_eq!(screen.borrow().palette, before);
+}
+
+#[test]
+fn indirect_register_writes_increment_r17() {
+    let (mut vdp, screen) = new_v9938();
+    screen5(&mut vdp);
+    write_register(&mut vdp, 17, 7);
+    vdp.write_port(0x9b, 0x03);
+    vdp.write_port(0x9b, 0x00);
+    // Bit 7 keeps the register number
+    write_register(&mut vdp, 17, 0x80 | 7);
+    vdp.write_port(0x9b, 0x05);
+    vdp.write_port(0x9b, 0x06);
+    render_frame(&mut vdp);
+    assert_eq!(get_hires(&screen.borrow(), 0, 0), 6);
+}
+
+#[test]
+fn status_registers_are_selected_by_r15() {
+    let (mut vdp, _) = new_v9938();
+    screen5(&mut vdp);
+    write_register(&mut vdp, 15, 2);
+    assert_eq!(status(&mut vdp) & 0x0d, 0x0c);
+
+    // Line interrupt: FH in S#1 and an interrupt with IE1
+    write_register(&mut vdp, 19, 100);
+    write_register(&mut vdp, 0, 0x16);
+    render_frame(&mut vdp);
+    assert!(vdp.int_line());
+    write_register(&mut vdp, 15, 1);
+    assert_eq!(status(&mut vdp) & 0x01, 0x01);
+    assert!(!vdp.int_line());
+    assert_eq!(status(&mut vdp) & 0x01, 0x00);
+}
+
+#[test]
+fn frame_interrupt_lasts_until_the_status_is_read() {
+    let (mut vdp, _) = new_vdp();
+    screen1(&mut vdp);
+    vdp.set_frame_flag();
+    assert!(!vdp.int_line());
+    // IE0 with F already set
+    write_register(&mut vdp, 1, 0x60);
+    assert!(vdp.int_line());
+    assert_eq!(status(&mut vdp) & 0x80, 0x80);
+    assert!(!vdp.int_line());
+}
+
+#[test]
+fn commands_run_in_the_bitmap_modes() {
+    let (mut vdp, _) = new_v9938();
+    screen5(&mut vdp);
+    // HMMV: fill 4x2 pixels at (2, 1) with 0x77
+    for (reg, value) in [
+        (36, 2),
+        (37, 0),
+        (38, 1),
+        (39, 0),
+        (40, 4),
+        (41, 0),
+        (42, 2),
+        (43, 0),
+    ] {
+        write_register(&mut vdp, reg, value);
+    }
+    write_register(&mut vdp, 44, 0x77);
+    write_register(&mut vdp, 45, 0x00);
+    write_register(&mut vdp, 46, 0xc0);
+    // The engine takes its time
+    write_register(&mut vdp, 15, 2);
+    assert_eq!(status(&mut vdp) & 0x01, 0x01);
+    vdp.run(1000);
+    assert_eq!(status(&mut vdp) & 0x01, 0x00);
+    set_read_address(&mut vdp, 128);
+    assert_eq!(vdp.read_port(0x98), 0x00);
+    assert_eq!(vdp.read_port(0x98), 0x77);
+    assert_eq!(vdp.read_port(0x98), 0x77);
+    assert_eq!(vdp.read_port(0x98), 0x00);
+
+    // POINT reads the pixel back through S#7
+    for (reg, value) in [(32, 3), (33, 0), (34, 2), (35, 0)] {
+        write_register(&mut vdp, reg, value);
+    }
+    write_register(&mut vdp, 46, 0x40);
+    vdp.run(1000);
+    write_register(&mut vdp, 15, 7);
+    assert_eq!(status(&mut vdp), 0x07);
+}
+
+#[test]
+fn text80_uses_the_blink_colours() {
+    let (mut vdp, screen) = new_v9938();
+    write_register(&mut vdp, 0, 0x04);
+    write_register(&mut vdp, 1, 0x50);
+    write_register(&mut vdp, 2, 0x03); // names at 0x0000
+    write_register(&mut vdp, 3, 0x27); // blink table at 0x0800
+    write_register(&mut vdp, 4, 0x02); // patterns at 0x1000
+    write_register(&mut vdp, 7, 0xf4);
+    write_register(&mut vdp, 12, 0x1a);
+    write_register(&mut vdp, 13, 0x11);
+    write_vram(&mut vdp, 0x0000, &[0, 1]);
+    write_vram(&mut vdp, 0x0800, &[0x40]);
+    write_vram(&mut vdp, 0x1008, &[0xfc]);
+    render_frame(&mut vdp);
+
+    let screen = screen.borrow();
+    // Characters are 6 pixels wide, after 8 low resolution pixels
+    assert_eq!(get_hires(&screen, 0, 0), 4);
+    assert_eq!(get_hires(&screen, 16, 0), 4);
+    // The second character blinks
+    assert_eq!(get_hires(&screen, 22, 0), 1);
+    assert_eq!(get_hires(&screen, 27, 0), 1);
+    assert_eq!(get_hires(&screen, 28, 0), 4);
+}
+
+#[test]
+fn sprite_mode_2_shows_8_sprites_and_ors_cc_colours() {
+    let (mut vdp, screen) = new_v9938();
+    screen5(&mut vdp);
+    write_vram_128k(&mut vdp, 0x7800, &[0xff; 8]);
+    // Sprite 7 sits on sprite 6, sprite 8 is one too many
+    let mut attrs = Vec::new();
+    for x in [0, 20, 40, 60, 80, 100, 120, 120, 200] {
+        attrs.extend_from_slice(&[9, x, 0, 0]);
+    }
+    attrs.push(216);
+    write_vram_128k(&mut vdp, 0x7600, &attrs);
+    let mut colors = Vec::new();
+    for i in 0..9u8 {
+        colors.extend_from_slice(&[i + 1; 16]);
+    }
+    // Only line 2 of sprite 0 changes colour, sprite 7 has CC
+    colors[2] = 0x0f;
+    colors[7 * 16..8 * 16].fill(0x40 | 0x08);
+    write_vram_128k(&mut vdp, 0x7400, &colors);
+    render_frame(&mut vdp);
+
+    let screen = screen.borrow();
+    assert_eq!(get_hires(&screen, 0, 10), 1);
+    assert_eq!(get_hires(&screen, 0, 12), 15);
+    assert_eq!(get_hires(&screen, 100 * 2, 10), 6);
+    assert_eq!(get_hires(&screen, 120 * 2, 10), 7 | 8);
+    assert_eq!(get_hires(&screen, 200 * 2, 10), 0);
+    // CC sprites do not collide
+    assert_eq!(status(&mut vdp) & 0x7f, 0x48);
+}
+
+#[test]
+fn sprite_colours_wrap_below_an_attribute_table_at_0() {
+    let (mut vdp, screen) = new_v9938();
+    screen5(&mut vdp);
+    write_register(&mut vdp, 5, 0x00);
+    write_vram_128k(&mut vdp, 0x7800, &[0xff; 8]);
+    write_vram_128k(&mut vdp, 0x0000, &[9, 0, 0, 0, 216]);
+    write_vram_128k(&mut vdp, 0x1fe00, &[5; 16]);
+    render_frame(&mut vdp);
+    assert_eq!(get_hires(&screen.borrow(), 0, 10), 5);
+}
@@ -0,0 +1,446 @@
+use std::{
+    collections::VecDeque,
+    fs,
+    io::{self, Write},
+};
+
+use rsmsx::libs::z80::{
+    z80_base::{Registers, Z80},
+    z80_bus::Bus,
+};
+
+// FUSE style vectors: tests.in has the state before every test and
+// tests.expected the state after it
+const TESTS_IN: &str = include_str!("z80/tests.in");
+const TESTS_EXPECTED: &str = include_str!("z80/tests.expected");
+
+// 64KB of RAM without wait states, so the cycles are those of a plain Z80.
+// Ports read their high byte, like in the FUSE tests.
+struct TestBus {
+    memory: Vec<u8>,
+    // Level of INT and the bytes put on the data bus by the acknowledge
+    // cycles
+    int: bool,
+    data_bus: VecDeque<u8>,
+}
+
+impl Bus for TestBus {
+    fn read_byte(&self, address: u16) -> u8 {
+        self.memory[address as usize]
+    }
+
+    fn write_byte(&mut self, address: u16, value: u8) {
+        self.memory[address as usize] = value;
+    }
+
+    fn read_port(&mut self, port: u16) -> u8 {
+        (port >> 8) as u8
+    }
+
+    fn write_port(&mut self, _port: u16, _value: u8) {}
+
+    fn int_line(&self) -> bool {
+        self.int
+    }
+
+    fn acknowledge_interrupt(&mut self) -> u8 {
+        self.data_bus.pop_front().unwrap_or(0xff)
+    }
+}
+
+fn new_z80() -> Z80<TestBus> {
+    let mut z80 = Z80::new(TestBus {
+        memory: vec![0; 0x10000],
+        int: false,
+        data_bus: VecDeque::new(),
+    });
+    z80.reset();
+    z80.reset_cycles();
+    z80
+}
+
+// Registers in the order of the vector files
+#[derive(Debug, PartialEq, Eq)]
+struct State {
+    // AF BC DE HL AF' BC' DE' HL' IX IY SP PC MEMPTR
+    words: Vec<u16>,
+    // I R IFF1 IFF2 IM halted
+    bytes: Vec<u8>,
+    tstates: u64,
+}
+
+const MEMPTR: usize = 12;
+
+#[derive(Debug)]
+struct Vector {
+    name: String,
+    state: State,
+    memory: Vec<(u16, Vec<u8>)>,
+}
+
+fn hex(field: &str) -> u16 {
+    u16::from_str_radix(field, 16).unwrap_or_else(|_| panic!("bad hex value {}", field))
+}
+
+fn parse_state(words: &str, bytes: &str) -> State {
+    let words = words.split_whitespace().map(hex).collect::<Vec<_>>();
+    let fields = bytes.split_whitespace().collect::<Vec<_>>();
+    assert_eq!((words.len(), fields.len()), (13, 7), "bad state lines");
+    State {
+        words,
+        bytes: fields[..6].iter().map(|f| hex(f) as u8).collect(),
+        tstates: fields[6].parse().unwrap(),
+    }
+}
+
+// "addr byte byte ... -1"
+fn parse_memory(line: &str) -> (u16, Vec<u8>) {
+    let mut fields = line.split_whitespace();
+    let address = hex(fields.next().unwrap());
+    let bytes = fields
+        .take_while(|f| *f != "-1")
+        .map(|f| hex(f) as u8)
+        .collect();
+    (address, bytes)
+}
+
+// Blocks are separated by empty lines. Lines starting with spaces list the
+// bus events, which are not checked.
+fn parse_vectors(content: &str) -> Vec<Vector> {
+    let mut vectors = Vec::new();
+    let mut lines = content.lines().filter(|l| !l.starts_with(' '));
+    while let Some(name) = lines.find(|l| !l.trim().is_empty()) {
+        let words = lines.next().unwrap();
+        let bytes = lines.next().unwrap();
+        let memory = lines
+            .by_ref()
+            .take_while(|l| !l.trim().is_empty() && l.trim() != "-1")
+            .map(parse_memory)
+            .collect();
+        vectors.push(Vector {
+            name: name.trim().to_string(),
+            state: parse_state(words, bytes),
+            memory,
+        });
+    }
+    vectors
+}
+
+fn set_state(z80: &mut Z80<TestBus>, state: &State) {
+    let (w, b) = (&state.words, &state.bytes);
+    z80.set_registers(&Registers {
+        af: w[0],
+        bc: w[1],
+        de: w[2],
+        hl: w[3],
+        af_: w[4],
+        bc_: w[5],
+        de_: w[6],
+        hl_: w[7],
+        ix: w[8],
+        iy: w[9],
+        sp: w[10],
+        pc: w[11],
+        memptr: w[MEMPTR],
+        i: b[0],
+        r: b[1],
+        iff1: b[2],
+        iff2: b[3],
+        im: b[4],
+        halted: b[5] != 0,
+    });
+}
+
+fn get_state(z80: &Z80<TestBus>, tstates: u64) -> State {
+    let regs = z80.registers();
+    State {
+        words: vec![
+            regs.af,
+            regs.bc,
+            regs.de,
+            regs.hl,
+            regs.af_,
+            regs.bc_,
+            regs.de_,
+            regs.hl_,
+            regs.ix,
+            regs.iy,
+            regs.sp,
+            regs.pc,
+            regs.memptr,
+        ],
+        bytes: vec![
+            regs.i,
+            regs.r,
+            regs.iff1,
+            regs.iff2,
+            regs.im,
+            regs.halted as u8,
+        ],
+        tstates,
+    }
+}
+
+// Runs the vector and returns the differences with the expected state
+fn run_vector(input: &Vector, expected: &Vector) -> Vec<String> {
+    let mut z80 = new_z80();
+    set_state(&mut z80, &input.state);
+    for (address, bytes) in &input.memory {
+        for (i, b) in bytes.iter().enumerate() {
+            z80.poke(address.wrapping_add(i as u16), *b);
+        }
+    }
+
+    while z80.get_cycles() < input.state.tstates {
+        z80.do_opcode();
+    }
+
+    let mut errors = Vec::new();
+    let state = get_state(&z80, z80.get_cycles());
+    if state != expected.state {
+        errors.push(format!(
+            "{}: got {:04x?} {:02x?} {}, expected {:04x?} {:02x?} {}",
+            input.name,
+            state.words,
+            state.bytes,
+            state.tstates,
+            expected.state.words,
+            expected.state.bytes,
+            expected.state.tstates
+        ));
+    }
+    for (address, bytes) in &expected.memory {
+        for (i, b) in bytes.iter().enumerate() {
+            let address = address.wrapping_add(i as u16);
+            let value = z80.peek(address);
+            if value != *b {
+                errors.push(format!(
+                    "{}: ({:04x}) is {:02x} instead of {:02x}",
+                    input.name, address, value, b
+                ));
+            }
+        }
+    }
+    errors
+}
+
+#[test]
+fn fuse_vectors() {
+    let inputs = parse_vectors(TESTS_IN);
+    let expected = parse_vectors(TESTS_EXPECTED);
+    assert_eq!(inputs.len(), expected.len());
+    let mut errors = Vec::new();
+    for (input, expected) in inputs.iter().zip(&expected) {
+        assert_eq!(input.name, expected.name);
+        errors.extend(run_vector(input, expected));
+    }
+    assert!(errors.is_empty(), "\n{}", errors.join("\n"));
+}
+
+// Loads the program at 0 with the stack at 0x8000 and interrupts in the
+// given mode
+fn interrupt_z80(program: &[u8], mode: u8) -> Z80<TestBus> {
+    let mut z80 = new_z80();
+    for (i, b) in program.iter().enumerate() {
+        z80.poke(i as u16, *b);
+    }
+    let mut regs = z80.registers();
+    (regs.iff1, regs.iff2, regs.im) = (1, 1, mode);
+    regs.sp = 0x8000;
+    z80.set_registers(&regs);
+    z80
+}
+
+fn stacked_pc(z80: &Z80<TestBus>) -> u16 {
+    let sp = z80.SP();
+    ((z80.peek(sp.wrapping_add(1)) as u16) << 8) | z80.peek(sp) as u16
+}
+
+#[test]
+fn im1_calls_0x38() {
+    let mut z80 = interrupt_z80(&[0x00, 0x00], 1);
+    z80.bus_mut().int = true;
+    z80.do_opcode();
+    let regs = z80.registers();
+    assert_eq!(regs.pc, 0x38);
+    assert_eq!((regs.iff1, regs.iff2), (0, 0));
+    assert_eq!(stacked_pc(&z80), 0);
+}
+
+#[test]
+fn im2_jumps_through_the_vector_of_the_data_bus() {
+    let mut z80 = interrupt_z80(&[0x00], 2);
+    let mut regs = z80.registers();
+    regs.i = 0x80;
+    z80.set_registers(&regs);
+    z80.poke(0x8010, 0x34);
+    z80.poke(0x8011, 0x12);
+    z80.bus_mut().int = true;
+    z80.bus_mut().data_bus.push_back(0x10);
+    z80.do_opcode();
+    assert_eq!(z80.PC(), 0x1234);
+    assert_eq!(stacked_pc(&z80), 0);
+}
+
+#[test]
+fn im0_executes_the_instruction_of_the_data_bus() {
+    // RST 10h
+    let mut z80 = interrupt_z80(&[0x00], 0);
+    z80.bus_mut().int = true;
+    z80.bus_mut().data_bus.push_back(0xd7);
+    z80.do_opcode();
+    assert_eq!(z80.PC(), 0x10);
+    assert_eq!(stacked_pc(&z80), 0);
+
+    // CALL 4000h, with the address read by two more acknowledge cycles
+    let mut z80 = interrupt_z80(&[0x00], 0);
+    z80.bus_mut().int = true;
+    z80.bus_mut().data_bus.extend([0xcd, 0x00, 0x40]);
+    z80.do_opcode();
+    assert_eq!(z80.PC(), 0x4000);
+    assert_eq!(stacked_pc(&z80), 0);
+
+    // Nothing on the bus reads 0xff, RST 38h
+    let mut z80 = interrupt_z80(&[0x00], 0);
+    z80.bus_mut().int = true;
+    z80.do_opcode();
+    assert_eq!(z80.PC(), 0x38);
+}
+
+#[test]
+fn ei_takes_effect_after_the_next_instruction() {
+    // NOP, EI, NOP, NOP with INT held while interrupts are disabled
+    let mut z80 = interrupt_z80(&[0x00, 0xfb, 0x00, 0x00], 1);
+    let mut regs = z80.registers();
+    (regs.iff1, regs.iff2) = (0, 0);
+    z80.set_registers(&regs);
+    z80.bus_mut().int = true;
+    for pc in [1, 2, 3] {
+        z80.do_opcode();
+        assert_eq!(z80.PC(), pc);
+    }
+    z80.do_opcode();
+    assert_eq!(z80.PC(), 0x38);
+    assert_eq!(stacked_pc(&z80), 3);
+}
+
+#[test]
+fn halt_waits_for_an_interrupt() {
+    let mut z80 = interrupt_z80(&[0x76, 0x00], 1);
+    for _ in 0..3 {
+        z80.do_opcode();
+        assert!(z80.is_halted());
+    }
+    assert_eq!(z80.PC(), 0);
+    z80.bus_mut().int = true;
+    z80.do_opcode();
+    assert!(!z80.is_halted());
+    assert_eq!(z80.PC(), 0x38);
+    // RETI goes back after the HALT
+    assert_eq!(stacked_pc(&z80), 1);
+}
+
+// Address of the RET the BDOS entry jumps to, also the top of the memory
+// that programs read from 0x0006
+const BDOS: u16 = 0xfe00;
+
+// Runs a CP/M program at 0x100 until it jumps to 0, with a BDOS that only
+// implements the console output: C=2 prints E and C=9 prints the string
+// at DE up to '$'
+fn run_cpm(program: &[u8], echo: bool) -> String {
+    let mut z80 = new_z80();
+    for (i, b) in program.iter().enumerate() {
+        z80.poke(0x100 + i as u16, *b);
+    }
+    for (i, b) in [0xc3, BDOS as u8, (BDOS >> 8) as u8].iter().enumerate() {
+        z80.poke(5 + i as u16, *b);
+    }
+    z80.poke(BDOS, 0xc9);
+    // A RET from the program goes back to 0
+    z80.SetSP(BDOS - 2);
+    z80.SetPC(0x100);
+
+    let mut output = String::new();
+    loop {
+        match z80.PC() {
+            0x0000 => break,
+            0x0005 => {
+                let regs = z80.registers();
+                let start = output.len();
+                match regs.bc as u8 {
+                    2 => output.push(regs.de as u8 as char),
+                    9 => {
+                        let mut address = regs.de;
+                        while z80.peek(address) != b'$' {
+                            output.push(z80.peek(address) as char);
+                            address = address.wrapping_add(1);
+                        }
+                    }
+                    c => panic!("BDOS function {} not implemented", c),
+                }
+                if echo {
+                    print!("{}", &output[start..]);
+                    io::stdout().flush().unwrap();
+                }
+            }
+            _ => {}
+        }
+        z80.do_opcode();
+    }
+    output
+}
+
+#[test]
+fn cpm_prints_strings_and_characters() {
+    let program = [
+        0x0e, 0x09, // LD C,9
+        0x11, 0x12, 0x01, // LD DE,msg
+        0xcd, 0x05, 0x00, // CALL 5
+        0x0e, 0x02, // LD C,2
+        0x1e, b'!', // LD E,'!'
+        0xcd, 0x05, 0x00, // CALL 5
+        0xc3, 0x00, 0x00, // JP 0
+        b'Z', b'8', b'0', b'$', // msg
+    ];
+    assert_eq!(run_cpm(&program, false), "Z80!");
+}
+
+#[test]
+fn cpm_program_returns_to_the_system() {
+    let program = [
+        0x06, 0x05, // LD B,5
+        0xc5, // loop: PUSH BC
+        0x0e, 0x02, // LD C,2
+        0x3e, b'0', // LD A,'0'
+        0x80, // ADD A,B
+        0x5f, // LD E,A
+        0xcd, 0x05, 0x00, // CALL 5
+        0xc1, // POP BC
+        0x10, 0xf3, // DJNZ loop
+        0xc9, // RET
+    ];
+    assert_eq!(run_cpm(&program, false), "54321");
+}
+
+// The exercisers are not distributed with the emulator. Copy zexdoc.com and
+// zexall.com to tests/z80 and run them with
+// cargo test --release --test z80_test -- --ignored
+fn run_exerciser(name: &str) {
+    let path = format!("{}/tests/z80/{}", env!("CARGO_MANIFEST_DIR"), name);
+    let program = fs::read(&path).unwrap_or_else(|e| panic!("{}: {}", path, e));
+    let output = run_cpm(&program, true);
+    assert!(!output.contains("ERROR"), "{}", output);
+    assert!(output.contains("Tests complete"), "{}", output);
+}
+
+#[test]
+#[ignore = "needs tests/z80/zexdoc.com"]
+fn zexdoc() {
+    run_exerciser("zexdoc.com");
+}
+
+#[test]
+#[ignore = "needs tests/z80/zexall.com"]
+fn zexall() {
+    run_exerciser("zexall.com");
+}